@@ -0,0 +1,4 @@
+ALTER TABLE sessions
+    ADD COLUMN user_agent VARCHAR(255) NULL,
+    ADD COLUMN ip_address VARCHAR(45) NULL,
+    ADD COLUMN last_seen_at TIMESTAMP NULL;
@@ -0,0 +1,20 @@
+CREATE TABLE passkeys (
+    id BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    user_id BIGINT UNSIGNED NOT NULL,
+    credential_id VARCHAR(255) NOT NULL UNIQUE,
+    public_key BLOB NOT NULL,
+    sign_count INT UNSIGNED NOT NULL DEFAULT 0,
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP NULL,
+    INDEX idx_passkeys_user (user_id)
+);
+
+CREATE TABLE webauthn_challenges (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    user_id BIGINT UNSIGNED NULL,
+    kind VARCHAR(16) NOT NULL,
+    challenge VARCHAR(64) NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    INDEX idx_webauthn_challenges_expires (expires_at)
+);
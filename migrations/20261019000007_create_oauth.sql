@@ -0,0 +1,32 @@
+CREATE TABLE oauth_clients (
+    id VARCHAR(64) NOT NULL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    secret_hash VARCHAR(255) NULL,
+    redirect_uris TEXT NOT NULL,
+    grant_types VARCHAR(255) NOT NULL,
+    scopes VARCHAR(255) NOT NULL,
+    owner_id BIGINT UNSIGNED NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_oauth_clients_owner (owner_id)
+);
+
+CREATE TABLE oauth_codes (
+    code_hash CHAR(64) NOT NULL PRIMARY KEY,
+    client_id VARCHAR(64) NOT NULL,
+    user_id BIGINT UNSIGNED NOT NULL,
+    redirect_uri VARCHAR(2048) NOT NULL,
+    scope VARCHAR(255) NOT NULL,
+    code_challenge VARCHAR(128) NOT NULL,
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE TABLE oauth_tokens (
+    jti CHAR(36) NOT NULL PRIMARY KEY,
+    client_id VARCHAR(64) NOT NULL,
+    user_id BIGINT UNSIGNED NULL,
+    scope VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL,
+    revoked_at TIMESTAMP NULL,
+    INDEX idx_oauth_tokens_client (client_id)
+);
@@ -0,0 +1,3 @@
+-- presisi milidetik agar ETag berubah walaupun dua update terjadi di detik yang sama
+ALTER TABLE users
+    MODIFY updated_at TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3) ON UPDATE CURRENT_TIMESTAMP(3);
@@ -0,0 +1,4 @@
+-- FULLTEXT untuk kata bebas di GET /users/search, index created_at untuk filter tanggal
+ALTER TABLE users
+    ADD FULLTEXT INDEX users_name_email_fulltext (name, email),
+    ADD INDEX users_created_at_idx (created_at);
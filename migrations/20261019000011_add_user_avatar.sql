@@ -0,0 +1,4 @@
+-- avatar_key: prefix blob di BlobStore (internal), avatar_url: URL thumbnail terbesar
+ALTER TABLE users
+    ADD COLUMN avatar_key VARCHAR(255) NULL,
+    ADD COLUMN avatar_url VARCHAR(1024) NULL;
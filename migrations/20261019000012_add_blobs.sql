@@ -0,0 +1,23 @@
+-- blob disimpan berdasarkan hash isi sehingga file yang sama hanya tersimpan sekali
+CREATE TABLE blobs (
+    blob_key VARCHAR(255) NOT NULL PRIMARY KEY,
+    sha256 CHAR(64) NOT NULL,
+    byte_size BIGINT UNSIGNED NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_blobs_last_used (last_used_at)
+);
+
+-- referensi user ke blob per kegunaan (avatar_64, avatar_128, ...), blob tanpa referensi dihapus GC
+CREATE TABLE user_blobs (
+    user_id BIGINT UNSIGNED NOT NULL,
+    purpose VARCHAR(50) NOT NULL,
+    blob_key VARCHAR(255) NOT NULL,
+    PRIMARY KEY (user_id, purpose),
+    INDEX idx_user_blobs_key (blob_key)
+);
+
+-- avatar lama (prefix per upload) diganti referensi di user_blobs, URL lama tidak dilayani lagi
+UPDATE users SET avatar_url = NULL WHERE avatar_key IS NOT NULL;
+ALTER TABLE users DROP COLUMN avatar_key;
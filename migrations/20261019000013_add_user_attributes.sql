@@ -0,0 +1,18 @@
+-- atribut profil tambahan yang didefinisikan admin, aturan validasi ikut disimpan di sini
+CREATE TABLE attribute_definitions (
+    name VARCHAR(64) NOT NULL PRIMARY KEY,
+    label VARCHAR(255) NOT NULL,
+    kind VARCHAR(16) NOT NULL,
+    required BOOLEAN NOT NULL DEFAULT FALSE,
+    min_length INT UNSIGNED NULL,
+    max_length INT UNSIGNED NULL,
+    pattern VARCHAR(255) NULL,
+    minimum DOUBLE NULL,
+    maximum DOUBLE NULL,
+    choices JSON NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+);
+
+-- nilai atribut per user, key sama dengan attribute_definitions.name
+ALTER TABLE users ADD COLUMN attributes JSON NOT NULL DEFAULT (JSON_OBJECT());
@@ -0,0 +1,26 @@
+-- catatan audit hanya ditambah, tidak pernah diubah atau dihapus aplikasi
+-- hash = sha256(isi entri + prev_hash), perubahan/penghapusan baris memutus rantai dan terdeteksi lewat /audit/verify
+CREATE TABLE audit_log (
+    id BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    created_at DATETIME(3) NOT NULL,
+    action VARCHAR(32) NOT NULL,
+    actor_type VARCHAR(16) NOT NULL,
+    actor_id VARCHAR(255) NULL,
+    target_id BIGINT UNSIGNED NULL,
+    changes JSON NULL,
+    ip_address VARCHAR(45) NULL,
+    user_agent VARCHAR(255) NULL,
+    prev_hash CHAR(64) NOT NULL,
+    hash CHAR(64) NOT NULL,
+    INDEX idx_audit_created (created_at),
+    INDEX idx_audit_target (target_id, id),
+    INDEX idx_audit_actor (actor_type, actor_id, id)
+);
+
+-- ujung rantai, dikunci FOR UPDATE agar entri ditulis berurutan walau request berjalan paralel
+CREATE TABLE audit_chain (
+    id TINYINT UNSIGNED NOT NULL PRIMARY KEY,
+    last_hash CHAR(64) NOT NULL
+);
+
+INSERT INTO audit_chain (id, last_hash) VALUES (1, REPEAT('0', 64));
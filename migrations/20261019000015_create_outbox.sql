@@ -0,0 +1,20 @@
+-- event domain ditulis di transaksi yang sama dengan perubahan user, dikirim ke sink oleh dispatcher
+-- event dikirim minimal sekali: consumer harus mengabaikan event_id yang sudah pernah diterima
+CREATE TABLE outbox_events (
+    id BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    event_id CHAR(36) NOT NULL,
+    event_type VARCHAR(32) NOT NULL,
+    user_id BIGINT UNSIGNED NOT NULL,
+    payload JSON NOT NULL,
+    created_at DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    -- status pengiriman: attempts bertambah setiap kali event diklaim dispatcher
+    attempts INT UNSIGNED NOT NULL DEFAULT 0,
+    next_attempt_at DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    last_error TEXT NULL,
+    published_at DATETIME(3) NULL,
+    -- terisi jika percobaan sudah habis, event tidak dicoba lagi
+    failed_at DATETIME(3) NULL,
+    UNIQUE INDEX idx_outbox_event_id (event_id),
+    INDEX idx_outbox_pending (published_at, failed_at, next_attempt_at),
+    INDEX idx_outbox_user (user_id, id)
+);
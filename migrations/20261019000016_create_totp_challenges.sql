@@ -0,0 +1,9 @@
+-- percobaan kode per challenge token (jti), challenge ditolak setelah dipakai atau setelah batas percobaan
+CREATE TABLE totp_challenges (
+    jti CHAR(36) NOT NULL PRIMARY KEY,
+    user_id BIGINT UNSIGNED NOT NULL,
+    attempts INT UNSIGNED NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP NULL,
+    INDEX idx_totp_challenges_user (user_id)
+);
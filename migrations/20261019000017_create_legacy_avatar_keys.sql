@@ -0,0 +1,6 @@
+-- prefix avatar lama per user. migrasi 0012 menghapus users.avatar_key, isinya disalin ke sini
+-- oleh db::run_migrations sebelum 0012 dijalankan. baris dihapus setelah dipindah job legacy_avatars
+CREATE TABLE IF NOT EXISTS legacy_avatar_keys (
+    user_id BIGINT UNSIGNED NOT NULL PRIMARY KEY,
+    avatar_key VARCHAR(255) NOT NULL
+);
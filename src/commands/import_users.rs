@@ -0,0 +1,95 @@
+use serde::Deserialize;
+
+use crate::{
+    configs::db,
+    controllers::user_controller::insert_new_user,
+    errors::app_error::AppError,
+    models::{attribute_model::AttributeValues, audit_model::ActorKind},
+    utils::{audit::AuditContext, client_info::ClientInfo, legacy_hash::is_supported_hash, search_index::reindex_all, utils::validate_email_tld},
+};
+
+//satu user per baris (NDJSON), password_hash disimpan apa adanya dan di-rehash saat login pertama
+#[derive(Deserialize, Debug)]
+pub struct ImportedUser {
+    pub name: String,
+    pub email: String,
+    pub password_hash: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub skipped: usize,
+    pub invalid: usize,
+}
+
+pub fn parse_line(line: &str) -> Result<ImportedUser, String> {
+    let user: ImportedUser = serde_json::from_str(line).map_err(|e| format!("JSON tidak valid: {}", e))?;
+    let user = ImportedUser {
+        name: user.name.trim().to_string(),
+        email: user.email.trim().to_lowercase(),
+        password_hash: user.password_hash.trim().to_string(),
+    };
+
+    if user.name.chars().count() < 3 {
+        return Err("nama minimal 3 karakter".to_string());
+    }
+    if validate_email_tld(&user.email).is_err() {
+        return Err(format!("email {} tidak valid", user.email));
+    }
+    if !is_supported_hash(&user.password_hash) {
+        return Err("format password_hash tidak didukung".to_string());
+    }
+    Ok(user)
+}
+
+pub async fn import_users(path: &str, dry_run: bool) -> Result<(), AppError> {
+    let content = tokio::fs::read_to_string(path).await.map_err(|e| {
+        eprintln!("IMPORT: gagal membaca {}: {:?}", path, e);
+        AppError::BadRequest
+    })?;
+    let pool = db::get_pool().await?;
+    let ctx = AuditContext { actor_type: ActorKind::System, actor_id: Some("import-users".to_string()), client: ClientInfo::default() };
+    let mut summary = ImportSummary::default();
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let user = match parse_line(line) {
+            Ok(user) => user,
+            Err(reason) => {
+                eprintln!("IMPORT: baris {}: {}", index + 1, reason);
+                summary.invalid += 1;
+                continue;
+            }
+        };
+
+        let exists: Option<(u64,)> = sqlx::query_as("SELECT id FROM users WHERE email = ?")
+            .bind(&user.email)
+            .fetch_optional(&pool)
+            .await?;
+        if exists.is_some() {
+            summary.skipped += 1;
+            continue;
+        }
+
+        //dicatat di audit log seperti user yang dibuat lewat API
+        if !dry_run {
+            let mut tx = pool.begin().await?;
+            insert_new_user(&mut tx, &user.name, &user.email, Some(&user.password_hash), AttributeValues::new(), &ctx).await?;
+            tx.commit().await?;
+        }
+        summary.imported += 1;
+    }
+
+    println!(
+        "{}{} user diimport, {} dilewati (email sudah ada), {} tidak valid",
+        if dry_run { "[dry run] " } else { "" },
+        summary.imported, summary.skipped, summary.invalid
+    );
+    if !dry_run && summary.imported > 0 {
+        reindex_all().await;
+    }
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use crate::{configs::db, errors::app_error::AppError, jobs::{blob_gc::gc_now, purge_users::purge_now}, utils::search_index::{rebuild_index, user_index}};
+
+pub mod import_users;
+
+const USAGE: &str = "usage: backend [import-users <file.ndjson> [--dry-run] | purge-users | gc-blobs | rebuild-search-index]";
+
+//perintah CLI dijalankan sebelum server, tanpa argumen server berjalan seperti biasa
+pub async fn run(command: &str, args: &[String]) -> Result<(), AppError> {
+    match command {
+        "import-users" => {
+            let path = args.first().ok_or_else(|| {
+                eprintln!("{}", USAGE);
+                AppError::BadRequest
+            })?;
+            let dry_run = args.iter().any(|a| a == "--dry-run");
+            import_users::import_users(path, dry_run).await
+        }
+        "purge-users" => purge_now().await,
+        "gc-blobs" => gc_now().await,
+        //index dibangun ulang dari tabel users, aman dijalankan kapan saja
+        "rebuild-search-index" => {
+            let count = rebuild_index(&db::get_pool().await?, user_index()?).await?;
+            println!("{} user diindex", count);
+            Ok(())
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            Err(AppError::BadRequest)
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::time::Duration;
 use sqlx::{Error, MySql, Pool, mysql::MySqlPoolOptions};
-use crate::{ errors::app_error::AppError, utils::utils::load_config};
+use crate::{ errors::app_error::AppError, jobs::legacy_avatars::preserve_avatar_keys, utils::utils::load_config};
 
 
 async fn create_pool(url: String) -> Result<Pool<MySql>, Error> {
@@ -25,4 +25,14 @@ pub async  fn get_pool()-> Result<Pool<MySql>, AppError>{
 
     let database_url = format!("mysql://{}:{}@{}:{}/{}", user, password, host, port, name);
     Ok(create_pool(database_url).await?)
-}
\ No newline at end of file
+}
+
+//menjalankan migrasi di folder migrations saat server start
+pub async fn run_migrations() -> Result<(), AppError> {
+    let pool = get_pool().await?;
+    //file migrasi yang sudah pernah dijalankan tidak boleh diubah (checksum sqlx),
+    //data yang dibuang migrasi lama diselamatkan di sini sebelum migrasi berjalan
+    preserve_avatar_keys(&pool).await?;
+    sqlx::migrate!().run(&pool).await?;
+    Ok(())
+}
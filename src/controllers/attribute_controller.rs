@@ -0,0 +1,96 @@
+use axum::{Json, extract::Path};
+use http::StatusCode;
+use sqlx::types::Json as SqlJson;
+
+use crate::{
+    configs::db,
+    errors::app_error::AppError,
+    models::attribute_model::{AttributeDefinition, AttributeDefinitionInput},
+    utils::{attributes::{build_definition, is_valid_name, load_definitions}, search_index::reindex_all},
+};
+
+#[utoipa::path(
+    get, path = "/users/attributes", tag = "attributes", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    responses((status = 200, body = Vec<AttributeDefinition>), AppError)
+)]
+pub async fn list_attributes() -> Result<(StatusCode, Json<Vec<AttributeDefinition>>), AppError> {
+    let pool = db::get_pool().await?;
+    Ok((StatusCode::OK, Json(load_definitions(&pool).await?)))
+}
+
+//membuat atau mengganti definisi, nilai lama milik user tidak diubah dan divalidasi ulang saat user di-update
+#[utoipa::path(
+    put, path = "/users/attributes/{name}", tag = "attributes", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    params(("name" = String, Path, description = "Nama atribut: huruf kecil, angka dan _")),
+    request_body = AttributeDefinitionInput,
+    responses((status = 201, body = AttributeDefinition), (status = 200, body = AttributeDefinition), AppError)
+)]
+pub async fn put_attribute(Path(name): Path<String>, Json(payload): Json<AttributeDefinitionInput>) -> Result<(StatusCode, Json<AttributeDefinition>), AppError> {
+    let definition = build_definition(&name, payload).map_err(AppError::ValidationError)?;
+
+    let pool = db::get_pool().await?;
+    let mut tx = pool.begin().await?;
+    //rows_affected tidak bisa dipakai: dengan CLIENT_FOUND_ROWS upsert tanpa perubahan tetap bernilai 1
+    let existed: Option<(String,)> = sqlx::query_as("SELECT name FROM attribute_definitions WHERE name = ? FOR UPDATE")
+        .bind(&definition.name)
+        .fetch_optional(&mut *tx)
+        .await?;
+    sqlx::query(
+        "INSERT INTO attribute_definitions (name, label, kind, required, min_length, max_length, pattern, minimum, maximum, choices) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?) AS new \
+         ON DUPLICATE KEY UPDATE label = new.label, kind = new.kind, required = new.required, min_length = new.min_length, \
+         max_length = new.max_length, pattern = new.pattern, minimum = new.minimum, maximum = new.maximum, choices = new.choices"
+    )
+        .bind(&definition.name)
+        .bind(&definition.label)
+        .bind(definition.kind.as_str())
+        .bind(definition.required)
+        .bind(definition.min_length)
+        .bind(definition.max_length)
+        .bind(&definition.pattern)
+        .bind(definition.minimum)
+        .bind(definition.maximum)
+        .bind(definition.choices.as_ref().map(SqlJson))
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    let status = if existed.is_some() { StatusCode::OK } else { StatusCode::CREATED };
+    Ok((status, Json(definition)))
+}
+
+//nilai atribut ikut dihapus dari semua user
+#[utoipa::path(
+    delete, path = "/users/attributes/{name}", tag = "attributes", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    params(("name" = String, Path, description = "Nama atribut")),
+    responses((status = 204), AppError)
+)]
+pub async fn delete_attribute(Path(name): Path<String>) -> Result<StatusCode, AppError> {
+    if !is_valid_name(&name) {
+        return Err(AppError::NotFound);
+    }
+
+    let pool = db::get_pool().await?;
+    let mut tx = pool.begin().await?;
+    let deleted = sqlx::query("DELETE FROM attribute_definitions WHERE name = ?")
+        .bind(&name)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+    let path = format!("$.{}", name);
+    let changed = sqlx::query("UPDATE users SET attributes = JSON_REMOVE(attributes, ?) WHERE JSON_CONTAINS_PATH(attributes, 'one', ?)")
+        .bind(&path)
+        .bind(&path)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+
+    if changed > 0 {
+        reindex_all().await;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
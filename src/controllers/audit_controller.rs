@@ -0,0 +1,77 @@
+use axum::{Json, extract::Query};
+use http::StatusCode;
+use sqlx::{MySql, QueryBuilder};
+
+use crate::{
+    configs::db,
+    errors::app_error::AppError,
+    models::audit_model::{AuditEntry, AuditPage, AuditQuery, AuditVerification},
+    utils::audit::verify_chain,
+};
+
+const DEFAULT_PER_PAGE: u64 = 50;
+const MAX_PER_PAGE: u64 = 100;
+
+fn push_filters(builder: &mut QueryBuilder<'_, MySql>, query: &AuditQuery) {
+    builder.push(" WHERE TRUE");
+    if let Some(action) = query.action {
+        builder.push(" AND action = ").push_bind(action.as_str());
+    }
+    if let Some(actor_type) = query.actor_type {
+        builder.push(" AND actor_type = ").push_bind(actor_type.as_str());
+    }
+    if let Some(actor_id) = &query.actor_id {
+        builder.push(" AND actor_id = ").push_bind(actor_id.clone());
+    }
+    if let Some(target_id) = query.target_id {
+        builder.push(" AND target_id = ").push_bind(target_id);
+    }
+    if let Some(from) = query.from {
+        builder.push(" AND created_at >= ").push_bind(from);
+    }
+    if let Some(to) = query.to {
+        builder.push(" AND created_at < ").push_bind(to);
+    }
+}
+
+pub fn audit_count_query<'a>(query: &AuditQuery) -> QueryBuilder<'a, MySql> {
+    let mut builder = QueryBuilder::new("SELECT COUNT(*) FROM audit_log");
+    push_filters(&mut builder, query);
+    builder
+}
+
+pub fn audit_select_query<'a>(query: &AuditQuery, offset: u64, limit: u64) -> QueryBuilder<'a, MySql> {
+    let mut builder = QueryBuilder::new("SELECT * FROM audit_log");
+    push_filters(&mut builder, query);
+    builder.push(" ORDER BY id DESC LIMIT ").push_bind(limit).push(" OFFSET ").push_bind(offset);
+    builder
+}
+
+#[utoipa::path(
+    get, path = "/audit", tag = "audit", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    params(AuditQuery),
+    responses((status = 200, body = AuditPage), AppError)
+)]
+pub async fn list_audit(Query(query): Query<AuditQuery>) -> Result<(StatusCode, Json<AuditPage>), AppError> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE);
+
+    let pool = db::get_pool().await?;
+    let (total,): (i64,) = audit_count_query(&query).build_query_as().fetch_one(&pool).await?;
+    let items: Vec<AuditEntry> = audit_select_query(&query, (page - 1).saturating_mul(per_page), per_page)
+        .build_query_as()
+        .fetch_all(&pool)
+        .await?;
+
+    Ok((StatusCode::OK, Json(AuditPage { items, total: total as u64, page, per_page })))
+}
+
+//menghitung ulang seluruh rantai hash, valid = false berarti ada entri yang diubah atau dihapus
+#[utoipa::path(
+    get, path = "/audit/verify", tag = "audit", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    responses((status = 200, body = AuditVerification), AppError)
+)]
+pub async fn verify_audit() -> Result<(StatusCode, Json<AuditVerification>), AppError> {
+    let pool = db::get_pool().await?;
+    Ok((StatusCode::OK, Json(verify_chain(&pool).await?)))
+}
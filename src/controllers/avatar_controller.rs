@@ -0,0 +1,174 @@
+use std::{collections::BTreeMap, io::Cursor, time::Duration};
+use axum::{Extension, Json, extract::{Multipart, Path}, response::{IntoResponse, Redirect, Response}};
+use http::{StatusCode, header};
+use image::{ImageError, ImageFormat, ImageReader, Limits, imageops::FilterType};
+use serde_json::json;
+
+use crate::{
+    configs::db,
+    errors::app_error::AppError,
+    models::{audit_model::AuditAction, avatar_model::{AvatarResponse, AvatarUpload}, config_model::{AvatarConfig, BlobBackendKind}, outbox_model::DomainEventKind, user_model::{Claims, User}},
+    utils::{
+        audit::{AuditContext, append, user_changes},
+        blob_store::{BlobStore, blob_store, is_valid_key, save_blob},
+        outbox::enqueue,
+        utils::load_config,
+    },
+};
+
+pub const AVATAR_FIELD: &str = "avatar";
+pub const AVATAR_PREFIX: &str = "avatars";
+
+//tipe ditentukan dari magic bytes, Content-Type dari client tidak dipercaya
+pub fn sniff_image(data: &[u8]) -> Result<ImageFormat, AppError> {
+    match image::guess_format(data) {
+        Ok(format @ (ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP | ImageFormat::Gif)) => Ok(format),
+        _ => Err(AppError::UnsupportedMediaType),
+    }
+}
+
+//thumbnail persegi PNG per ukuran di config, gambar dipotong di tengah agar tidak gepeng
+pub fn make_thumbnails(data: &[u8], format: ImageFormat, cfg: &AvatarConfig) -> Result<Vec<(u32, Vec<u8>)>, AppError> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(cfg.max_dimension);
+    limits.max_image_height = Some(cfg.max_dimension);
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+    let image = reader.decode().map_err(|e| match e {
+        ImageError::Limits(_) => AppError::PayloadTooLarge,
+        _ => AppError::BadRequest,
+    })?;
+
+    let mut sizes: Vec<u32> = cfg.sizes.iter().copied().filter(|s| *s > 0).collect();
+    sizes.sort();
+    sizes.dedup();
+    sizes.into_iter()
+        .map(|size| {
+            let mut png = Vec::new();
+            image.resize_to_fill(size, size, FilterType::Lanczos3)
+                .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+                .map_err(|_| AppError::InternalServerError)?;
+            Ok((size, png))
+        })
+        .collect()
+}
+
+//body dibaca per chunk agar file yang terlalu besar ditolak tanpa dimuat utuh
+async fn read_avatar(multipart: &mut Multipart, max_bytes: usize) -> Result<Vec<u8>, AppError> {
+    while let Some(mut field) = multipart.next_field().await.map_err(|_| AppError::BadRequest)? {
+        if field.name() != Some(AVATAR_FIELD) {
+            continue;
+        }
+        let mut data = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(|_| AppError::BadRequest)? {
+            if data.len() + chunk.len() > max_bytes {
+                return Err(AppError::PayloadTooLarge);
+            }
+            data.extend_from_slice(&chunk);
+        }
+        return Ok(data);
+    }
+    Err(AppError::BadRequest)
+}
+
+#[utoipa::path(
+    put, path = "/me/avatar", tag = "me", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    request_body(content = AvatarUpload, content_type = "multipart/form-data"),
+    responses((status = 200, body = AvatarResponse), AppError)
+)]
+pub async fn upload_avatar(ctx: AuditContext, Extension(claims): Extension<Claims>, mut multipart: Multipart) -> Result<(StatusCode, Json<AvatarResponse>), AppError> {
+    let cfg = load_config()?;
+    let data = read_avatar(&mut multipart, cfg.avatar.max_bytes).await?;
+    let format = sniff_image(&data)?;
+    let avatar_cfg = cfg.avatar.clone();
+    let thumbnails = tokio::task::spawn_blocking(move || make_thumbnails(&data, format, &avatar_cfg))
+        .await
+        .map_err(|_| AppError::InternalServerError)??;
+
+    //key berbasis hash isi sehingga URL bisa di-cache selamanya dan upload ulang file yang sama tidak menambah blob
+    let store = blob_store(&cfg.blob_store)?;
+    let pool = db::get_pool().await?;
+    let mut keys = Vec::with_capacity(thumbnails.len());
+    for (size, png) in thumbnails {
+        keys.push((size, save_blob(&store, &pool, AVATAR_PREFIX, "png", "image/png", png).await?));
+    }
+    let urls: BTreeMap<u32, String> = keys.iter().map(|(size, key)| (*size, store.url(key))).collect();
+    let avatar_url = urls.values().next_back().cloned().ok_or(AppError::InternalServerError)?;
+
+    //blob avatar lama tidak dihapus di sini, GC menghapusnya setelah tidak ada referensi
+    let mut tx = pool.begin().await?;
+    let before = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ? AND deleted_at IS NULL FOR UPDATE")
+        .bind(claims.sub)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    sqlx::query("DELETE FROM user_blobs WHERE user_id = ? AND purpose LIKE 'avatar\\_%'")
+        .bind(claims.sub)
+        .execute(&mut *tx)
+        .await?;
+    for (size, key) in &keys {
+        sqlx::query("INSERT INTO user_blobs (user_id, purpose, blob_key) VALUES (?, ?, ?)")
+            .bind(claims.sub)
+            .bind(format!("avatar_{}", size))
+            .bind(key)
+            .execute(&mut *tx)
+            .await?;
+    }
+    sqlx::query("UPDATE users SET avatar_url = ? WHERE id = ?")
+        .bind(&avatar_url)
+        .bind(claims.sub)
+        .execute(&mut *tx)
+        .await?;
+    //avatar lama yang belum dipindah job legacy_avatars ikut ditinggalkan
+    sqlx::query("DELETE FROM legacy_avatar_keys WHERE user_id = ?")
+        .bind(claims.sub)
+        .execute(&mut *tx)
+        .await?;
+    let after = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
+        .bind(claims.sub)
+        .fetch_one(&mut *tx)
+        .await?;
+    //sama dengan update_user_in: event dan audit hanya jika avatar_url berubah
+    if let Some(changes) = user_changes(Some(&before), Some(&after)) {
+        let data = json!({ "user": after, "changed": changes.keys().collect::<Vec<_>>() });
+        enqueue(&mut tx, DomainEventKind::UserUpdated, claims.sub, data).await?;
+        append(&mut tx, &ctx, AuditAction::UserUpdate, Some(claims.sub), Some(changes)).await?;
+    }
+    tx.commit().await?;
+
+    Ok((StatusCode::OK, Json(AvatarResponse { avatar_url, thumbnails: urls })))
+}
+
+//dipakai jika blob_store.public_url kosong; backend local dilayani langsung, S3 redirect ke presigned URL
+#[utoipa::path(
+    get, path = "/avatars/{file}", tag = "me",
+    params(("file" = String, Path, description = "{sha256}.png")),
+    responses(
+        (status = 200, content_type = "image/png", body = Vec<u8>),
+        (status = 307, description = "Redirect ke presigned URL (backend s3)"),
+        AppError
+    )
+)]
+pub async fn get_avatar(Path(file): Path<String>) -> Result<Response, AppError> {
+    let key = format!("{}/{}", AVATAR_PREFIX, file);
+    if !is_valid_key(&key) {
+        return Err(AppError::NotFound);
+    }
+    let cfg = load_config()?.blob_store;
+    let store = blob_store(&cfg)?;
+    if cfg.backend == BlobBackendKind::S3 {
+        let url = store.presigned_url(&key, Duration::from_secs(cfg.presign_ttl))?;
+        return Ok(Redirect::temporary(&url).into_response());
+    }
+    let blob = store.get(&key).await?.ok_or(AppError::NotFound)?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, blob.content_type),
+            (header::CACHE_CONTROL, "public, max-age=31536000, immutable".to_string()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        blob.data,
+    ).into_response())
+}
@@ -0,0 +1,15 @@
+use axum::Json;
+use http::StatusCode;
+use jsonwebtoken::jwk::JwkSet;
+
+use crate::{errors::app_error::AppError, utils::utils::load_jwt_keys};
+
+//daftar public key untuk service lain yang ingin memverifikasi token kita
+#[utoipa::path(
+    get, path = "/.well-known/jwks.json", tag = "keys",
+    responses((status = 200, body = Object, description = "JWK Set kunci publik untuk verifikasi jwt"), AppError)
+)]
+pub async fn jwks() -> Result<(StatusCode, Json<JwkSet>), AppError> {
+    let keys = load_jwt_keys()?;
+    Ok((StatusCode::OK, Json(keys.jwks())))
+}
@@ -1 +1,13 @@
-pub mod user_controller;
\ No newline at end of file
+pub mod user_controller;
+pub mod key_controller;
+pub mod session_controller;
+pub mod totp_controller;
+pub mod passkey_controller;
+pub mod oidc_controller;
+pub mod oauth_controller;
+pub mod user_v2_controller;
+pub mod user_transfer_controller;
+pub mod user_batch_controller;
+pub mod avatar_controller;
+pub mod attribute_controller;
+pub mod audit_controller;
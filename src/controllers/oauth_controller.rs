@@ -0,0 +1,280 @@
+use axum::{Extension, Form, Json, extract::{Path, Query}, response::{IntoResponse, Redirect, Response}};
+use chrono::{Duration, Utc};
+use http::{HeaderMap, StatusCode, header};
+use sqlx::{MySql, Pool};
+use validator::Validate;
+
+use crate::{
+    configs::db,
+    errors::{app_error::AppError, oauth_error::OAuthError},
+    models::{
+        oauth_model::{AuthorizeQuery, GrantType, Introspection, OAuthClient, OAuthClientCreated, OAuthClientInsert, StoredClient, TokenForm, TokenRequest, TokenResponse},
+        user_model::Claims,
+    },
+    utils::{
+        oauth::{authenticate_client, decode_access_token, find_client, grant_scope, hash_code, is_first_party, issue_access_token, redirect_with, validate_redirect_uri, verify_access_token},
+        oidc::{pkce_challenge, random_token},
+        utils::{hashing_password, load_config, load_jwt_keys},
+    },
+};
+
+#[utoipa::path(
+    post, path = "/oauth/clients", tag = "oauth", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), request_body = OAuthClientInsert,
+    responses((status = 201, body = OAuthClientCreated), AppError)
+)]
+pub async fn create_client(Extension(claims): Extension<Claims>, Json(payload): Json<OAuthClientInsert>) -> Result<(StatusCode, Json<OAuthClientCreated>), AppError> {
+    payload.validate().map_err(AppError::ValidationError)?;
+    let cfg = load_config()?.oauth;
+
+    let uses_code = payload.grant_types.contains(&GrantType::AuthorizationCode);
+    if uses_code && payload.redirect_uris.is_empty() {
+        return Err(AppError::BadRequest);
+    }
+    if !payload.redirect_uris.iter().all(|uri| validate_redirect_uri(uri)) {
+        return Err(AppError::BadRequest);
+    }
+    //client credentials hanya untuk client yang bisa menyimpan secret
+    if payload.grant_types.contains(&GrantType::ClientCredentials) && !payload.confidential {
+        return Err(AppError::BadRequest);
+    }
+    if payload.scopes.is_empty() || !payload.scopes.iter().all(|s| cfg.scopes.contains(s)) {
+        return Err(AppError::BadRequest);
+    }
+
+    let client_id = random_token();
+    let client_secret = payload.confidential.then(random_token);
+    let secret_hash = match client_secret.as_deref() {
+        Some(secret) => Some(hashing_password(secret).await?),
+        None => None,
+    };
+    let grant_types: Vec<&str> = payload.grant_types.iter().map(GrantType::as_str).collect();
+
+    let pool = db::get_pool().await?;
+    sqlx::query("INSERT INTO oauth_clients (id, name, secret_hash, redirect_uris, grant_types, scopes, owner_id) VALUES (?, ?, ?, ?, ?, ?, ?)")
+        .bind(&client_id)
+        .bind(payload.name.trim())
+        .bind(secret_hash)
+        .bind(payload.redirect_uris.join(" "))
+        .bind(grant_types.join(" "))
+        .bind(payload.scopes.join(" "))
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+
+    let client = find_client(&pool, &client_id).await?.ok_or(AppError::InternalServerError)?;
+    Ok((StatusCode::CREATED, Json(OAuthClientCreated { client: client.into(), client_secret })))
+}
+
+#[utoipa::path(
+    get, path = "/oauth/clients", tag = "oauth", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    responses((status = 200, body = Vec<OAuthClient>), AppError)
+)]
+pub async fn list_clients(Extension(claims): Extension<Claims>) -> Result<(StatusCode, Json<Vec<OAuthClient>>), AppError> {
+    let pool = db::get_pool().await?;
+    let clients = sqlx::query_as::<_, StoredClient>("SELECT id, name, secret_hash, redirect_uris, grant_types, scopes, created_at FROM oauth_clients WHERE owner_id = ? ORDER BY created_at DESC")
+        .bind(claims.sub)
+        .fetch_all(&pool)
+        .await?;
+    Ok((StatusCode::OK, Json(clients.into_iter().map(OAuthClient::from).collect())))
+}
+
+//menghapus client sekaligus mencabut semua token dan code miliknya
+#[utoipa::path(
+    delete, path = "/oauth/clients/{id}", tag = "oauth", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(("id" = String, Path, description = "client_id")),
+    responses((status = 204), AppError)
+)]
+pub async fn delete_client(Extension(claims): Extension<Claims>, Path(id): Path<String>) -> Result<StatusCode, AppError> {
+    let pool = db::get_pool().await?;
+    let mut tx = pool.begin().await?;
+    let result = sqlx::query("DELETE FROM oauth_clients WHERE id = ? AND owner_id = ?")
+        .bind(&id)
+        .bind(claims.sub)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound);
+    }
+    sqlx::query("UPDATE oauth_tokens SET revoked_at = NOW() WHERE client_id = ? AND revoked_at IS NULL")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM oauth_codes WHERE client_id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+//authorization endpoint: user yang sedang login langsung diberi code. tidak ada layar consent,
+//sebagai gantinya hanya client di oauth.first_party_clients yang boleh memakai alur ini;
+//client lain (misal didaftarkan user mana pun lewat POST /oauth/clients) mendapat unauthorized_client
+//sehingga tidak bisa diam-diam mendapat token atas nama user yang sedang login
+#[utoipa::path(
+    get, path = "/oauth/authorize", tag = "oauth", security(("session" = [])), params(AuthorizeQuery),
+    responses((status = 303, description = "Redirect ke redirect_uri dengan code atau error"), AppError)
+)]
+pub async fn authorize(Extension(claims): Extension<Claims>, Query(query): Query<AuthorizeQuery>) -> Result<Redirect, AppError> {
+    let cfg = load_config()?.oauth;
+    let pool = db::get_pool().await?;
+
+    //client atau redirect_uri tidak valid: jangan redirect ke uri yang belum diverifikasi
+    let client = find_client(&pool, &query.client_id).await?.ok_or(AppError::BadRequest)?;
+    let registered = client.redirect_uris();
+    let redirect_uri = match query.redirect_uri.as_deref() {
+        Some(uri) if registered.contains(&uri) => uri.to_string(),
+        None if registered.len() == 1 => registered[0].to_string(),
+        _ => return Err(AppError::BadRequest),
+    };
+
+    let state = query.state.as_deref().unwrap_or_default();
+    let fail = |error: &str| -> Result<Redirect, AppError> {
+        let mut params = vec![("error", error)];
+        if !state.is_empty() {
+            params.push(("state", state));
+        }
+        Ok(Redirect::to(&redirect_with(&redirect_uri, &params)?))
+    };
+
+    if query.response_type != "code" {
+        return fail("unsupported_response_type");
+    }
+    if !client.allows(GrantType::AuthorizationCode) || !is_first_party(&cfg, &client.id) {
+        return fail("unauthorized_client");
+    }
+    //PKCE S256 wajib untuk semua client
+    let Some(code_challenge) = query.code_challenge.as_deref().filter(|_| query.code_challenge_method.as_deref() == Some("S256")) else {
+        return fail("invalid_request");
+    };
+    let Ok(scope) = grant_scope(query.scope.as_deref(), &client.scopes(), &cfg.scopes) else {
+        return fail("invalid_scope");
+    };
+
+    let code = random_token();
+    sqlx::query("INSERT INTO oauth_codes (code_hash, client_id, user_id, redirect_uri, scope, code_challenge, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?)")
+        .bind(hash_code(&code))
+        .bind(&client.id)
+        .bind(claims.sub)
+        .bind(&redirect_uri)
+        .bind(&scope)
+        .bind(code_challenge)
+        .bind(Utc::now() + Duration::seconds(cfg.code_ttl))
+        .execute(&pool)
+        .await?;
+
+    let mut params = vec![("code", code.as_str())];
+    if !state.is_empty() {
+        params.push(("state", state));
+    }
+    Ok(Redirect::to(&redirect_with(&redirect_uri, &params)?))
+}
+
+//code hanya bisa ditukar sekali
+async fn take_code(pool: &Pool<MySql>, code: &str) -> Result<(String, u64, String, String, String), OAuthError> {
+    let code_hash = hash_code(code);
+    let row: Option<(String, u64, String, String, String)> = sqlx::query_as("SELECT client_id, user_id, redirect_uri, scope, code_challenge FROM oauth_codes WHERE code_hash = ? AND expires_at > NOW()")
+        .bind(&code_hash)
+        .fetch_optional(pool)
+        .await?;
+    let deleted = sqlx::query("DELETE FROM oauth_codes WHERE code_hash = ?")
+        .bind(&code_hash)
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM oauth_codes WHERE expires_at <= NOW()")
+        .execute(pool)
+        .await?;
+
+    match row {
+        Some(row) if deleted.rows_affected() > 0 => Ok(row),
+        _ => Err(OAuthError::InvalidGrant),
+    }
+}
+
+#[utoipa::path(
+    post, path = "/oauth/token", tag = "oauth", security(("oauth_client" = []), ()),
+    request_body(content = TokenRequest, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 200, body = TokenResponse), OAuthError)
+)]
+pub async fn token(headers: HeaderMap, Form(req): Form<TokenRequest>) -> Result<Response, OAuthError> {
+    let cfg = load_config()?.oauth;
+    let pool = db::get_pool().await?;
+    let client = authenticate_client(&pool, &headers, req.client_id.as_deref(), req.client_secret.as_deref()).await?;
+
+    let response = match req.grant_type.as_str() {
+        "client_credentials" => {
+            if !client.is_confidential() || !client.allows(GrantType::ClientCredentials) {
+                return Err(OAuthError::UnauthorizedClient);
+            }
+            let scope = grant_scope(req.scope.as_deref(), &client.scopes(), &cfg.scopes)?;
+            issue_access_token(&pool, &client.id, None, &scope).await?
+        }
+        "authorization_code" => {
+            if !client.allows(GrantType::AuthorizationCode) || !is_first_party(&cfg, &client.id) {
+                return Err(OAuthError::UnauthorizedClient);
+            }
+            let code = req.code.as_deref().ok_or(OAuthError::InvalidRequest)?;
+            let verifier = req.code_verifier.as_deref().ok_or(OAuthError::InvalidRequest)?;
+            let (client_id, user_id, redirect_uri, scope, challenge) = take_code(&pool, code).await?;
+
+            if client_id != client.id || req.redirect_uri.as_deref() != Some(redirect_uri.as_str()) || pkce_challenge(verifier) != challenge {
+                return Err(OAuthError::InvalidGrant);
+            }
+            issue_access_token(&pool, &client.id, Some(user_id), &scope).await?
+        }
+        _ => return Err(OAuthError::UnsupportedGrantType),
+    };
+
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)).into_response())
+}
+
+//introspection (RFC 7662), token tidak valid cukup dijawab active = false
+#[utoipa::path(
+    post, path = "/oauth/introspect", tag = "oauth", security(("oauth_client" = []), ()),
+    request_body(content = TokenForm, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 200, body = Introspection), OAuthError)
+)]
+pub async fn introspect(headers: HeaderMap, Form(form): Form<TokenForm>) -> Result<Json<Introspection>, OAuthError> {
+    let pool = db::get_pool().await?;
+    let client = authenticate_client(&pool, &headers, form.client_id.as_deref(), form.client_secret.as_deref()).await?;
+    if !client.is_confidential() {
+        return Err(OAuthError::UnauthorizedClient);
+    }
+
+    let keys = load_jwt_keys()?;
+    let introspection = match verify_access_token(&pool, &keys, &form.token).await {
+        Ok(claims) => Introspection {
+            active: true,
+            scope: Some(claims.scope),
+            client_id: Some(claims.client_id),
+            sub: Some(claims.sub),
+            exp: Some(claims.exp),
+            iat: Some(claims.iat),
+            token_type: Some("Bearer".to_string()),
+        },
+        Err(AppError::Unauthorized) => Introspection::default(),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(Json(introspection))
+}
+
+//revocation (RFC 7009), selalu 200 walaupun token tidak dikenal atau milik client lain
+#[utoipa::path(
+    post, path = "/oauth/revoke", tag = "oauth", security(("oauth_client" = []), ()),
+    request_body(content = TokenForm, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 200), OAuthError)
+)]
+pub async fn revoke(headers: HeaderMap, Form(form): Form<TokenForm>) -> Result<StatusCode, OAuthError> {
+    let pool = db::get_pool().await?;
+    let client = authenticate_client(&pool, &headers, form.client_id.as_deref(), form.client_secret.as_deref()).await?;
+
+    let keys = load_jwt_keys()?;
+    if let Ok(claims) = decode_access_token(&keys, &form.token) {
+        sqlx::query("UPDATE oauth_tokens SET revoked_at = NOW() WHERE jti = ? AND client_id = ? AND revoked_at IS NULL")
+            .bind(&claims.jti)
+            .bind(&client.id)
+            .execute(&pool)
+            .await?;
+    }
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,204 @@
+use axum::{Json, extract::{Path, Query}, response::Redirect};
+use axum_extra::extract::{CookieJar, cookie::Cookie};
+use chrono::{Duration, Utc};
+use http::StatusCode;
+use serde::Deserialize;
+use sqlx::{MySql, Pool};
+use subtle::ConstantTimeEq;
+use utoipa::IntoParams;
+
+use crate::{
+    configs::db,
+    controllers::user_controller::create_passwordless_user,
+    errors::app_error::AppError,
+    models::{audit_model::ActorKind, config_model::{OidcConfig, OidcProviderConfig}},
+    utils::{
+        audit::AuditContext,
+        client_info::ClientInfo,
+        oidc::{IdTokenClaims, authorization_url, discover, exchange_code, fetch_jwks, http_client, random_token, verify_id_token},
+        search_index::index_user,
+        session::{server_cookie, start_session, two_factor_challenge},
+        utils::{is_totp_enabled, load_config},
+    },
+};
+
+//cookie berisi state di browser yang memulai login, hanya dikirim ke route OIDC
+pub const STATE_COOKIE: &str = "oidc_state";
+const STATE_COOKIE_PATH: &str = "/login/oidc";
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OidcCallback {
+    pub code: Option<String>,
+    pub state: String,
+    pub error: Option<String>,
+}
+
+fn find_provider(cfg: &OidcConfig, name: &str) -> Result<OidcProviderConfig, AppError> {
+    cfg.providers.iter()
+        .find(|p| p.name == name)
+        .cloned()
+        .ok_or(AppError::NotFound)
+}
+
+//state dari query harus sama dengan cookie yang dipasang oidc_start di browser yang sama,
+//mencegah login CSRF lewat URL callback milik orang lain
+pub fn state_matches(jar: &CookieJar, state: &str) -> bool {
+    jar.get(STATE_COOKIE).is_some_and(|cookie| bool::from(cookie.value().as_bytes().ct_eq(state.as_bytes())))
+}
+
+fn remove_state_cookie(jar: CookieJar) -> CookieJar {
+    jar.remove(Cookie::build(STATE_COOKIE).path(STATE_COOKIE_PATH))
+}
+
+//state hanya bisa dipakai sekali dan harus untuk provider yang sama
+async fn take_state(pool: &Pool<MySql>, state: &str, provider: &str) -> Result<(String, String), AppError> {
+    let row: Option<(String, String)> = sqlx::query_as("SELECT nonce, code_verifier FROM oidc_states WHERE state = ? AND provider = ? AND expires_at > NOW()")
+        .bind(state)
+        .bind(provider)
+        .fetch_optional(pool)
+        .await?;
+    let deleted = sqlx::query("DELETE FROM oidc_states WHERE state = ?")
+        .bind(state)
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM oidc_states WHERE expires_at <= NOW()")
+        .execute(pool)
+        .await?;
+
+    match row {
+        Some(row) if deleted.rows_affected() > 0 => Ok(row),
+        _ => Err(AppError::Unauthorized),
+    }
+}
+
+//cari user dari identity yang sudah tertaut, atau tautkan berdasarkan email terverifikasi
+pub async fn link_account(pool: &Pool<MySql>, provider: &str, claims: &IdTokenClaims, allow_signup: bool, client: &ClientInfo) -> Result<u64, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let linked: Option<(u64, bool)> = sqlx::query_as("SELECT i.user_id, u.deleted_at IS NOT NULL FROM user_identities i JOIN users u ON u.id = i.user_id WHERE i.provider = ? AND i.subject = ?")
+        .bind(provider)
+        .bind(&claims.sub)
+        .fetch_optional(&mut *tx)
+        .await?;
+    match linked {
+        Some((_, true)) => return Err(AppError::Unauthorized),
+        Some((user_id, false)) => return Ok(user_id),
+        None => (),
+    }
+
+    //email yang belum diverifikasi provider tidak boleh dipakai untuk menautkan akun
+    let email = claims.email.as_deref()
+        .filter(|_| claims.email_verified)
+        .ok_or(AppError::Unauthorized)?;
+
+    //email milik user yang sudah dihapus tetap dipesan sampai di-purge
+    let existing: Option<(u64, bool)> = sqlx::query_as("SELECT id, deleted_at IS NOT NULL FROM users WHERE email = ?")
+        .bind(email)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let user_id = match existing {
+        Some((_, true)) => return Err(AppError::Unauthorized),
+        Some((id, false)) => id,
+        //signup oleh user itu sendiri, id aktor belum ada sebelum user dibuat
+        None if allow_signup => {
+            let name = claims.name.as_deref().unwrap_or(email);
+            let ctx = AuditContext { actor_type: ActorKind::User, actor_id: None, client: client.clone() };
+            create_passwordless_user(&mut tx, name, email, &ctx).await?
+        }
+        None => return Err(AppError::NotFound),
+    };
+
+    sqlx::query("INSERT INTO user_identities (user_id, provider, subject, email) VALUES (?, ?, ?, ?)")
+        .bind(user_id)
+        .bind(provider)
+        .bind(&claims.sub)
+        .bind(email)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    if existing.is_none() {
+        index_user(user_id).await;
+    }
+
+    Ok(user_id)
+}
+
+#[utoipa::path(
+    get, path = "/login/oidc", tag = "oidc",
+    responses((status = 200, body = Vec<String>), AppError)
+)]
+pub async fn list_oidc_providers() -> Result<(StatusCode, Json<Vec<String>>), AppError> {
+    let cfg = load_config()?.oidc;
+    Ok((StatusCode::OK, Json(cfg.providers.into_iter().map(|p| p.name).collect())))
+}
+
+//langkah 1: redirect browser ke provider dengan state, nonce dan PKCE
+#[utoipa::path(
+    get, path = "/login/oidc/{provider}", tag = "oidc", params(("provider" = String, Path)),
+    responses((status = 303, description = "Redirect ke provider", headers(("Set-Cookie" = String, description = "Cookie oidc_state httpOnly, dicocokkan saat callback"))), AppError)
+)]
+pub async fn oidc_start(Path(name): Path<String>) -> Result<(CookieJar, Redirect), AppError> {
+    let cfg = load_config()?.oidc;
+    let provider = find_provider(&cfg, &name)?;
+    let metadata = discover(&http_client()?, &provider.issuer).await?;
+
+    let state = random_token();
+    let nonce = random_token();
+    let code_verifier = random_token();
+
+    let pool = db::get_pool().await?;
+    sqlx::query("INSERT INTO oidc_states (state, provider, nonce, code_verifier, expires_at) VALUES (?, ?, ?, ?, ?)")
+        .bind(&state)
+        .bind(&provider.name)
+        .bind(&nonce)
+        .bind(&code_verifier)
+        .bind(Utc::now() + Duration::seconds(cfg.state_ttl))
+        .execute(&pool)
+        .await?;
+
+    let url = authorization_url(&metadata, &provider, &state, &nonce, &code_verifier)?;
+    let mut cookie = server_cookie(STATE_COOKIE, state)?;
+    cookie.set_path(STATE_COOKIE_PATH);
+    cookie.set_max_age(time::Duration::seconds(cfg.state_ttl));
+    Ok((CookieJar::new().add(cookie), Redirect::to(&url)))
+}
+
+//langkah 2: provider mengembalikan code, ditukar dengan id token lalu dibuatkan session.
+//user dengan 2FA aktif tidak mendapat session, tetapi diarahkan ke aplikasi dengan challenge token
+//di fragment URL (#challenge_token=...&expires_in=...) untuk ditukar lewat POST /login/totp
+#[utoipa::path(
+    get, path = "/login/oidc/{provider}/callback", tag = "oidc", params(("provider" = String, Path), OidcCallback),
+    responses((status = 303, description = "Login berhasil atau butuh kode 2FA (challenge token di fragment URL), redirect ke aplikasi", headers(("Set-Cookie" = String, description = "Cookie jwt httpOnly"))), AppError)
+)]
+pub async fn oidc_callback(client: ClientInfo, jar: CookieJar, Path(name): Path<String>, Query(callback): Query<OidcCallback>) -> Result<(CookieJar, Redirect), AppError> {
+    let cfg = load_config()?.oidc;
+    let provider = find_provider(&cfg, &name)?;
+    if !state_matches(&jar, &callback.state) {
+        return Err(AppError::Unauthorized);
+    }
+    let pool = db::get_pool().await?;
+    let (nonce, code_verifier) = take_state(&pool, &callback.state, &provider.name).await?;
+
+    if let Some(error) = callback.error.as_deref() {
+        eprintln!("OIDC: provider {} mengembalikan error {}", provider.name, error);
+        return Err(AppError::Unauthorized);
+    }
+    let code = callback.code.as_deref().ok_or(AppError::BadRequest)?;
+
+    let http = http_client()?;
+    let metadata = discover(&http, &provider.issuer).await?;
+    let tokens = exchange_code(&http, &metadata, &provider, code, &code_verifier).await?;
+    let jwks = fetch_jwks(&http, &metadata).await?;
+    let claims = verify_id_token(&jwks, &tokens.id_token, &metadata.issuer, &provider.client_id, &nonce)?;
+
+    let user_id = link_account(&pool, &provider.name, &claims, cfg.allow_signup, &client).await?;
+    if is_totp_enabled(&pool, user_id).await? {
+        let challenge = two_factor_challenge(user_id)?;
+        let url = format!("{}#challenge_token={}&expires_in={}", cfg.post_login_redirect, challenge.challenge_token, challenge.expires_in);
+        return Ok((remove_state_cookie(jar), Redirect::to(&url)));
+    }
+
+    let session = start_session(&pool, user_id, &client).await?;
+    Ok((remove_state_cookie(session), Redirect::to(&cfg.post_login_redirect)))
+}
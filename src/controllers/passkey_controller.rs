@@ -0,0 +1,285 @@
+use axum::{Extension, Json, extract::Path};
+use axum_extra::extract::CookieJar;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{Duration, Utc};
+use http::StatusCode;
+use sqlx::{MySql, Pool};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    configs::db,
+    errors::app_error::AppError,
+    models::{
+        config_model::WebauthnConfig,
+        user_model::{Claims, User},
+        webauthn_model::{
+            AuthenticatorSelection, CeremonyOptions, CreationOptions, CredentialDescriptor, CredentialParameter,
+            Passkey, PasskeyLogin, PasskeyLoginStart, PasskeyRegister, PublicKeyUser, RelyingParty, RequestOptions, StoredPasskey,
+        },
+    },
+    utils::{
+        client_info::ClientInfo,
+        session::start_session,
+        utils::load_config,
+        webauthn::{COSE_EDDSA, COSE_ES256, decode_b64, generate_challenge, verify_assertion, verify_registration},
+    },
+};
+
+const REGISTER: &str = "register";
+const LOGIN: &str = "login";
+
+fn user_verification(cfg: &WebauthnConfig) -> &'static str {
+    if cfg.require_user_verification { "required" } else { "preferred" }
+}
+
+//user handle WebAuthn: id user dalam 8 byte big endian (tanpa data pribadi)
+fn user_handle(user_id: u64) -> String {
+    URL_SAFE_NO_PAD.encode(user_id.to_be_bytes())
+}
+
+async fn credential_descriptors(pool: &Pool<MySql>, user_id: u64) -> Result<Vec<CredentialDescriptor>, AppError> {
+    let ids: Vec<(String,)> = sqlx::query_as("SELECT credential_id FROM passkeys WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(ids.into_iter().map(|(id,)| CredentialDescriptor { typ: "public-key", id }).collect())
+}
+
+async fn store_challenge(pool: &Pool<MySql>, cfg: &WebauthnConfig, user_id: Option<u64>, kind: &str, challenge: &str) -> Result<String, AppError> {
+    let id = Uuid::new_v4().to_string();
+    let expires_at = Utc::now() + Duration::milliseconds(cfg.timeout_ms as i64);
+    sqlx::query("INSERT INTO webauthn_challenges (id, user_id, kind, challenge, expires_at) VALUES (?, ?, ?, ?, ?)")
+        .bind(&id)
+        .bind(user_id)
+        .bind(kind)
+        .bind(challenge)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+    Ok(id)
+}
+
+//challenge hanya bisa dipakai sekali, dihapus saat diambil
+async fn take_challenge(pool: &Pool<MySql>, id: &str, kind: &str) -> Result<(String, Option<u64>), AppError> {
+    let row: Option<(String, Option<u64>)> = sqlx::query_as("SELECT challenge, user_id FROM webauthn_challenges WHERE id = ? AND kind = ? AND expires_at > NOW()")
+        .bind(id)
+        .bind(kind)
+        .fetch_optional(pool)
+        .await?;
+    let deleted = sqlx::query("DELETE FROM webauthn_challenges WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM webauthn_challenges WHERE expires_at <= NOW()")
+        .execute(pool)
+        .await?;
+
+    match row {
+        Some(row) if deleted.rows_affected() > 0 => Ok(row),
+        _ => Err(AppError::Unauthorized),
+    }
+}
+
+#[utoipa::path(
+    post, path = "/me/passkeys/options", tag = "me", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    responses((status = 200, body = CeremonyOptions<CreationOptions>), AppError)
+)]
+pub async fn passkey_register_options(Extension(claims): Extension<Claims>) -> Result<(StatusCode, Json<CeremonyOptions<CreationOptions>>), AppError> {
+    let pool = db::get_pool().await?;
+    let cfg = load_config()?.webauthn;
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ? AND deleted_at IS NULL")
+        .bind(claims.sub)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let challenge = generate_challenge();
+    let challenge_id = store_challenge(&pool, &cfg, Some(user.id), REGISTER, &challenge).await?;
+
+    let public_key = CreationOptions {
+        challenge,
+        rp: RelyingParty { id: cfg.rp_id.clone(), name: cfg.rp_name.clone() },
+        user: PublicKeyUser { id: user_handle(user.id), name: user.email, display_name: user.name },
+        pub_key_cred_params: vec![
+            CredentialParameter { typ: "public-key", alg: COSE_ES256 },
+            CredentialParameter { typ: "public-key", alg: COSE_EDDSA },
+        ],
+        timeout: cfg.timeout_ms,
+        attestation: "none",
+        exclude_credentials: credential_descriptors(&pool, user.id).await?,
+        authenticator_selection: AuthenticatorSelection {
+            resident_key: "preferred",
+            user_verification: user_verification(&cfg),
+        },
+    };
+
+    Ok((StatusCode::OK, Json(CeremonyOptions { challenge_id, public_key })))
+}
+
+#[utoipa::path(
+    post, path = "/me/passkeys", tag = "me", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), request_body = PasskeyRegister,
+    responses((status = 201, body = Passkey), AppError)
+)]
+pub async fn passkey_register(Extension(claims): Extension<Claims>, payload: Json<PasskeyRegister>) -> Result<(StatusCode, Json<Passkey>), AppError> {
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let pool = db::get_pool().await?;
+    let cfg = load_config()?.webauthn;
+    let (challenge, user_id) = take_challenge(&pool, &payload.challenge_id, REGISTER).await?;
+    if user_id != Some(claims.sub) {
+        return Err(AppError::Unauthorized);
+    }
+
+    let response = &payload.credential.response;
+    let credential = verify_registration(
+        &cfg,
+        &challenge,
+        &decode_b64(&response.client_data_json)?,
+        &decode_b64(&response.attestation_object)?,
+    )?;
+    let credential_id = URL_SAFE_NO_PAD.encode(&credential.credential_id);
+
+    if sqlx::query_as::<_, (u64,)>("SELECT id FROM passkeys WHERE credential_id = ?")
+        .bind(&credential_id)
+        .fetch_optional(&pool)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Conflict);
+    }
+
+    let result = sqlx::query("INSERT INTO passkeys (user_id, credential_id, public_key, sign_count, name) VALUES (?, ?, ?, ?, ?)")
+        .bind(claims.sub)
+        .bind(&credential_id)
+        .bind(&credential.public_key)
+        .bind(credential.sign_count)
+        .bind(payload.name.trim())
+        .execute(&pool)
+        .await?;
+
+    let passkey = sqlx::query_as::<_, Passkey>("SELECT id, name, created_at, last_used_at FROM passkeys WHERE id = ?")
+        .bind(result.last_insert_id())
+        .fetch_one(&pool)
+        .await?;
+    Ok((StatusCode::CREATED, Json(passkey)))
+}
+
+#[utoipa::path(
+    get, path = "/me/passkeys", tag = "me", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    responses((status = 200, body = Vec<Passkey>), AppError)
+)]
+pub async fn list_passkeys(Extension(claims): Extension<Claims>) -> Result<(StatusCode, Json<Vec<Passkey>>), AppError> {
+    let pool = db::get_pool().await?;
+    let passkeys = sqlx::query_as::<_, Passkey>("SELECT id, name, created_at, last_used_at FROM passkeys WHERE user_id = ? ORDER BY created_at")
+        .bind(claims.sub)
+        .fetch_all(&pool)
+        .await?;
+    Ok((StatusCode::OK, Json(passkeys)))
+}
+
+#[utoipa::path(
+    delete, path = "/me/passkeys/{id}", tag = "me", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(("id" = u64, Path, description = "Id passkey")),
+    responses((status = 204), AppError)
+)]
+pub async fn delete_passkey(Extension(claims): Extension<Claims>, Path(id): Path<u64>) -> Result<StatusCode, AppError> {
+    let pool = db::get_pool().await?;
+    let result = sqlx::query("DELETE FROM passkeys WHERE id = ? AND user_id = ?")
+        .bind(id)
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+//email opsional: tanpa email dipakai discoverable credential (passkey memilih akun sendiri)
+#[utoipa::path(
+    post, path = "/login/passkey/options", tag = "auth", security(("api_key" = []), ("oauth_bearer" = [])), request_body = PasskeyLoginStart,
+    responses((status = 200, body = CeremonyOptions<RequestOptions>), AppError)
+)]
+pub async fn passkey_login_options(payload: Json<PasskeyLoginStart>) -> Result<(StatusCode, Json<CeremonyOptions<RequestOptions>>), AppError> {
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let pool = db::get_pool().await?;
+    let cfg = load_config()?.webauthn;
+
+    //email yang tidak terdaftar tetap mendapat respon yang sama agar tidak bisa dipakai enumerasi user
+    let user_id = match payload.email.as_deref() {
+        Some(email) => sqlx::query_as::<_, (u64,)>("SELECT id FROM users WHERE email = ? AND deleted_at IS NULL")
+            .bind(email.trim())
+            .fetch_optional(&pool)
+            .await?
+            .map(|(id,)| id),
+        None => None,
+    };
+    let allow_credentials = match user_id {
+        Some(id) => credential_descriptors(&pool, id).await?,
+        None => Vec::new(),
+    };
+
+    let challenge = generate_challenge();
+    let challenge_id = store_challenge(&pool, &cfg, user_id, LOGIN, &challenge).await?;
+
+    let public_key = RequestOptions {
+        challenge,
+        rp_id: cfg.rp_id.clone(),
+        timeout: cfg.timeout_ms,
+        allow_credentials,
+        user_verification: user_verification(&cfg),
+    };
+    Ok((StatusCode::OK, Json(CeremonyOptions { challenge_id, public_key })))
+}
+
+#[utoipa::path(
+    post, path = "/login/passkey", tag = "auth", security(("api_key" = []), ("oauth_bearer" = [])), request_body = PasskeyLogin,
+    responses((status = 200, description = "Login berhasil", headers(("Set-Cookie" = String, description = "Cookie jwt httpOnly"))), AppError)
+)]
+pub async fn passkey_login(client: ClientInfo, payload: Json<PasskeyLogin>) -> Result<(StatusCode, CookieJar), AppError> {
+    let pool = db::get_pool().await?;
+    let cfg = load_config()?.webauthn;
+    let (challenge, expected_user) = take_challenge(&pool, &payload.challenge_id, LOGIN).await?;
+
+    let credential_id = payload.credential.id.trim_end_matches('=');
+    let passkey = sqlx::query_as::<_, StoredPasskey>("SELECT id, user_id, public_key, sign_count FROM passkeys WHERE credential_id = ?")
+        .bind(credential_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+
+    if expected_user.is_some_and(|id| id != passkey.user_id) {
+        return Err(AppError::Unauthorized);
+    }
+    let response = &payload.credential.response;
+    if let Some(handle) = response.user_handle.as_deref().filter(|h| !h.is_empty())
+        && decode_b64(handle)? != passkey.user_id.to_be_bytes() {
+        return Err(AppError::Unauthorized);
+    }
+
+    let sign_count = verify_assertion(
+        &cfg,
+        &challenge,
+        &passkey.public_key,
+        passkey.sign_count,
+        &decode_b64(&response.client_data_json)?,
+        &decode_b64(&response.authenticator_data)?,
+        &decode_b64(&response.signature)?,
+    )?;
+
+    //update bersyarat agar dua login paralel dengan counter yang sama tidak sama-sama lolos
+    let updated = sqlx::query("UPDATE passkeys SET sign_count = ?, last_used_at = NOW() WHERE id = ? AND sign_count = ?")
+        .bind(sign_count)
+        .bind(passkey.id)
+        .bind(passkey.sign_count)
+        .execute(&pool)
+        .await?;
+    if updated.rows_affected() == 0 {
+        return Err(AppError::Unauthorized);
+    }
+
+    let jar = start_session(&pool, passkey.user_id, &client).await?;
+    Ok((StatusCode::OK, jar))
+}
@@ -0,0 +1,39 @@
+use axum::{Extension, Json, extract::Path};
+use http::StatusCode;
+
+use crate::{configs::db, errors::app_error::AppError, models::{session_model::Session, user_model::Claims}, utils::session::revoke_session};
+
+#[utoipa::path(
+    get, path = "/me/sessions", tag = "me", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    responses((status = 200, body = Vec<Session>), AppError)
+)]
+pub async fn list_sessions(Extension(claims): Extension<Claims>) -> Result<(StatusCode, Json<Vec<Session>>), AppError> {
+    let pool = db::get_pool().await?;
+    let mut sessions = sqlx::query_as::<_, Session>(
+        "SELECT id, user_agent, ip_address, created_at, last_seen_at, expires_at FROM sessions
+         WHERE user_id = ? AND revoked_at IS NULL AND expires_at > NOW()
+         ORDER BY COALESCE(last_seen_at, created_at) DESC")
+        .bind(claims.sub)
+        .fetch_all(&pool)
+        .await?;
+
+    for session in sessions.iter_mut() {
+        session.current = claims.sid.as_deref() == Some(session.id.as_str());
+    }
+
+    Ok((StatusCode::OK, Json(sessions)))
+}
+
+//sign out perangkat tertentu, token dengan sid ini langsung ditolak oleh check_login
+#[utoipa::path(
+    delete, path = "/me/sessions/{id}", tag = "me", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(("id" = String, Path, description = "Id session")),
+    responses((status = 204), AppError)
+)]
+pub async fn delete_session(Extension(claims): Extension<Claims>, Path(id): Path<String>) -> Result<StatusCode, AppError> {
+    let pool = db::get_pool().await?;
+    if !revoke_session(&pool, claims.sub, &id).await? {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,221 @@
+use axum::{Extension, Json};
+use axum_extra::extract::CookieJar;
+use chrono::{DateTime, Utc};
+use http::StatusCode;
+use sqlx::{MySql, Pool};
+use validator::Validate;
+
+use crate::{
+    configs::db,
+    errors::app_error::AppError,
+    models::{totp_model::{RecoveryCodes, TotpCode, TotpLogin, TotpSetup, UserTotp}, user_model::{Claims, TokenType}},
+    utils::{
+        client_info::ClientInfo,
+        session::start_session,
+        totp::{generate_recovery_code, generate_secret, normalize_recovery_code, provisioning_uri, verify_code},
+        utils::{hashing_password, jwt_verify_type, load_config, verify_password},
+    },
+};
+
+async fn get_user_totp(pool: &Pool<MySql>, user_id: u64) -> Result<UserTotp, AppError> {
+    sqlx::query_as::<_, UserTotp>("SELECT email, totp_secret, totp_enabled_at IS NOT NULL AS totp_enabled FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(AppError::NotFound)
+}
+
+//memeriksa kode TOTP dan menyimpan time step terakhir agar kode yang sama tidak bisa dipakai ulang
+async fn check_totp_code(pool: &Pool<MySql>, user_id: u64, secret: &str, code: &str) -> Result<bool, AppError> {
+    let cfg = load_config()?.totp;
+    let Some(step) = verify_code(&cfg, secret, code, Utc::now().timestamp() as u64) else {
+        return Ok(false);
+    };
+
+    let result = sqlx::query("UPDATE users SET totp_last_step = ? WHERE id = ? AND (totp_last_step IS NULL OR totp_last_step < ?)")
+        .bind(step)
+        .bind(user_id)
+        .bind(step)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+//kode pemulihan hanya bisa dipakai sekali
+async fn use_recovery_code(pool: &Pool<MySql>, user_id: u64, code: &str) -> Result<bool, AppError> {
+    let code = normalize_recovery_code(code);
+    let candidates: Vec<(u64, String)> = sqlx::query_as("SELECT id, code_hash FROM recovery_codes WHERE user_id = ? AND used_at IS NULL")
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    for (id, hash) in candidates {
+        if verify_password(Some(&hash), &code).await? {
+            let result = sqlx::query("UPDATE recovery_codes SET used_at = NOW() WHERE id = ? AND used_at IS NULL")
+                .bind(id)
+                .execute(pool)
+                .await?;
+            return Ok(result.rows_affected() > 0);
+        }
+    }
+    Ok(false)
+}
+
+async fn replace_recovery_codes(pool: &Pool<MySql>, user_id: u64, count: usize) -> Result<Vec<String>, AppError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM recovery_codes WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let mut codes = Vec::with_capacity(count);
+    for _ in 0..count {
+        let code = generate_recovery_code();
+        let hash = hashing_password(&normalize_recovery_code(&code)).await?;
+        sqlx::query("INSERT INTO recovery_codes (user_id, code_hash) VALUES (?, ?)")
+            .bind(user_id)
+            .bind(hash)
+            .execute(&mut *tx)
+            .await?;
+        codes.push(code);
+    }
+    tx.commit().await?;
+    Ok(codes)
+}
+
+//langkah 1: buat secret baru (belum aktif sampai dikonfirmasi)
+#[utoipa::path(
+    post, path = "/me/totp/setup", tag = "me", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    responses((status = 200, body = TotpSetup), AppError)
+)]
+pub async fn setup_totp(Extension(claims): Extension<Claims>) -> Result<(StatusCode, Json<TotpSetup>), AppError> {
+    let pool = db::get_pool().await?;
+    let cfg = load_config()?.totp;
+    let user = get_user_totp(&pool, claims.sub).await?;
+    if user.totp_enabled {
+        return Err(AppError::Conflict);
+    }
+
+    let secret = generate_secret();
+    sqlx::query("UPDATE users SET totp_secret = ?, totp_last_step = NULL WHERE id = ?")
+        .bind(&secret)
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+
+    let provisioning_uri = provisioning_uri(&cfg, &user.email, &secret);
+    Ok((StatusCode::OK, Json(TotpSetup { secret, provisioning_uri })))
+}
+
+//langkah 2: konfirmasi dengan kode pertama, lalu kode pemulihan ditampilkan sekali
+#[utoipa::path(
+    post, path = "/me/totp/confirm", tag = "me", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), request_body = TotpCode,
+    responses((status = 200, body = RecoveryCodes), AppError)
+)]
+pub async fn confirm_totp(Extension(claims): Extension<Claims>, payload: Json<TotpCode>) -> Result<(StatusCode, Json<RecoveryCodes>), AppError> {
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let pool = db::get_pool().await?;
+    let user = get_user_totp(&pool, claims.sub).await?;
+    if user.totp_enabled {
+        return Err(AppError::Conflict);
+    }
+    let secret = user.totp_secret.ok_or(AppError::BadRequest)?;
+
+    if !check_totp_code(&pool, claims.sub, &secret, &payload.code).await? {
+        return Err(AppError::Unauthorized);
+    }
+
+    sqlx::query("UPDATE users SET totp_enabled_at = NOW() WHERE id = ?")
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+
+    let recovery_codes = replace_recovery_codes(&pool, claims.sub, load_config()?.totp.recovery_codes).await?;
+    Ok((StatusCode::OK, Json(RecoveryCodes { recovery_codes })))
+}
+
+#[utoipa::path(
+    delete, path = "/me/totp", tag = "me", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), request_body = TotpCode,
+    responses((status = 204), AppError)
+)]
+pub async fn disable_totp(Extension(claims): Extension<Claims>, payload: Json<TotpCode>) -> Result<StatusCode, AppError> {
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let pool = db::get_pool().await?;
+    let user = get_user_totp(&pool, claims.sub).await?;
+    let secret = user.totp_secret.filter(|_| user.totp_enabled).ok_or(AppError::NotFound)?;
+
+    if !check_totp_code(&pool, claims.sub, &secret, &payload.code).await? {
+        return Err(AppError::Unauthorized);
+    }
+
+    sqlx::query("UPDATE users SET totp_secret = NULL, totp_enabled_at = NULL, totp_last_step = NULL WHERE id = ?")
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+    sqlx::query("DELETE FROM recovery_codes WHERE user_id = ?")
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+//setiap percobaan dihitung sebelum kode diperiksa, challenge yang sudah dipakai atau melewati batas ditolak
+async fn count_challenge_attempt(pool: &Pool<MySql>, challenge: &Claims, max_attempts: u32) -> Result<bool, AppError> {
+    let expires_at = DateTime::from_timestamp(challenge.exp, 0).ok_or(AppError::Unauthorized)?;
+    sqlx::query("INSERT INTO totp_challenges (jti, user_id, attempts, expires_at) VALUES (?, ?, 1, ?) ON DUPLICATE KEY UPDATE attempts = attempts + 1")
+        .bind(&challenge.jti)
+        .bind(challenge.sub)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM totp_challenges WHERE expires_at <= NOW()")
+        .execute(pool)
+        .await?;
+
+    let (attempts, used): (u32, bool) = sqlx::query_as("SELECT attempts, used_at IS NOT NULL FROM totp_challenges WHERE jti = ?")
+        .bind(&challenge.jti)
+        .fetch_one(pool)
+        .await?;
+    Ok(!used && attempts <= max_attempts)
+}
+
+//challenge hanya menghasilkan satu session walaupun ada dua request dengan kode benar bersamaan
+async fn use_challenge(pool: &Pool<MySql>, jti: &str) -> Result<bool, AppError> {
+    let result = sqlx::query("UPDATE totp_challenges SET used_at = NOW() WHERE jti = ? AND used_at IS NULL")
+        .bind(jti)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+//langkah kedua login: tukar challenge token + kode TOTP/kode pemulihan dengan cookie jwt
+#[utoipa::path(
+    post, path = "/login/totp", tag = "auth", security(("api_key" = []), ("oauth_bearer" = [])), request_body = TotpLogin,
+    responses((status = 200, description = "Login berhasil", headers(("Set-Cookie" = String, description = "Cookie jwt httpOnly"))), AppError)
+)]
+pub async fn login_totp(client: ClientInfo, payload: Json<TotpLogin>) -> Result<(StatusCode, CookieJar), AppError> {
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let challenge = jwt_verify_type(&payload.challenge_token, TokenType::Challenge).await?;
+    let pool = db::get_pool().await?;
+    if !count_challenge_attempt(&pool, &challenge, load_config()?.totp.max_attempts).await? {
+        return Err(AppError::Unauthorized);
+    }
+    let user = get_user_totp(&pool, challenge.sub).await?;
+    let secret = user.totp_secret.filter(|_| user.totp_enabled).ok_or(AppError::Unauthorized)?;
+
+    let valid = match (&payload.code, &payload.recovery_code) {
+        (Some(code), _) => check_totp_code(&pool, challenge.sub, &secret, code).await?,
+        (None, Some(recovery_code)) => use_recovery_code(&pool, challenge.sub, recovery_code).await?,
+        (None, None) => return Err(AppError::BadRequest),
+    };
+    if !valid || !use_challenge(&pool, &challenge.jti).await? {
+        return Err(AppError::Unauthorized);
+    }
+
+    let jar = start_session(&pool, challenge.sub, &client).await?;
+    Ok((StatusCode::OK, jar))
+}
@@ -0,0 +1,135 @@
+use axum::{Json, response::IntoResponse};
+use http::StatusCode;
+use sqlx::MySqlConnection;
+use validator::Validate;
+
+use crate::{
+    configs::db,
+    controllers::user_controller::{create_user, delete_user_in, update_user_in},
+    errors::app_error::AppError,
+    models::{
+        batch_model::{BatchOperation, BatchRequest, BatchResponse, BatchResult},
+        user_model::{AttributeChange, User},
+    },
+    utils::{
+        audit::AuditContext,
+        etag::IfMatch,
+        search_index::{index_user, unindex_user},
+        utils::{load_config, validation_messages},
+    },
+};
+
+//validasi dan query sama persis dengan endpoint tunggalnya
+async fn execute(conn: &mut MySqlConnection, op: &BatchOperation, ctx: &AuditContext) -> Result<(StatusCode, u64, Option<User>), AppError> {
+    match op {
+        BatchOperation::Create { data } => {
+            let id = create_user(conn, data, ctx).await?;
+            Ok((StatusCode::CREATED, id, None))
+        }
+        BatchOperation::Update { id, data, if_match } => {
+            data.validate().map_err(AppError::ValidationError)?;
+            let condition = if_match.as_deref().map(IfMatch::parse);
+            let user = update_user_in(conn, *id, Some(data.name.trim()), Some(data.email.trim()), data.attributes.as_ref().map(AttributeChange::Replace), condition.as_ref(), ctx).await?;
+            Ok((StatusCode::OK, *id, Some(user)))
+        }
+        BatchOperation::Delete { id } => {
+            delete_user_in(conn, *id, ctx).await?;
+            Ok((StatusCode::NO_CONTENT, *id, None))
+        }
+    }
+}
+
+fn success(index: usize, (status, id, user): (StatusCode, u64, Option<User>)) -> BatchResult {
+    BatchResult { index, status: status.as_u16(), id: Some(id), user, errors: Vec::new() }
+}
+
+fn failure(index: usize, error: AppError) -> BatchResult {
+    let errors = match &error {
+        AppError::ValidationError(e) => validation_messages(e),
+        e => vec![e.to_string()],
+    };
+    //status sama dengan yang dikirim endpoint tunggal untuk error ini
+    BatchResult { index, status: error.into_response().status().as_u16(), id: None, user: None, errors }
+}
+
+fn not_applied(index: usize, failed: usize) -> BatchResult {
+    BatchResult {
+        index,
+        status: StatusCode::FAILED_DEPENDENCY.as_u16(),
+        id: None,
+        user: None,
+        errors: vec![format!("dibatalkan karena operasi {} gagal", failed)],
+    }
+}
+
+//mode transaksi diatur lewat config batch.transactional
+#[utoipa::path(
+    post, path = "/users/batch", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), request_body = BatchRequest,
+    responses((status = 200, body = BatchResponse, description = "Hasil per operasi, cek committed untuk mode transaksi"), AppError)
+)]
+pub async fn batch_users(ctx: AuditContext, Json(payload): Json<BatchRequest>) -> Result<(StatusCode, Json<BatchResponse>), AppError> {
+    let cfg = load_config()?.batch;
+    if payload.operations.is_empty() {
+        return Err(AppError::BadRequest);
+    }
+    if payload.operations.len() > cfg.max_operations {
+        return Err(AppError::PayloadTooLarge);
+    }
+
+    let pool = db::get_pool().await?;
+    let mut results = Vec::with_capacity(payload.operations.len());
+    let committed = if cfg.transactional {
+        let mut tx = pool.begin().await?;
+        let mut failed = None;
+        for (index, op) in payload.operations.iter().enumerate() {
+            match execute(&mut tx, op, &ctx).await {
+                Ok(done) => results.push(success(index, done)),
+                Err(e) => {
+                    results.push(failure(index, e));
+                    failed = Some(index);
+                    break;
+                }
+            }
+        }
+
+        match failed {
+            None => {
+                tx.commit().await?;
+                true
+            }
+            //operasi yang sudah jalan ikut dibatalkan, sisanya tidak dijalankan
+            Some(failed) => {
+                tx.rollback().await?;
+                for result in results.iter_mut().take(failed) {
+                    *result = not_applied(result.index, failed);
+                }
+                results.extend((failed + 1..payload.operations.len()).map(|index| not_applied(index, failed)));
+                false
+            }
+        }
+    } else {
+        for (index, op) in payload.operations.iter().enumerate() {
+            let mut tx = pool.begin().await?;
+            match execute(&mut tx, op, &ctx).await {
+                Ok(done) => {
+                    tx.commit().await?;
+                    results.push(success(index, done));
+                }
+                Err(e) => results.push(failure(index, e)),
+            }
+        }
+        true
+    };
+
+    if committed {
+        for (op, result) in payload.operations.iter().zip(&results) {
+            match (op, result.id) {
+                (BatchOperation::Delete { .. }, Some(id)) => unindex_user(id).await,
+                (_, Some(id)) => index_user(id).await,
+                _ => {}
+            }
+        }
+    }
+
+    Ok((StatusCode::OK, Json(BatchResponse { transactional: cfg.transactional, committed, results })))
+}
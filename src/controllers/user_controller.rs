@@ -1,39 +1,137 @@
-use axum::{Json, extract::{ Path, Query}};
-use axum_extra::extract::{CookieJar, cookie::Cookie};
-use http::{ StatusCode};
+use axum::{Extension, Json, extract::{ Path, Query}, response::{IntoResponse, Response}};
+use axum_extra::extract::CookieJar;
+use http::{HeaderMap, HeaderName, StatusCode, header};
+use sqlx::{MySqlConnection, types::Json as SqlJson};
+use serde_json::json;
 use validator::Validate;
-use crate::{configs::db, errors::app_error::AppError, models::user_model::{SeacrhBy, SearchQuery, User, UserInsert, UserLogin, UserQuery, UserUpdate}, utils::utils::{check_email, create_jwt, hashing_password, verify_password}};
+use crate::{configs::db, errors::app_error::AppError, models::{attribute_model::AttributeValues, audit_model::AuditAction, outbox_model::DomainEventKind, search_model::{UserSearchHit, UserSearchPage, UserSearchParams}, totp_model::TwoFactorChallenge, user_model::{AttributeChange, Claims, PasswordChange, SeacrhBy, SearchQuery, User, UserInsert, UserLogin, UserListQuery, UserPatch, UserQuery, UserUpdate}}, utils::{attributes::{combine_errors, load_definitions, merge_attributes, validate_attributes}, audit::{AuditContext, append, record, user_changes}, etag::{IfMatch, if_match, user_etag}, outbox::enqueue, password_hash::rehash_if_needed, password_policy::enforce_password_policy, search::{highlights, run_search}, search_index::{index_user, unindex_user}, search_query::{check_attributes, parse_search}, session::{remove_session_cookie, revoke_session, start_session, two_factor_challenge}, utils::{check_email, hashing_password, is_totp_enabled, load_config, verify_password}}};
 
+#[utoipa::path(
+    get, path = "/user", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    responses((status = 200, body = Vec<User>), AppError)
+)]
 pub async fn get_all_user()-> Result<(StatusCode, Json<Vec<User>>), AppError> {
     let pool = db::get_pool().await?;
-    let result= sqlx::query_as::<_, User>("SELECT * FROM users")
+    let result= sqlx::query_as::<_, User>("SELECT * FROM users WHERE deleted_at IS NULL")
         .fetch_all(&pool).await?;
 
     Ok((StatusCode::OK, Json(result)))
 }
 
-pub async fn insert_user(payload: Json<UserInsert>) -> Result<(StatusCode,String), AppError> {
-    payload.validate().map_err(AppError::ValidationError)?;
-
+#[utoipa::path(
+    post, path = "/users", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), request_body = UserInsert,
+    responses((status = 201, body = String, content_type = "text/plain"), AppError)
+)]
+pub async fn insert_user(ctx: AuditContext, payload: Json<UserInsert>) -> Result<(StatusCode,String), AppError> {
     let pool = db::get_pool().await?;
+    let mut tx = pool.begin().await?;
+    let id = create_user(&mut tx, &payload, &ctx).await?;
+    tx.commit().await?;
+    index_user(id).await;
+
+    Ok((StatusCode::CREATED, "User berhasil dibuat".to_string()))
+}
+
+//validasi dan insert user baru, dipakai POST /users dan batch
+pub async fn create_user(conn: &mut MySqlConnection, payload: &UserInsert, ctx: &AuditContext) -> Result<u64, AppError> {
+    let definitions = load_definitions(&mut *conn).await?;
+    let attributes = combine_errors(payload.validate(), validate_attributes(&definitions, &payload.attributes))
+        .map_err(AppError::ValidationError)?;
 
     let name = payload.name.trim();
     let email = payload.email.trim();
-    let password_hash = hashing_password(&payload.password.trim()).await?;
+    enforce_password_policy(payload.password.trim(), name, email).await?;
 
-    if check_email(email).await? {
+    let (taken,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users WHERE email = ?")
+        .bind(email)
+        .fetch_one(&mut *conn)
+        .await?;
+    if taken > 0 {
         return Err(AppError::Conflict);
     }
+    let password_hash = hashing_password(payload.password.trim()).await?;
+    insert_new_user(conn, name, email, Some(&password_hash), attributes, ctx).await
+}
+
+//user tanpa password (signup lewat OIDC), atribut wajib tetap divalidasi seperti POST /users
+pub async fn create_passwordless_user(conn: &mut MySqlConnection, name: &str, email: &str, ctx: &AuditContext) -> Result<u64, AppError> {
+    let definitions = load_definitions(&mut *conn).await?;
+    let attributes = validate_attributes(&definitions, &AttributeValues::new()).map_err(AppError::ValidationError)?;
+    insert_new_user(conn, name, email, None, attributes, ctx).await
+}
 
-    sqlx::query("INSERT INTO users (name, email, password) VALUE (?, ?, ?)")
+//insert user yang sudah divalidasi beserta audit dan event UserCreated di transaksi yang sama,
+//semua jalur pembuatan user (API, batch, import, OIDC) lewat sini
+pub async fn insert_new_user(conn: &mut MySqlConnection, name: &str, email: &str, password_hash: Option<&str>, attributes: AttributeValues, ctx: &AuditContext) -> Result<u64, AppError> {
+    let id = sqlx::query("INSERT INTO users (name, email, password, attributes) VALUE (?, ?, ?, ?)")
         .bind(name)
         .bind(email)
         .bind(password_hash)
-        .execute(&pool).await?;
-    
-    Ok((StatusCode::CREATED, "User berhasil dibuat".to_string()))
+        .bind(SqlJson(attributes))
+        .execute(&mut *conn).await?
+        .last_insert_id();
+
+    let created = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await?;
+    append(conn, ctx, AuditAction::UserCreate, Some(id), user_changes(None, Some(&created))).await?;
+    enqueue(conn, DomainEventKind::UserCreated, id, json!({ "user": created })).await?;
+    Ok(id)
 }
 
+//GET /users?q= mencari di nama dan email, tanpa q mengembalikan semua user
+#[utoipa::path(
+    get, path = "/users", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(UserListQuery),
+    responses((status = 200, body = Vec<User>), AppError)
+)]
+pub async fn list_users(Query(query): Query<UserListQuery>) -> Result<(StatusCode, Json<Vec<User>>), AppError> {
+    let pool = db::get_pool().await?;
+    let result = match query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        Some(q) => {
+            let value = format!("%{}%", q);
+            sqlx::query_as::<_, User>("SELECT * FROM users WHERE (name LIKE ? OR email LIKE ?) AND deleted_at IS NULL")
+                .bind(&value)
+                .bind(&value)
+                .fetch_all(&pool)
+                .await?
+        }
+        None => sqlx::query_as::<_, User>("SELECT * FROM users WHERE deleted_at IS NULL")
+            .fetch_all(&pool)
+            .await?,
+    };
+
+    Ok((StatusCode::OK, Json(result)))
+}
+
+//GET /users/search?q= memakai bahasa query (lihat utils::search_query), hasil diurutkan menurut relevansi
+#[utoipa::path(
+    get, path = "/users/search", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    params(UserSearchParams),
+    responses((status = 200, body = UserSearchPage), AppError)
+)]
+pub async fn search_users(Query(params): Query<UserSearchParams>) -> Result<(StatusCode, Json<UserSearchPage>), AppError> {
+    let cfg = load_config()?.search;
+    let expr = parse_search(params.q.as_deref().unwrap_or_default())?;
+    if !expr.attribute_names().is_empty() {
+        let definitions = load_definitions(&db::get_pool().await?).await?;
+        check_attributes(&expr, &definitions.iter().map(|d| d.name.as_str()).collect::<Vec<_>>())?;
+    }
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(cfg.default_per_page).clamp(1, cfg.max_per_page.max(1));
+
+    let result = run_search(&cfg, &expr, (page - 1).saturating_mul(per_page), per_page).await?;
+    let items = result.hits.into_iter()
+        .map(|hit| UserSearchHit { highlights: highlights(&expr, &hit.user), user: hit.user, score: hit.score })
+        .collect();
+
+    Ok((StatusCode::OK, Json(UserSearchPage { items, total: result.total, page, per_page })))
+}
+
+#[utoipa::path(
+    post, path = "/user/search", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), request_body = SearchQuery,
+    responses((status = 200, body = Vec<User>), AppError)
+)]
 pub async fn get_user(payload: Json<SearchQuery>) -> Result<(StatusCode, Json<Vec<User>>), AppError> {
     payload.validate().map_err(AppError::ValidationError)?;
 
@@ -43,8 +141,8 @@ pub async fn get_user(payload: Json<SearchQuery>) -> Result<(StatusCode, Json<Ve
     let result_vec = sqlx::query_as::<_, User>
     (
         match by {
-            SeacrhBy::Name => "SELECT * FROM users WHERE name LIKE ?",
-            SeacrhBy::Email => "SELECT * FROM users WHERE email LIKE ?",
+            SeacrhBy::Name => "SELECT * FROM users WHERE name LIKE ? AND deleted_at IS NULL",
+            SeacrhBy::Email => "SELECT * FROM users WHERE email LIKE ? AND deleted_at IS NULL",
         }
     )
     .bind(value)
@@ -52,74 +150,328 @@ pub async fn get_user(payload: Json<SearchQuery>) -> Result<(StatusCode, Json<Ve
     Ok((StatusCode::OK, Json(result_vec)))
 }
 
-pub async fn delete_user(Query(user_query): Query<UserQuery>)-> Result<(StatusCode, Json<String>), AppError> {
+//soft delete: user disembunyikan dan semua session/token-nya dicabut, bisa di-restore sampai di-purge
+async fn soft_delete_user(id: u64, ctx: &AuditContext) -> Result<(StatusCode, Json<String>), AppError> {
     let pool = db::get_pool().await?;
-    let result = sqlx::query("DELETE FROM users WHERE id = ?")
-        .bind(user_query.id)
-        .execute(&pool)
+    let mut tx = pool.begin().await?;
+    delete_user_in(&mut tx, id, ctx).await?;
+    tx.commit().await?;
+    unindex_user(id).await;
+
+    Ok((StatusCode::NO_CONTENT, Json("User deleted successfully".to_string())))
+}
+
+pub async fn delete_user_in(conn: &mut MySqlConnection, id: u64, ctx: &AuditContext) -> Result<(), AppError> {
+    let before = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ? AND deleted_at IS NULL FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    sqlx::query("UPDATE users SET deleted_at = NOW() WHERE id = ?")
+        .bind(id)
+        .execute(&mut *conn)
         .await?;
 
-    if result.rows_affected() == 0{
-        return Err(AppError::NotFound);
-    }
+    sqlx::query("UPDATE sessions SET revoked_at = NOW() WHERE user_id = ? AND revoked_at IS NULL")
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("UPDATE oauth_tokens SET revoked_at = NOW() WHERE user_id = ? AND revoked_at IS NULL")
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
 
-    Ok((StatusCode::NO_CONTENT, Json("User deleted successfully".to_string())))
+    let after = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await?;
+    append(conn, ctx, AuditAction::UserDelete, Some(id), user_changes(Some(&before), Some(&after))).await?;
+    enqueue(conn, DomainEventKind::UserDeleted, id, json!({ "user": after })).await?;
+    Ok(())
+}
+
+#[utoipa::path(
+    delete, path = "/users/{id}", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(("id" = u64, Path, description = "Id user")),
+    responses((status = 204), AppError)
+)]
+pub async fn delete_user_by_id(ctx: AuditContext, Path(id): Path<u64>) -> Result<(StatusCode, Json<String>), AppError> {
+    soft_delete_user(id, &ctx).await
 }
 
-pub async fn get_user_edit(Query(user_query): Query<UserQuery>) -> Result<(StatusCode, Json<User>), AppError> {
+//route lama DELETE /user/?id=
+#[utoipa::path(
+    delete, path = "/user/", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(UserQuery),
+    responses((status = 204), AppError)
+)]
+pub async fn delete_user(ctx: AuditContext, Query(user_query): Query<UserQuery>)-> Result<(StatusCode, Json<String>), AppError> {
+    soft_delete_user(user_query.id, &ctx).await
+}
+
+//daftar user yang dihapus dan masih bisa di-restore
+#[utoipa::path(
+    get, path = "/users/deleted", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    responses((status = 200, body = Vec<User>), AppError)
+)]
+pub async fn get_deleted_users() -> Result<(StatusCode, Json<Vec<User>>), AppError> {
     let pool = db::get_pool().await?;
-    let result = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
-        .bind(user_query.id)
-        .fetch_one(&pool)
+    let result = sqlx::query_as::<_, User>("SELECT * FROM users WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC")
+        .fetch_all(&pool)
         .await?;
 
     Ok((StatusCode::OK, Json(result)))
 }
 
-pub async fn edit_user(Path(id): Path<u64>, payload: Json<UserUpdate>) -> Result<(StatusCode, Json<User>), AppError> {
-    payload.validate().map_err(AppError::ValidationError)?;
+//session yang dicabut saat delete tidak dipulihkan, user harus login ulang
+#[utoipa::path(
+    post, path = "/users/{id}/restore", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(("id" = u64, Path, description = "Id user")),
+    responses((status = 200, body = User), AppError)
+)]
+pub async fn restore_user(ctx: AuditContext, Path(id): Path<u64>) -> Result<(StatusCode, Json<User>), AppError> {
+    let pool = db::get_pool().await?;
+    let mut tx = pool.begin().await?;
+    let before = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ? AND deleted_at IS NOT NULL FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    sqlx::query("UPDATE users SET deleted_at = NULL WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+    append(&mut tx, &ctx, AuditAction::UserRestore, Some(id), user_changes(Some(&before), Some(&user))).await?;
+    enqueue(&mut tx, DomainEventKind::UserRestored, id, json!({ "user": user })).await?;
+    tx.commit().await?;
+    index_user(id).await;
+
+    Ok((StatusCode::OK, Json(user)))
+}
 
+async fn find_user(id: u64) -> Result<User, AppError> {
     let pool = db::get_pool().await?;
-    let email = payload.email.trim();
-    let name = payload.name.trim();
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ? AND deleted_at IS NULL")
+        .bind(id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(AppError::NotFound)
+}
 
-    sqlx::query("UPDATE users SET name = ?, email = ? WHERE id = ?")
-        .bind(name)
-        .bind(email)
+#[utoipa::path(
+    get, path = "/users/{id}", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(("id" = u64, Path, description = "Id user")),
+    responses((status = 200, body = User, headers(("ETag" = String, description = "Versi user, kirim kembali di If-Match"))), AppError)
+)]
+pub async fn get_user_by_id(Path(id): Path<u64>) -> Result<(StatusCode, [(HeaderName, String); 1], Json<User>), AppError> {
+    Ok(with_etag(find_user(id).await?))
+}
+
+//route lama GET /user/?id=
+#[utoipa::path(
+    get, path = "/user/", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(UserQuery),
+    responses((status = 200, body = User, headers(("ETag" = String, description = "Versi user, kirim kembali di If-Match"))), AppError)
+)]
+pub async fn get_user_edit(Query(user_query): Query<UserQuery>) -> Result<(StatusCode, [(HeaderName, String); 1], Json<User>), AppError> {
+    Ok(with_etag(find_user(user_query.id).await?))
+}
+
+fn with_etag(user: User) -> (StatusCode, [(HeaderName, String); 1], Json<User>) {
+    (StatusCode::OK, [(header::ETAG, user_etag(&user))], Json(user))
+}
+
+//dipakai PUT dan PATCH: baris dikunci agar cek If-Match dan email unik tidak balapan dengan update lain
+async fn update_user(id: u64, name: Option<&str>, email: Option<&str>, attributes: Option<AttributeChange<'_>>, headers: &HeaderMap, ctx: &AuditContext) -> Result<User, AppError> {
+    let pool = db::get_pool().await?;
+    let mut tx = pool.begin().await?;
+    let result = update_user_in(&mut tx, id, name, email, attributes, if_match(headers).as_ref(), ctx).await?;
+    tx.commit().await?;
+    index_user(id).await;
+
+    Ok(result)
+}
+
+//harus dijalankan di dalam transaksi agar FOR UPDATE menahan lock sampai commit
+pub async fn update_user_in(conn: &mut MySqlConnection, id: u64, name: Option<&str>, email: Option<&str>, attributes: Option<AttributeChange<'_>>, condition: Option<&IfMatch>, ctx: &AuditContext) -> Result<User, AppError> {
+    let current = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ? AND deleted_at IS NULL FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    if let Some(condition) = condition && !condition.matches(&user_etag(&current)) {
+        return Err(AppError::PreconditionFailed);
+    }
+
+    //atribut divalidasi ulang sesudah digabung, definisi bisa saja berubah sejak atribut lama disimpan
+    let attributes = match attributes {
+        Some(change) => {
+            let merged = match change {
+                AttributeChange::Replace(values) => values.clone(),
+                AttributeChange::Merge(patch) => merge_attributes(&current.attributes, patch),
+            };
+            let definitions = load_definitions(&mut *conn).await?;
+            validate_attributes(&definitions, &merged).map_err(AppError::ValidationError)?
+        }
+        None => current.attributes.clone(),
+    };
+
+    if let Some(email) = email {
+        let (taken,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users WHERE email = ? AND id <> ?")
+            .bind(email)
+            .bind(id)
+            .fetch_one(&mut *conn)
+            .await?;
+        if taken > 0 {
+            return Err(AppError::Conflict);
+        }
+    }
+
+    sqlx::query("UPDATE users SET name = ?, email = ?, attributes = ? WHERE id = ?")
+        .bind(name.unwrap_or(&current.name))
+        .bind(email.unwrap_or(&current.email))
+        .bind(SqlJson(attributes))
         .bind(id)
-        .execute(&pool)
+        .execute(&mut *conn)
         .await?;
 
     let result = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
         .bind(id)
-        .fetch_one(&pool)
+        .fetch_one(&mut *conn)
         .await?;
 
-    Ok((StatusCode::OK, Json(result)))
+    //update tanpa perubahan tidak dicatat dan tidak menghasilkan event
+    if let Some(changes) = user_changes(Some(&current), Some(&result)) {
+        let data = json!({ "user": result, "changed": changes.keys().collect::<Vec<_>>() });
+        enqueue(conn, DomainEventKind::UserUpdated, id, data).await?;
+        append(conn, ctx, AuditAction::UserUpdate, Some(id), Some(changes)).await?;
+    }
+    Ok(result)
 }
 
-pub async fn login_user(payload:Json<UserLogin>)-> Result<(StatusCode, CookieJar), AppError>{
+#[utoipa::path(
+    put, path = "/users/{id}", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    params(("id" = u64, Path, description = "Id user"), ("If-Match" = Option<String>, Header, description = "ETag dari GET, 412 jika user sudah berubah")),
+    request_body = UserUpdate,
+    responses((status = 200, body = User, headers(("ETag" = String, description = "Versi user, kirim kembali di If-Match"))), AppError)
+)]
+pub async fn edit_user(ctx: AuditContext, Path(id): Path<u64>, headers: HeaderMap, payload: Json<UserUpdate>) -> Result<(StatusCode, [(HeaderName, String); 1], Json<User>), AppError> {
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let attributes = payload.attributes.as_ref().map(AttributeChange::Replace);
+    let user = update_user(id, Some(payload.name.trim()), Some(payload.email.trim()), attributes, &headers, &ctx).await?;
+    Ok(with_etag(user))
+}
+
+#[utoipa::path(
+    patch, path = "/users/{id}", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    params(("id" = u64, Path, description = "Id user"), ("If-Match" = Option<String>, Header, description = "ETag dari GET, 412 jika user sudah berubah")),
+    request_body(content = UserPatch, content_type = "application/merge-patch+json"),
+    responses((status = 200, body = User, headers(("ETag" = String, description = "Versi user, kirim kembali di If-Match"))), AppError)
+)]
+pub async fn patch_user(ctx: AuditContext, Path(id): Path<u64>, headers: HeaderMap, payload: Json<UserPatch>) -> Result<(StatusCode, [(HeaderName, String); 1], Json<User>), AppError> {
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let attributes = payload.attributes.as_ref().map(AttributeChange::Merge);
+    let user = update_user(id, payload.name.as_deref().map(str::trim), payload.email.as_deref().map(str::trim), attributes, &headers, &ctx).await?;
+    Ok(with_etag(user))
+}
+
+#[utoipa::path(
+    post, path = "/login", tag = "auth", security(("api_key" = []), ("oauth_bearer" = [])), request_body = UserLogin,
+    responses(
+        (status = 200, description = "Login berhasil", headers(("Set-Cookie" = String, description = "Cookie jwt httpOnly"))),
+        (status = 202, body = TwoFactorChallenge, description = "2FA aktif, lanjutkan ke /login/totp"),
+        AppError
+    )
+)]
+pub async fn login_user(ctx: AuditContext, payload:Json<UserLogin>)-> Result<Response, AppError>{
     payload.validate().map_err(AppError::ValidationError)?;
     let pool = db::get_pool().await?;
     let email = payload.email.trim();
     let password = payload.password.trim();
 
-    match check_email(email).await? {
-        false => return Err(AppError::NotFound),
-        _ => (),
+    if !check_email(email).await? {
+        return Err(AppError::NotFound);
     }
 
-    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = ?")
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = ? AND deleted_at IS NULL")
         .bind(email)
-        .fetch_one(&pool).await?;
+        .fetch_optional(&pool).await?
+        .ok_or(AppError::NotFound)?;
 
-    if !verify_password(&user.password, password).await? {
+    if !verify_password(user.password.as_deref(), password).await? {
+        record(&pool, &ctx, AuditAction::LoginFailed, Some(user.id)).await?;
         return Err(AppError::Unauthorized);
     }
+    if let Some(hash) = user.password.as_deref() {
+        rehash_if_needed(&pool, &load_config()?.password_hash, user.id, hash, password).await;
+    }
+
+    //user dengan 2FA aktif mendapat challenge token, bukan cookie jwt
+    if is_totp_enabled(&pool, user.id).await? {
+        return Ok((StatusCode::ACCEPTED, Json(two_factor_challenge(user.id)?)).into_response());
+    }
+
+    let jar = start_session(&pool, user.id, &ctx.client).await?;
+    Ok((StatusCode::OK, jar).into_response())
+
+}
+
+#[utoipa::path(
+    post, path = "/logout", tag = "me", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    responses((status = 204), AppError)
+)]
+pub async fn logout_user(Extension(claims): Extension<Claims>) -> Result<(StatusCode, CookieJar), AppError> {
+    let pool = db::get_pool().await?;
+    if let Some(sid) = claims.sid.as_deref() {
+        revoke_session(&pool, claims.sub, sid).await?;
+    }
+
+    let jar = remove_session_cookie(CookieJar::new());
+    Ok((StatusCode::NO_CONTENT, jar))
+}
+//ganti password lalu keluarkan semua session lain milik user
+#[utoipa::path(
+    put, path = "/me/password", tag = "me", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), request_body = PasswordChange,
+    responses((status = 204), AppError)
+)]
+pub async fn change_password(ctx: AuditContext, Extension(claims): Extension<Claims>, Json(payload): Json<PasswordChange>) -> Result<StatusCode, AppError> {
+    payload.validate().map_err(AppError::ValidationError)?;
+    let pool = db::get_pool().await?;
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ? AND deleted_at IS NULL")
+        .bind(claims.sub)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    if user.password.is_some() {
+        let current = payload.current_password.as_deref().unwrap_or_default().trim();
+        if !verify_password(user.password.as_deref(), current).await? {
+            return Err(AppError::Unauthorized);
+        }
+    }
 
-    let token = create_jwt(user.id)?;
+    let new_password = payload.new_password.trim();
+    enforce_password_policy(new_password, &user.name, &user.email).await?;
+    let password_hash = hashing_password(new_password).await?;
 
-    let jar = CookieJar::new().add(Cookie::new("jwt", token));
-    Ok((StatusCode::OK, jar))
+    let mut tx = pool.begin().await?;
+    sqlx::query("UPDATE users SET password = ? WHERE id = ?")
+        .bind(password_hash)
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE sessions SET revoked_at = NOW() WHERE user_id = ? AND id <> ? AND revoked_at IS NULL")
+        .bind(user.id)
+        .bind(claims.sid.as_deref().unwrap_or_default())
+        .execute(&mut *tx)
+        .await?;
+    append(&mut tx, &ctx, AuditAction::PasswordChange, Some(user.id), None).await?;
+    tx.commit().await?;
 
-}
\ No newline at end of file
+    Ok(StatusCode::NO_CONTENT)
+}
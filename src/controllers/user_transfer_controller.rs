@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use axum::{Json, body::{Body, Bytes}, extract::Query, response::{IntoResponse, Response}};
+use futures::{StreamExt, TryStreamExt, stream};
+use http::{HeaderMap, StatusCode, header};
+use sqlx::{MySql, Pool, Transaction};
+use validator::Validate;
+
+use crate::{
+    configs::db,
+    controllers::user_controller::insert_new_user,
+    errors::app_error::AppError,
+    models::{
+        attribute_model::{AttributeDefinition, AttributeKind, AttributeValues},
+        transfer_model::{ExportParams, ImportParams, ImportReport, ImportRowResult, ImportRowStatus, TransferFormat},
+        user_model::{User, UserInsert},
+    },
+    utils::{attributes::{combine_errors, load_definitions, parse_attribute_text, validate_attributes}, audit::AuditContext, csv, password_policy::enforce_password_policy, search_index::reindex_all, utils::{hashing_password, validation_messages}},
+};
+
+//batas agar satu request tidak menahan koneksi dan memori terlalu lama
+pub const MAX_IMPORT_ROWS: usize = 10_000;
+pub const MAX_RECORD_BYTES: usize = 64 * 1024;
+const EXPORT_BATCH: i64 = 500;
+const CSV_COLUMNS: [&str; 3] = ["name", "email", "password"];
+
+fn import_format(headers: &HeaderMap) -> Result<TransferFormat, AppError> {
+    let content_type = headers.get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    match content_type.as_str() {
+        "text/csv" => Ok(TransferFormat::Csv),
+        "application/x-ndjson" | "application/jsonl" => Ok(TransferFormat::Ndjson),
+        _ => Err(AppError::UnsupportedMediaType),
+    }
+}
+
+fn is_unique_violation(error: &sqlx::Error) -> bool {
+    error.as_database_error().is_some_and(|e| e.is_unique_violation())
+}
+
+fn failed(row: usize, email: Option<String>, errors: Vec<String>) -> ImportRowResult {
+    ImportRowResult { row, email, status: ImportRowStatus::Failed, id: None, errors }
+}
+
+struct Importer {
+    format: TransferFormat,
+    dry_run: bool,
+    ctx: AuditContext,
+    pool: Pool<MySql>,
+    //hanya ada pada mode atomic tanpa dry run
+    tx: Option<Transaction<'static, MySql>>,
+    //posisi kolom name, email, password setelah header CSV dibaca
+    columns: Option<[usize; 3]>,
+    //kolom CSV lain yang namanya sama dengan atribut custom (posisi, tipe, nama)
+    attribute_columns: Vec<(usize, AttributeKind, String)>,
+    definitions: Vec<AttributeDefinition>,
+    //record CSV yang field-nya masih berlanjut ke baris berikutnya
+    pending: String,
+    //email (lowercase) yang sudah muncul di file beserta nomor barisnya
+    seen: HashMap<String, usize>,
+    rows: Vec<ImportRowResult>,
+}
+
+impl Importer {
+    async fn line(&mut self, line: &[u8]) -> Result<(), AppError> {
+        let line = std::str::from_utf8(line).map_err(|_| AppError::BadRequest)?;
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        match self.format {
+            TransferFormat::Ndjson => {
+                if line.trim().is_empty() {
+                    return Ok(());
+                }
+                let parsed = serde_json::from_str::<UserInsert>(line).map_err(|e| format!("JSON tidak valid: {}", e));
+                self.record(parsed).await
+            }
+            TransferFormat::Csv => {
+                if !self.pending.is_empty() {
+                    self.pending.push('\n');
+                }
+                self.pending.push_str(line);
+                if csv::is_incomplete(&self.pending) {
+                    if self.pending.len() > MAX_RECORD_BYTES {
+                        return Err(AppError::PayloadTooLarge);
+                    }
+                    return Ok(());
+                }
+                let record = std::mem::take(&mut self.pending);
+                if record.trim().is_empty() {
+                    return Ok(());
+                }
+                self.csv_record(&record).await
+            }
+        }
+    }
+
+    async fn csv_record(&mut self, record: &str) -> Result<(), AppError> {
+        let fields = csv::parse_record(record);
+        let Some(columns) = self.columns else {
+            //header yang rusak membuat seluruh file tidak bisa dibaca
+            let header = fields.map_err(|_| AppError::BadRequest)?;
+            let position = |name: &str| header.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+            let [name, email, password] = CSV_COLUMNS.map(position);
+            self.columns = Some([name.ok_or(AppError::BadRequest)?, email.ok_or(AppError::BadRequest)?, password.ok_or(AppError::BadRequest)?]);
+            self.attribute_columns = self.definitions.iter()
+                .filter_map(|def| position(&def.name).map(|i| (i, def.kind, def.name.clone())))
+                .collect();
+            return Ok(());
+        };
+
+        let parsed = fields.and_then(|fields| {
+            let [name, email, password] = columns.map(|i| fields.get(i).cloned());
+            let attributes = self.attribute_columns.iter()
+                .filter_map(|(i, kind, attribute)| fields.get(*i).map(|text| (attribute.clone(), parse_attribute_text(*kind, text))))
+                .collect();
+            match (name, email, password) {
+                (Some(name), Some(email), Some(password)) => Ok(UserInsert { name, email, password, attributes }),
+                _ => Err(format!("jumlah kolom {} kurang dari header", fields.len())),
+            }
+        });
+        self.record(parsed).await
+    }
+
+    async fn record(&mut self, parsed: Result<UserInsert, String>) -> Result<(), AppError> {
+        let row = self.rows.len() + 1;
+        if row > MAX_IMPORT_ROWS {
+            return Err(AppError::PayloadTooLarge);
+        }
+        let result = match parsed {
+            Ok(user) => self.import(row, user).await?,
+            Err(reason) => failed(row, None, vec![reason]),
+        };
+        self.rows.push(result);
+        Ok(())
+    }
+
+    //aturan sama dengan POST /users, error per baris dicatat tanpa menghentikan import
+    async fn import(&mut self, row: usize, user: UserInsert) -> Result<ImportRowResult, AppError> {
+        let user = UserInsert { name: user.name.trim().to_string(), email: user.email.trim().to_string(), password: user.password.trim().to_string(), attributes: user.attributes };
+        let email = Some(user.email.clone());
+
+        let (mut errors, attributes) = match combine_errors(user.validate(), validate_attributes(&self.definitions, &user.attributes)) {
+            Ok(attributes) => (Vec::new(), attributes),
+            Err(e) => (validation_messages(&e), AttributeValues::new()),
+        };
+        match enforce_password_policy(&user.password, &user.name, &user.email).await {
+            Ok(()) => {}
+            Err(AppError::ValidationError(e)) => errors.extend(validation_messages(&e)),
+            Err(e) => return Err(e),
+        }
+        if !errors.is_empty() {
+            return Ok(failed(row, email, errors));
+        }
+
+        let key = user.email.to_lowercase();
+        if let Some(first) = self.seen.get(&key) {
+            return Ok(failed(row, email, vec![format!("email: duplikat dengan baris {}", first)]));
+        }
+        self.seen.insert(key, row);
+
+        //email milik user yang sudah dihapus tetap dipesan sampai di-purge
+        let exists = sqlx::query_as::<_, (u64,)>("SELECT id FROM users WHERE email = ?").bind(&user.email);
+        let exists = match self.tx.as_mut() {
+            Some(tx) => exists.fetch_optional(&mut **tx).await?,
+            None => exists.fetch_optional(&self.pool).await?,
+        };
+        if exists.is_some() {
+            return Ok(failed(row, email, vec!["email: sudah terdaftar".to_string()]));
+        }
+
+        if self.dry_run {
+            return Ok(ImportRowResult { row, email, status: ImportRowStatus::Valid, id: None, errors: Vec::new() });
+        }
+
+        //audit dan event UserCreated ikut transaksi import, atau transaksi per baris jika tidak atomic
+        let password_hash = hashing_password(&user.password).await?;
+        let inserted = match self.tx.as_mut() {
+            Some(tx) => insert_new_user(tx, &user.name, &user.email, Some(&password_hash), attributes, &self.ctx).await,
+            None => {
+                let mut tx = self.pool.begin().await?;
+                let inserted = insert_new_user(&mut tx, &user.name, &user.email, Some(&password_hash), attributes, &self.ctx).await;
+                if inserted.is_ok() {
+                    tx.commit().await?;
+                }
+                inserted
+            }
+        };
+        match inserted {
+            Ok(id) => Ok(ImportRowResult { row, email, status: ImportRowStatus::Created, id: Some(id), errors: Vec::new() }),
+            //dibuat oleh request lain setelah pengecekan di atas
+            Err(AppError::Db(e)) if is_unique_violation(&e) => Ok(failed(row, email, vec!["email: sudah terdaftar".to_string()])),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn finish(mut self, atomic: bool) -> Result<ImportReport, AppError> {
+        if !self.pending.trim().is_empty() {
+            let record = std::mem::take(&mut self.pending);
+            self.csv_record(&record).await?;
+        }
+        if self.format == TransferFormat::Csv && self.columns.is_none() {
+            return Err(AppError::BadRequest);
+        }
+
+        let failed = self.rows.iter().filter(|r| r.status == ImportRowStatus::Failed).count();
+        let committed = match self.tx.take() {
+            Some(tx) if failed == 0 => {
+                tx.commit().await?;
+                true
+            }
+            Some(tx) => {
+                tx.rollback().await?;
+                for row in self.rows.iter_mut().filter(|r| r.status == ImportRowStatus::Created) {
+                    row.status = ImportRowStatus::RolledBack;
+                    row.id = None;
+                }
+                false
+            }
+            None => !self.dry_run,
+        };
+
+        let created = self.rows.iter().filter(|r| r.status == ImportRowStatus::Created).count();
+        if created > 0 {
+            reindex_all().await;
+        }
+        Ok(ImportReport { dry_run: self.dry_run, atomic, committed, total: self.rows.len(), created, failed, rows: self.rows })
+    }
+}
+
+//body dibaca per chunk dan diproses per baris, file besar tidak pernah dimuat utuh ke memori
+#[utoipa::path(
+    post, path = "/users/import", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(ImportParams),
+    request_body(content((String = "text/csv"), (String = "application/x-ndjson")), description = "CSV dengan header name,email,password (kolom lain bernama atribut custom ikut diimport) atau satu UserInsert per baris"),
+    responses((status = 200, body = ImportReport), AppError)
+)]
+pub async fn import_users(ctx: AuditContext, Query(params): Query<ImportParams>, headers: HeaderMap, body: Body) -> Result<(StatusCode, Json<ImportReport>), AppError> {
+    let format = import_format(&headers)?;
+    let pool = db::get_pool().await?;
+    let definitions = load_definitions(&pool).await?;
+    let tx = if params.atomic && !params.dry_run { Some(pool.begin().await?) } else { None };
+    let mut importer = Importer {
+        format,
+        dry_run: params.dry_run,
+        ctx,
+        pool,
+        tx,
+        columns: None,
+        attribute_columns: Vec::new(),
+        definitions,
+        pending: String::new(),
+        seen: HashMap::new(),
+        rows: Vec::new(),
+    };
+
+    let mut stream = body.into_data_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        buffer.extend_from_slice(&chunk.map_err(|_| AppError::BadRequest)?);
+        while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            importer.line(&line).await?;
+        }
+        if buffer.len() > MAX_RECORD_BYTES {
+            return Err(AppError::PayloadTooLarge);
+        }
+    }
+    if !buffer.is_empty() {
+        importer.line(&buffer).await?;
+    }
+
+    Ok((StatusCode::OK, Json(importer.finish(params.atomic).await?)))
+}
+
+pub fn export_header(format: TransferFormat) -> String {
+    match format {
+        TransferFormat::Csv => csv::write_record(&["id", "name", "email", "created_at", "updated_at"]),
+        TransferFormat::Ndjson => String::new(),
+    }
+}
+
+pub fn export_row(format: TransferFormat, user: &User) -> Result<String, AppError> {
+    Ok(match format {
+        TransferFormat::Csv => csv::write_record(&[
+            &user.id.to_string(),
+            &user.name,
+            &user.email,
+            &user.created_at.to_rfc3339(),
+            &user.updated_at.to_rfc3339(),
+        ]),
+        TransferFormat::Ndjson => {
+            let mut line = serde_json::to_string(user).map_err(|_| AppError::InternalServerError)?;
+            line.push('\n');
+            line
+        }
+    })
+}
+
+//diambil per batch berdasarkan id, hanya satu batch yang ada di memori
+#[utoipa::path(
+    get, path = "/users/export", tag = "users", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(ExportParams),
+    responses((status = 200, content((String = "text/csv"), (String = "application/x-ndjson")), description = "User yang tidak dihapus, tanpa password"), AppError)
+)]
+pub async fn export_users(Query(params): Query<ExportParams>) -> Result<Response, AppError> {
+    let format = params.format.unwrap_or(TransferFormat::Ndjson);
+    let pool = db::get_pool().await?;
+
+    let batches = stream::try_unfold((pool, Some(0u64)), move |(pool, after)| async move {
+        let Some(after) = after else {
+            return Ok(None);
+        };
+        let users = sqlx::query_as::<_, User>("SELECT * FROM users WHERE deleted_at IS NULL AND id > ? ORDER BY id LIMIT ?")
+            .bind(after)
+            .bind(EXPORT_BATCH)
+            .fetch_all(&pool)
+            .await?;
+        let Some(last) = users.last() else {
+            return Ok(None);
+        };
+        let next = (users.len() as i64 == EXPORT_BATCH).then_some(last.id);
+        let chunk = users.iter().map(|u| export_row(format, u)).collect::<Result<String, AppError>>()?;
+        Ok::<_, AppError>(Some((Bytes::from(chunk), (pool, next))))
+    });
+    let body = stream::once(async move { Ok::<_, AppError>(Bytes::from(export_header(format))) })
+        .chain(batches)
+        .try_filter(|chunk| std::future::ready(!chunk.is_empty()));
+
+    let (content_type, filename) = match format {
+        TransferFormat::Csv => ("text/csv; charset=utf-8", "users.csv"),
+        TransferFormat::Ndjson => ("application/x-ndjson", "users.ndjson"),
+    };
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(body),
+    ).into_response())
+}
@@ -0,0 +1,88 @@
+use axum::{Json, extract::{Path, Query}};
+use http::{HeaderMap, HeaderName, StatusCode};
+
+use crate::{
+    controllers::user_controller,
+    errors::app_error::AppError,
+    models::{user_model::{User, UserListQuery, UserPatch, UserUpdate}, user_v2_model::UserV2},
+    utils::audit::AuditContext,
+};
+
+//handler v2 memakai logika v1, hanya bentuk id dan respons yang berbeda
+
+//id v2 berupa string, id yang bukan angka dianggap tidak ada
+fn parse_id(id: &str) -> Result<u64, AppError> {
+    id.parse().map_err(|_| AppError::NotFound)
+}
+
+fn one(res: (StatusCode, [(HeaderName, String); 1], Json<User>)) -> (StatusCode, [(HeaderName, String); 1], Json<UserV2>) {
+    let (status, etag, Json(user)) = res;
+    (status, etag, Json(user.into()))
+}
+
+fn many(res: (StatusCode, Json<Vec<User>>)) -> (StatusCode, Json<Vec<UserV2>>) {
+    let (status, Json(users)) = res;
+    (status, Json(users.into_iter().map(UserV2::from).collect()))
+}
+
+#[utoipa::path(
+    get, path = "/api/v2/users", operation_id = "list_users_v2", tag = "users v2", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(UserListQuery),
+    responses((status = 200, body = Vec<UserV2>), AppError)
+)]
+pub async fn list_users(query: Query<UserListQuery>) -> Result<(StatusCode, Json<Vec<UserV2>>), AppError> {
+    Ok(many(user_controller::list_users(query).await?))
+}
+
+#[utoipa::path(
+    get, path = "/api/v2/users/deleted", operation_id = "get_deleted_users_v2", tag = "users v2", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    responses((status = 200, body = Vec<UserV2>), AppError)
+)]
+pub async fn get_deleted_users() -> Result<(StatusCode, Json<Vec<UserV2>>), AppError> {
+    Ok(many(user_controller::get_deleted_users().await?))
+}
+
+#[utoipa::path(
+    get, path = "/api/v2/users/{id}", operation_id = "get_user_v2", tag = "users v2", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(("id" = String, Path, description = "Id user")),
+    responses((status = 200, body = UserV2, headers(("ETag" = String, description = "Versi user, kirim kembali di If-Match"))), AppError)
+)]
+pub async fn get_user(Path(id): Path<String>) -> Result<(StatusCode, [(HeaderName, String); 1], Json<UserV2>), AppError> {
+    Ok(one(user_controller::get_user_by_id(Path(parse_id(&id)?)).await?))
+}
+
+#[utoipa::path(
+    put, path = "/api/v2/users/{id}", operation_id = "edit_user_v2", tag = "users v2", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    params(("id" = String, Path, description = "Id user"), ("If-Match" = Option<String>, Header, description = "ETag dari GET, 412 jika user sudah berubah")),
+    request_body = UserUpdate,
+    responses((status = 200, body = UserV2, headers(("ETag" = String, description = "Versi user, kirim kembali di If-Match"))), AppError)
+)]
+pub async fn edit_user(ctx: AuditContext, Path(id): Path<String>, headers: HeaderMap, payload: Json<UserUpdate>) -> Result<(StatusCode, [(HeaderName, String); 1], Json<UserV2>), AppError> {
+    Ok(one(user_controller::edit_user(ctx, Path(parse_id(&id)?), headers, payload).await?))
+}
+
+#[utoipa::path(
+    patch, path = "/api/v2/users/{id}", operation_id = "patch_user_v2", tag = "users v2", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])),
+    params(("id" = String, Path, description = "Id user"), ("If-Match" = Option<String>, Header, description = "ETag dari GET, 412 jika user sudah berubah")),
+    request_body(content = UserPatch, content_type = "application/merge-patch+json"),
+    responses((status = 200, body = UserV2, headers(("ETag" = String, description = "Versi user, kirim kembali di If-Match"))), AppError)
+)]
+pub async fn patch_user(ctx: AuditContext, Path(id): Path<String>, headers: HeaderMap, payload: Json<UserPatch>) -> Result<(StatusCode, [(HeaderName, String); 1], Json<UserV2>), AppError> {
+    Ok(one(user_controller::patch_user(ctx, Path(parse_id(&id)?), headers, payload).await?))
+}
+
+#[utoipa::path(
+    delete, path = "/api/v2/users/{id}", operation_id = "delete_user_v2", tag = "users v2", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(("id" = String, Path, description = "Id user")),
+    responses((status = 204), AppError)
+)]
+pub async fn delete_user(ctx: AuditContext, Path(id): Path<String>) -> Result<StatusCode, AppError> {
+    let (status, _) = user_controller::delete_user_by_id(ctx, Path(parse_id(&id)?)).await?;
+    Ok(status)
+}
+
+#[utoipa::path(
+    post, path = "/api/v2/users/{id}/restore", operation_id = "restore_user_v2", tag = "users v2", security(("api_key" = [], "session" = []), ("oauth_bearer" = [], "session" = [])), params(("id" = String, Path, description = "Id user")),
+    responses((status = 200, body = UserV2), AppError)
+)]
+pub async fn restore_user(ctx: AuditContext, Path(id): Path<String>) -> Result<(StatusCode, Json<UserV2>), AppError> {
+    let (status, Json(user)) = user_controller::restore_user(ctx, Path(parse_id(&id)?)).await?;
+    Ok((status, Json(user.into())))
+}
@@ -0,0 +1,178 @@
+use utoipa::{
+    Modify, OpenApi,
+    openapi::{
+        OpenApi as OpenApiSpec,
+        path::{Operation, PathItem},
+        security::{ApiKey, ApiKeyValue, Http, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    },
+};
+
+use crate::controllers::{attribute_controller, audit_controller, avatar_controller, key_controller, oauth_controller, oidc_controller, passkey_controller, session_controller, totp_controller, user_batch_controller, user_controller, user_transfer_controller, user_v2_controller};
+
+//alias lama yang memakai handler yang sama dengan route /users: (method, path lama, path baru)
+pub const LEGACY_ALIASES: [(&str, &str, &str); 5] = [
+    ("post", "/user", "/users"),
+    ("get", "/user/deleted", "/users/deleted"),
+    ("put", "/user/{id}", "/users/{id}"),
+    ("patch", "/user/{id}", "/users/{id}"),
+    ("post", "/user/{id}/restore", "/users/{id}/restore"),
+];
+
+//route v2 yang memakai handler v1 tanpa perubahan: (method, path v2, path v1)
+pub const V2_SHARED: [(&str, &str, &str); 1] = [
+    ("post", "/api/v2/users", "/users"),
+];
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "User API", description = "Path tanpa prefix sama dengan /api/v1. /api/v2 hanya mengubah route /users (id berupa string), route lain sama dengan v1. Route /user sudah deprecated, gunakan /users."),
+    paths(
+        user_controller::list_users,
+        user_controller::insert_user,
+        user_controller::get_deleted_users,
+        user_controller::search_users,
+        user_transfer_controller::import_users,
+        user_transfer_controller::export_users,
+        user_batch_controller::batch_users,
+        attribute_controller::list_attributes,
+        attribute_controller::put_attribute,
+        attribute_controller::delete_attribute,
+        user_controller::get_user_by_id,
+        user_controller::edit_user,
+        user_controller::patch_user,
+        user_controller::delete_user_by_id,
+        user_controller::restore_user,
+        user_controller::get_all_user,
+        user_controller::get_user,
+        user_controller::get_user_edit,
+        user_controller::delete_user,
+        user_controller::login_user,
+        user_controller::logout_user,
+        user_controller::change_password,
+        user_v2_controller::list_users,
+        user_v2_controller::get_deleted_users,
+        user_v2_controller::get_user,
+        user_v2_controller::edit_user,
+        user_v2_controller::patch_user,
+        user_v2_controller::delete_user,
+        user_v2_controller::restore_user,
+        audit_controller::list_audit,
+        audit_controller::verify_audit,
+        avatar_controller::upload_avatar,
+        avatar_controller::get_avatar,
+        session_controller::list_sessions,
+        session_controller::delete_session,
+        totp_controller::setup_totp,
+        totp_controller::confirm_totp,
+        totp_controller::disable_totp,
+        totp_controller::login_totp,
+        passkey_controller::list_passkeys,
+        passkey_controller::passkey_register,
+        passkey_controller::passkey_register_options,
+        passkey_controller::delete_passkey,
+        passkey_controller::passkey_login_options,
+        passkey_controller::passkey_login,
+        key_controller::jwks,
+        oidc_controller::list_oidc_providers,
+        oidc_controller::oidc_start,
+        oidc_controller::oidc_callback,
+        oauth_controller::list_clients,
+        oauth_controller::create_client,
+        oauth_controller::delete_client,
+        oauth_controller::authorize,
+        oauth_controller::token,
+        oauth_controller::introspect,
+        oauth_controller::revoke,
+    ),
+    modifiers(&SecuritySchemes, &LegacyRoutes, &V2Routes),
+    tags(
+        (name = "users", description = "Data user"),
+        (name = "users v2", description = "Data user di /api/v2, id berupa string"),
+        (name = "attributes", description = "Definisi atribut custom profil user"),
+        (name = "me", description = "Akun user yang sedang login"),
+        (name = "auth", description = "Login"),
+        (name = "audit", description = "Log audit perubahan data dan login"),
+        (name = "oidc", description = "Login lewat provider eksternal"),
+        (name = "oauth", description = "OAuth2 authorization server"),
+        (name = "keys", description = "Kunci publik jwt"),
+    )
+)]
+pub struct ApiDoc;
+
+//Cargo.toml tidak punya license, jangan tampilkan license kosong
+pub fn api_doc() -> OpenApiSpec {
+    let mut doc = ApiDoc::openapi();
+    doc.info.license = None;
+    doc
+}
+
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut OpenApiSpec) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme("api_key", SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-KEY"))));
+        components.add_security_scheme("session", SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new("jwt"))));
+        components.add_security_scheme("oauth_bearer", SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()));
+        components.add_security_scheme("oauth_client", SecurityScheme::Http(Http::new(HttpAuthScheme::Basic)));
+    }
+}
+
+fn operation_mut<'a>(item: &'a mut PathItem, method: &str) -> &'a mut Option<Operation> {
+    match method {
+        "get" => &mut item.get,
+        "post" => &mut item.post,
+        "put" => &mut item.put,
+        "patch" => &mut item.patch,
+        "delete" => &mut item.delete,
+        _ => unreachable!("method {} tidak dipakai di LEGACY_ALIASES atau V2_SHARED", method),
+    }
+}
+
+//menyalin operasi ke path lain dengan operation_id yang diberi akhiran
+fn copy_operation(openapi: &mut OpenApiSpec, method: &str, from: &str, to: &str, suffix: &str) {
+    let Some(mut operation) = openapi.paths.paths.get_mut(from).and_then(|item| operation_mut(item, method).clone()) else {
+        return;
+    };
+    operation.operation_id = operation.operation_id.map(|id| format!("{}_{}", id, suffix));
+    let item = openapi.paths.paths.entry(to.to_string()).or_default();
+    *operation_mut(item, method) = Some(operation);
+}
+
+pub fn is_legacy_path(path: &str) -> bool {
+    path == "/user" || path.starts_with("/user/")
+}
+
+//alias lama disalin dari operasi /users, lalu semua route /user ditandai deprecated
+struct LegacyRoutes;
+
+impl Modify for LegacyRoutes {
+    fn modify(&self, openapi: &mut OpenApiSpec) {
+        for (method, legacy, successor) in LEGACY_ALIASES {
+            copy_operation(openapi, method, successor, legacy, "legacy");
+        }
+
+        for (path, item) in openapi.paths.paths.iter_mut().filter(|(path, _)| is_legacy_path(path)) {
+            for method in ["get", "post", "put", "patch", "delete"] {
+                if let Some(operation) = operation_mut(item, method) {
+                    operation.deprecated = Some(utoipa::openapi::Deprecated::True);
+                    operation.description = Some(format!("Deprecated, gunakan {}. Respons membawa header Deprecation dan Sunset.", path.replacen("/user", "/users", 1)));
+                }
+            }
+        }
+    }
+}
+
+//handler v1 yang juga dipasang di /api/v2 disalin ke path v2 dengan tag v2
+struct V2Routes;
+
+impl Modify for V2Routes {
+    fn modify(&self, openapi: &mut OpenApiSpec) {
+        for (method, v2, v1) in V2_SHARED {
+            copy_operation(openapi, method, v1, v2, "v2");
+            if let Some(operation) = openapi.paths.paths.get_mut(v2).and_then(|item| operation_mut(item, method).as_mut()) {
+                operation.tags = Some(vec!["users v2".to_string()]);
+            }
+        }
+    }
+}
@@ -1,13 +1,18 @@
 use argon2::password_hash::Error as PasswordHashError;
 use axum::response::IntoResponse;
 use http::StatusCode;
+use std::collections::BTreeMap;
 use thiserror::Error;
+use utoipa::{IntoResponses, openapi::{ContentBuilder, RefOr, ResponseBuilder, Type, response::Response, schema::ObjectBuilder}};
 
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Database error")]
     Db(#[from] sqlx::Error),
 
+    #[error("Migration error")]
+    MigrateError(#[from] sqlx::migrate::MigrateError),
+
     #[error("Validation error")]
     ValidationError(#[from] validator::ValidationErrors),
 
@@ -23,6 +28,17 @@ pub enum AppError {
     #[error("JWT error: {0}")]
     JwtError(#[from] jsonwebtoken::errors::Error),
 
+    #[error("HTTP client error: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Search index error: {0}")]
+    SearchIndexError(#[from] tantivy::TantivyError),
+
+    //belum dipakai handler mana pun, dipertahankan untuk kompatibilitas
+    #[allow(dead_code)]
     #[error("Cookie error")]
     CookieError,
 
@@ -44,6 +60,18 @@ pub enum AppError {
     #[error("Forbidden access")]
     Forbidden,
 
+    #[error("Precondition failed")]
+    PreconditionFailed,
+
+    #[error("Not acceptable")]
+    NotAcceptable,
+
+    #[error("Payload too large")]
+    PayloadTooLarge,
+
+    #[error("Unsupported media type")]
+    UnsupportedMediaType,
+
 }
 
 impl IntoResponse for AppError {
@@ -53,6 +81,10 @@ impl IntoResponse for AppError {
                 eprintln!("DB ERROR: {:?}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
             }
+            AppError::MigrateError(e) => {
+                eprintln!("MIGRATION ERROR: {:?}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+            }
             AppError::NotFound => {
                 (StatusCode::NOT_FOUND, "Data not found").into_response()
             }
@@ -88,6 +120,18 @@ impl IntoResponse for AppError {
                 eprintln!("JWT ERROR: {:?}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "JWT error").into_response()
             }
+            AppError::HttpError(e) => {
+                eprintln!("HTTP ERROR: {:?}", e);
+                (StatusCode::BAD_GATEWAY, "Upstream error").into_response()
+            }
+            AppError::IoError(e) => {
+                eprintln!("IO ERROR: {:?}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response()
+            }
+            AppError::SearchIndexError(e) => {
+                eprintln!("SEARCH INDEX ERROR: {:?}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Search index error").into_response()
+            }
             AppError::CookieError => {
                 eprintln!("Cookie ERROR");
                 (StatusCode::INTERNAL_SERVER_ERROR, "Cookie error").into_response()
@@ -95,6 +139,44 @@ impl IntoResponse for AppError {
             AppError::Forbidden => {
                 (StatusCode::FORBIDDEN, "Forbidden access").into_response()
             }
+            AppError::PreconditionFailed => {
+                (StatusCode::PRECONDITION_FAILED, "Precondition failed").into_response()
+            }
+            AppError::NotAcceptable => {
+                (StatusCode::NOT_ACCEPTABLE, "Not acceptable").into_response()
+            }
+            AppError::PayloadTooLarge => {
+                (StatusCode::PAYLOAD_TOO_LARGE, "Payload too large").into_response()
+            }
+            AppError::UnsupportedMediaType => {
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, "Unsupported media type").into_response()
+            }
         }
     }
 }
+
+//error dikirim sebagai teks biasa, dipakai di dokumentasi OpenAPI setiap endpoint
+impl IntoResponses for AppError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        [
+            ("400", "Bad request atau validation error"),
+            ("401", "Unauthorized access"),
+            ("403", "Forbidden access"),
+            ("404", "Data not found"),
+            ("406", "Not acceptable"),
+            ("409", "Conflict"),
+            ("412", "Precondition failed"),
+            ("413", "Payload too large"),
+            ("415", "Unsupported media type"),
+            ("500", "Internal server error"),
+            ("502", "Upstream error"),
+        ]
+        .into_iter()
+        .map(|(status, description)| {
+            let content = ContentBuilder::new().schema(Some(ObjectBuilder::new().schema_type(Type::String))).build();
+            let response = ResponseBuilder::new().description(description).content("text/plain", content).build();
+            (status.to_string(), RefOr::T(response))
+        })
+        .collect()
+    }
+}
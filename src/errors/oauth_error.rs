@@ -0,0 +1,72 @@
+use axum::{Json, response::IntoResponse};
+use http::{StatusCode, header};
+use serde_json::json;
+use std::collections::BTreeMap;
+use thiserror::Error;
+use utoipa::{IntoResponses, openapi::{ContentBuilder, RefOr, ResponseBuilder, Type, response::Response, schema::ObjectBuilder}};
+
+use crate::errors::app_error::AppError;
+
+//error endpoint token OAuth2 (RFC 6749 5.2), dikirim sebagai {"error": "..."}
+#[derive(Debug, Error)]
+pub enum OAuthError {
+    #[error("invalid_request")]
+    InvalidRequest,
+
+    #[error("invalid_client")]
+    InvalidClient,
+
+    #[error("invalid_grant")]
+    InvalidGrant,
+
+    #[error("unauthorized_client")]
+    UnauthorizedClient,
+
+    #[error("unsupported_grant_type")]
+    UnsupportedGrantType,
+
+    #[error("invalid_scope")]
+    InvalidScope,
+
+    #[error(transparent)]
+    App(#[from] AppError),
+}
+
+impl From<sqlx::Error> for OAuthError {
+    fn from(e: sqlx::Error) -> Self {
+        OAuthError::App(e.into())
+    }
+}
+
+impl IntoResponse for OAuthError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self {
+            OAuthError::App(e) => return e.into_response(),
+            OAuthError::InvalidClient => StatusCode::UNAUTHORIZED,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        let body = Json(json!({ "error": self.to_string() }));
+
+        if status == StatusCode::UNAUTHORIZED {
+            (status, [(header::WWW_AUTHENTICATE, "Basic realm=\"oauth\"")], body).into_response()
+        } else {
+            (status, [(header::CACHE_CONTROL, "no-store")], body).into_response()
+        }
+    }
+}
+
+//400/401 memakai body {"error": "..."}, error lain sama dengan AppError
+impl IntoResponses for OAuthError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        let mut responses = AppError::responses();
+        for (status, description) in [("400", "Error OAuth2 (RFC 6749 5.2)"), ("401", "invalid_client")] {
+            let schema = ObjectBuilder::new()
+                .property("error", ObjectBuilder::new().schema_type(Type::String))
+                .required("error");
+            let content = ContentBuilder::new().schema(Some(schema)).build();
+            let response = ResponseBuilder::new().description(description).content("application/json", content).build();
+            responses.insert(status.to_string(), RefOr::T(response));
+        }
+        responses
+    }
+}
@@ -0,0 +1,66 @@
+use sqlx::{MySql, Pool};
+
+use crate::{configs::db, errors::app_error::AppError, utils::{blob_store::{BlobStore, blob_store}, utils::load_config}};
+
+//jumlah blob yang diperiksa per putaran
+const BATCH_SIZE: u32 = 500;
+
+//blob yatim: tidak direferensikan user_blobs dan tidak dipakai selama masa tenggang
+const ORPHAN_CONDITION: &str = "NOT EXISTS (SELECT 1 FROM user_blobs ub WHERE ub.blob_key = b.blob_key) \
+    AND b.last_used_at < NOW() - INTERVAL ? HOUR";
+
+//hapus blob yatim dari store lalu dari tabel blobs, mengembalikan jumlah blob yang dihapus
+pub async fn collect_garbage(pool: &Pool<MySql>, store: &impl BlobStore, grace_hours: u32) -> Result<u64, AppError> {
+    let mut removed = 0;
+    let mut after = String::new();
+    loop {
+        let keys: Vec<(String,)> = sqlx::query_as(&format!("SELECT b.blob_key FROM blobs b WHERE {} AND b.blob_key > ? ORDER BY b.blob_key LIMIT ?", ORPHAN_CONDITION))
+            .bind(grace_hours)
+            .bind(&after)
+            .bind(BATCH_SIZE)
+            .fetch_all(pool)
+            .await?;
+
+        for (key,) in &keys {
+            //dicek ulang dengan lock, blob bisa dipakai lagi sejak SELECT di atas
+            let mut tx = pool.begin().await?;
+            let locked: Option<(String,)> = sqlx::query_as(&format!("SELECT b.blob_key FROM blobs b WHERE b.blob_key = ? AND {} FOR UPDATE", ORPHAN_CONDITION))
+                .bind(key)
+                .bind(grace_hours)
+                .fetch_optional(&mut *tx)
+                .await?;
+            if locked.is_none() {
+                continue;
+            }
+            //objek dihapus dulu, jika gagal baris tetap ada dan dicoba lagi di putaran berikutnya
+            if let Err(e) = store.delete(key).await {
+                eprintln!("BLOB GC: gagal menghapus {}: {:?}", key, e);
+                continue;
+            }
+            sqlx::query("DELETE FROM blobs WHERE blob_key = ?")
+                .bind(key)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            removed += 1;
+        }
+
+        match keys.last() {
+            Some((key,)) if keys.len() == BATCH_SIZE as usize => after = key.clone(),
+            _ => return Ok(removed),
+        }
+    }
+}
+
+pub async fn gc_once() -> Result<u64, AppError> {
+    let cfg = load_config()?.blob_store;
+    let pool = db::get_pool().await?;
+    collect_garbage(&pool, &blob_store(&cfg)?, cfg.gc_grace_hours).await
+}
+
+//dipakai perintah CLI gc-blobs
+pub async fn gc_now() -> Result<(), AppError> {
+    let count = gc_once().await?;
+    println!("{} blob dihapus", count);
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use tokio::signal::unix::{SignalKind, signal};
+
+use crate::utils::utils::reload_jwt_keys;
+
+//SIGHUP memuat ulang kunci jwt sehingga rotasi kunci tidak perlu restart
+pub async fn run_on_sighup() {
+    let Ok(mut hangup) = signal(SignalKind::hangup()) else {
+        eprintln!("JWT ERROR: tidak bisa memasang handler SIGHUP");
+        return;
+    };
+    while hangup.recv().await.is_some() {
+        match reload_jwt_keys() {
+            Ok(_) => println!("JWT: kunci dimuat ulang"),
+            Err(e) => eprintln!("JWT ERROR: gagal memuat ulang kunci, kunci lama tetap dipakai: {:?}", e),
+        }
+    }
+}
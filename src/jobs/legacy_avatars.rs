@@ -0,0 +1,135 @@
+use sqlx::{MySql, Pool};
+
+use crate::{
+    configs::db,
+    controllers::avatar_controller::AVATAR_PREFIX,
+    errors::app_error::AppError,
+    utils::{blob_store::{BlobStore, blob_store, save_blob}, utils::load_config},
+};
+
+//jumlah user yang dipindahkan per putaran
+const BATCH_SIZE: u32 = 100;
+
+//kolom users.avatar_key dihapus migrasi 0012, prefixnya disalin ke legacy_avatar_keys selama kolom masih ada
+pub async fn preserve_avatar_keys(pool: &Pool<MySql>) -> Result<(), AppError> {
+    let (exists,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'users' AND COLUMN_NAME = 'avatar_key'"
+    )
+        .fetch_one(pool)
+        .await?;
+    if exists == 0 {
+        return Ok(());
+    }
+    //tabel yang sama dibuat migrasi 0017, di sini dibuat lebih awal karena 0012 berjalan sebelumnya
+    sqlx::query("CREATE TABLE IF NOT EXISTS legacy_avatar_keys (user_id BIGINT UNSIGNED NOT NULL PRIMARY KEY, avatar_key VARCHAR(255) NOT NULL)")
+        .execute(pool)
+        .await?;
+    sqlx::query("REPLACE INTO legacy_avatar_keys (user_id, avatar_key) SELECT id, avatar_key FROM users WHERE avatar_key IS NOT NULL")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+//avatar lama disimpan per upload di {avatar_key}/{ukuran}.png, dipindah ke blob berbasis hash isi
+//lalu direferensikan lewat user_blobs. baris legacy_avatar_keys dihapus setelah selesai sehingga job aman diulang
+async fn migrate_user(pool: &Pool<MySql>, store: &impl BlobStore, user_id: u64, prefix: &str, sizes: &[u32]) -> Result<(), AppError> {
+    let mut keys = Vec::new();
+    for size in sizes {
+        let old_key = format!("{}/{}.png", prefix, size);
+        if let Some(blob) = store.get(&old_key).await? {
+            keys.push((*size, save_blob(store, pool, AVATAR_PREFIX, "png", "image/png", blob.data).await?));
+        }
+    }
+    let avatar_url = keys.last().map(|(_, key)| store.url(key));
+
+    let mut tx = pool.begin().await?;
+    //baris user dikunci dulu seperti upload avatar agar urutan lock sama
+    sqlx::query("SELECT id FROM users WHERE id = ? FOR UPDATE")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    //upload baru sejak SELECT awal sudah menghapus baris legacy, avatar lama tidak boleh menimpanya
+    let current: Option<(String,)> = sqlx::query_as("SELECT avatar_key FROM legacy_avatar_keys WHERE user_id = ? FOR UPDATE")
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if current.as_ref().map(|(key,)| key.as_str()) != Some(prefix) {
+        return Ok(());
+    }
+    sqlx::query("DELETE FROM user_blobs WHERE user_id = ? AND purpose LIKE 'avatar\\_%'")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    for (size, key) in &keys {
+        sqlx::query("INSERT INTO user_blobs (user_id, purpose, blob_key) VALUES (?, ?, ?)")
+            .bind(user_id)
+            .bind(format!("avatar_{}", size))
+            .bind(key)
+            .execute(&mut *tx)
+            .await?;
+    }
+    sqlx::query("UPDATE users SET avatar_url = ? WHERE id = ?")
+        .bind(avatar_url)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM legacy_avatar_keys WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    //salinan sudah tersimpan sebagai blob, gagal hapus hanya menyisakan file yatim
+    for size in sizes {
+        let old_key = format!("{}/{}.png", prefix, size);
+        if let Err(e) = store.delete(&old_key).await {
+            eprintln!("LEGACY AVATAR: gagal menghapus {}: {:?}", old_key, e);
+        }
+    }
+    Ok(())
+}
+
+//mengembalikan jumlah user yang avatarnya dipindahkan, user yang gagal dicoba lagi saat start berikutnya
+pub async fn migrate_legacy_avatars(pool: &Pool<MySql>, store: &impl BlobStore, sizes: &[u32]) -> Result<u64, AppError> {
+    let mut sizes: Vec<u32> = sizes.iter().copied().filter(|s| *s > 0).collect();
+    sizes.sort();
+    sizes.dedup();
+
+    let mut migrated = 0;
+    let mut after = 0;
+    loop {
+        let users: Vec<(u64, String)> = sqlx::query_as("SELECT user_id, avatar_key FROM legacy_avatar_keys WHERE user_id > ? ORDER BY user_id LIMIT ?")
+            .bind(after)
+            .bind(BATCH_SIZE)
+            .fetch_all(pool)
+            .await?;
+
+        for (user_id, prefix) in &users {
+            match migrate_user(pool, store, *user_id, prefix, &sizes).await {
+                Ok(()) => migrated += 1,
+                Err(e) => eprintln!("LEGACY AVATAR: gagal memindahkan avatar user {}: {:?}", user_id, e),
+            }
+        }
+
+        match users.last() {
+            Some((id, _)) if users.len() == BATCH_SIZE as usize => after = *id,
+            _ => return Ok(migrated),
+        }
+    }
+}
+
+//dijalankan sekali saat server start
+pub async fn run_once() {
+    let Ok(cfg) = load_config() else {
+        return;
+    };
+    let result = match (db::get_pool().await, blob_store(&cfg.blob_store)) {
+        (Ok(pool), Ok(store)) => migrate_legacy_avatars(&pool, &store, &cfg.avatar.sizes).await,
+        (Err(e), _) | (_, Err(e)) => Err(e),
+    };
+    match result {
+        Ok(0) => (),
+        Ok(count) => println!("LEGACY AVATAR: avatar {} user dipindahkan", count),
+        Err(e) => eprintln!("LEGACY AVATAR ERROR: {:?}", e),
+    }
+}
@@ -0,0 +1,13 @@
+pub mod purge_users;
+pub mod blob_gc;
+pub mod outbox_dispatcher;
+pub mod legacy_avatars;
+pub mod key_reload;
+
+//job latar belakang yang berjalan bersama server
+pub fn spawn_jobs() {
+    tokio::spawn(purge_users::run_schedule());
+    tokio::spawn(outbox_dispatcher::run_dispatcher());
+    tokio::spawn(legacy_avatars::run_once());
+    tokio::spawn(key_reload::run_on_sighup());
+}
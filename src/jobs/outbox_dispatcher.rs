@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+use sqlx::{MySql, Pool, QueryBuilder};
+
+use crate::{
+    configs::db,
+    errors::app_error::AppError,
+    models::{config_model::OutboxConfig, outbox_model::{EventEnvelope, OutboxEvent}},
+    utils::{outbox::{EventSink, event_sinks, retry_delay}, utils::load_config},
+};
+
+//hanya event tertua yang belum selesai per user yang diklaim agar urutan event satu user terjaga
+const CLAIM_QUERY: &str = "SELECT * FROM outbox_events e \
+    WHERE e.published_at IS NULL AND e.failed_at IS NULL AND e.next_attempt_at <= NOW(3) \
+    AND NOT EXISTS (SELECT 1 FROM outbox_events p WHERE p.user_id = e.user_id AND p.id < e.id AND p.published_at IS NULL AND p.failed_at IS NULL) \
+    ORDER BY e.id LIMIT ? FOR UPDATE SKIP LOCKED";
+
+const CLEANUP_EVERY: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Default, PartialEq)]
+pub struct DispatchReport {
+    pub published: usize,
+    pub retried: usize,
+    pub failed: usize,
+}
+
+impl DispatchReport {
+    pub fn claimed(&self) -> usize {
+        self.published + self.retried + self.failed
+    }
+}
+
+//event yang diklaim disewa selama lease_secs, dispatcher lain melewatinya sampai sewa habis
+async fn claim(pool: &Pool<MySql>, cfg: &OutboxConfig) -> Result<Vec<OutboxEvent>, AppError> {
+    let mut tx = pool.begin().await?;
+    let mut events: Vec<OutboxEvent> = sqlx::query_as(CLAIM_QUERY)
+        .bind(cfg.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+    if events.is_empty() {
+        return Ok(events);
+    }
+
+    let mut builder = QueryBuilder::<MySql>::new("UPDATE outbox_events SET attempts = attempts + 1, next_attempt_at = NOW(3) + INTERVAL ");
+    builder.push_bind(cfg.lease_secs).push(" SECOND WHERE id IN (");
+    let mut ids = builder.separated(", ");
+    for event in &events {
+        ids.push_bind(event.id);
+    }
+    builder.push(")");
+    builder.build().execute(&mut *tx).await?;
+    tx.commit().await?;
+
+    for event in &mut events {
+        event.attempts += 1;
+    }
+    Ok(events)
+}
+
+//event dikirim ke semua sink, jika satu sink gagal seluruh event dicoba lagi (sink lain bisa menerima duplikat)
+async fn deliver(pool: &Pool<MySql>, sinks: &[impl EventSink], cfg: &OutboxConfig, event: &OutboxEvent, report: &mut DispatchReport) -> Result<(), AppError> {
+    let envelope = EventEnvelope::from(event);
+    let mut error = None;
+    for sink in sinks {
+        if let Err(e) = sink.publish(&envelope).await {
+            error = Some(format!("{}: {}", sink.name(), e));
+            break;
+        }
+    }
+
+    match error {
+        None => {
+            sqlx::query("UPDATE outbox_events SET published_at = NOW(3), last_error = NULL WHERE id = ?")
+                .bind(event.id)
+                .execute(pool)
+                .await?;
+            report.published += 1;
+        }
+        Some(error) if event.attempts >= cfg.max_attempts => {
+            eprintln!("OUTBOX: event {} gagal permanen setelah {} percobaan: {}", event.event_id, event.attempts, error);
+            sqlx::query("UPDATE outbox_events SET failed_at = NOW(3), last_error = ? WHERE id = ?")
+                .bind(error)
+                .bind(event.id)
+                .execute(pool)
+                .await?;
+            report.failed += 1;
+        }
+        Some(error) => {
+            sqlx::query("UPDATE outbox_events SET next_attempt_at = NOW(3) + INTERVAL ? SECOND, last_error = ? WHERE id = ?")
+                .bind(retry_delay(cfg, event.attempts))
+                .bind(error)
+                .bind(event.id)
+                .execute(pool)
+                .await?;
+            report.retried += 1;
+        }
+    }
+    Ok(())
+}
+
+pub async fn dispatch_once(pool: &Pool<MySql>, sinks: &[impl EventSink], cfg: &OutboxConfig) -> Result<DispatchReport, AppError> {
+    let mut report = DispatchReport::default();
+    for event in claim(pool, cfg).await? {
+        deliver(pool, sinks, cfg, &event, &mut report).await?;
+    }
+    Ok(report)
+}
+
+//event terkirim yang sudah lewat masa simpan dihapus bertahap
+pub async fn cleanup_published(pool: &Pool<MySql>, retention_hours: u32) -> Result<u64, AppError> {
+    let mut removed = 0;
+    loop {
+        let deleted = sqlx::query("DELETE FROM outbox_events WHERE published_at < NOW(3) - INTERVAL ? HOUR LIMIT 1000")
+            .bind(retention_hours)
+            .execute(pool)
+            .await?
+            .rows_affected();
+        removed += deleted;
+        if deleted < 1000 {
+            return Ok(removed);
+        }
+    }
+}
+
+pub async fn run_dispatcher() {
+    let Ok(cfg) = load_config().map(|c| c.outbox) else {
+        return;
+    };
+    if cfg.sinks.is_empty() {
+        return;
+    }
+    let sinks = match event_sinks(&cfg) {
+        Ok(sinks) => sinks,
+        Err(e) => {
+            eprintln!("OUTBOX ERROR: sink tidak valid: {:?}", e);
+            return;
+        }
+    };
+
+    let poll_interval = Duration::from_millis(cfg.poll_interval_ms.max(1));
+    //pool dibuat sekali, jika database belum siap dicoba lagi setiap poll_interval
+    let pool = loop {
+        match db::get_pool().await {
+            Ok(pool) => break pool,
+            Err(e) => eprintln!("OUTBOX ERROR: {:?}", e),
+        }
+        tokio::time::sleep(poll_interval).await;
+    };
+
+    let mut last_cleanup: Option<Instant> = None;
+    loop {
+        if cfg.retention_hours > 0 && last_cleanup.is_none_or(|t| t.elapsed() >= CLEANUP_EVERY) {
+            last_cleanup = Some(Instant::now());
+            if let Err(e) = cleanup_published(&pool, cfg.retention_hours).await {
+                eprintln!("OUTBOX ERROR: {:?}", e);
+            }
+        }
+        let result = dispatch_once(&pool, &sinks, &cfg).await;
+
+        //batch penuh berarti masih ada antrean, langsung lanjut tanpa menunggu
+        match result {
+            Ok(report) if report.claimed() >= cfg.batch_size as usize => continue,
+            Ok(_) => (),
+            Err(e) => eprintln!("OUTBOX ERROR: {:?}", e),
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
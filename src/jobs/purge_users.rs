@@ -0,0 +1,123 @@
+use std::time::Duration;
+use sqlx::{MySql, Pool};
+
+use crate::{configs::db, errors::app_error::AppError, jobs::blob_gc, utils::utils::load_config};
+
+//jumlah user yang dihapus per putaran agar transaksi tetap kecil
+const BATCH_SIZE: u32 = 500;
+
+//data milik user yang ikut dihapus permanen (tabel, kolom user)
+const USER_TABLES: &[(&str, &str)] = &[
+    ("sessions", "user_id"),
+    ("recovery_codes", "user_id"),
+    ("totp_challenges", "user_id"),
+    ("passkeys", "user_id"),
+    ("webauthn_challenges", "user_id"),
+    ("user_identities", "user_id"),
+    ("oauth_codes", "user_id"),
+    ("oauth_tokens", "user_id"),
+    ("user_blobs", "user_id"),
+    ("legacy_avatar_keys", "user_id"),
+];
+
+//false jika user sudah di-restore (atau dihapus ulang) sejak dipilih, tidak ada data yang disentuh
+pub(crate) async fn purge_user(pool: &Pool<MySql>, user_id: u64, purge_after_days: u32) -> Result<bool, AppError> {
+    let mut tx = pool.begin().await?;
+
+    //baris user dikunci dulu sehingga restore menunggu sampai purge selesai
+    let locked: Option<(u64,)> = sqlx::query_as("SELECT id FROM users WHERE id = ? AND deleted_at IS NOT NULL AND deleted_at < NOW() - INTERVAL ? DAY FOR UPDATE")
+        .bind(user_id)
+        .bind(purge_after_days)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if locked.is_none() {
+        return Ok(false);
+    }
+
+    //client OAuth milik user beserta token dan code-nya
+    sqlx::query("DELETE t FROM oauth_tokens t JOIN oauth_clients c ON c.id = t.client_id WHERE c.owner_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE o FROM oauth_codes o JOIN oauth_clients c ON c.id = o.client_id WHERE c.owner_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM oauth_clients WHERE owner_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for (table, column) in USER_TABLES {
+        sqlx::query(&format!("DELETE FROM {} WHERE {} = ?", table, column))
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    sqlx::query("DELETE FROM users WHERE id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+//hapus permanen user yang sudah melewati masa retensi, mengembalikan jumlah user yang dihapus
+pub async fn purge_deleted_users(pool: &Pool<MySql>, purge_after_days: u32) -> Result<u64, AppError> {
+    let mut purged = 0;
+    loop {
+        let ids: Vec<(u64,)> = sqlx::query_as("SELECT id FROM users WHERE deleted_at IS NOT NULL AND deleted_at < NOW() - INTERVAL ? DAY ORDER BY deleted_at LIMIT ?")
+            .bind(purge_after_days)
+            .bind(BATCH_SIZE)
+            .fetch_all(pool)
+            .await?;
+
+        for (id,) in &ids {
+            if purge_user(pool, *id, purge_after_days).await? {
+                purged += 1;
+            }
+        }
+        if ids.len() < BATCH_SIZE as usize {
+            return Ok(purged);
+        }
+    }
+}
+
+//blob milik user yang dihapus menjadi yatim dan ikut dibersihkan GC di akhir putaran
+async fn purge_once() -> Result<u64, AppError> {
+    let cfg = load_config()?.user_retention;
+    let pool = db::get_pool().await?;
+    let purged = purge_deleted_users(&pool, cfg.purge_after_days).await?;
+    match blob_gc::gc_once().await {
+        Ok(0) => (),
+        Ok(count) => println!("BLOB GC: {} blob dihapus", count),
+        Err(e) => eprintln!("BLOB GC ERROR: {:?}", e),
+    }
+    Ok(purged)
+}
+
+//interval dibaca saat start, masa retensi dibaca ulang setiap putaran
+pub async fn run_schedule() {
+    let interval = load_config().map(|c| c.user_retention.purge_interval).unwrap_or_default();
+    if interval == 0 {
+        return;
+    }
+
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+    loop {
+        ticker.tick().await;
+        match purge_once().await {
+            Ok(0) => (),
+            Ok(count) => println!("PURGE: {} user dihapus permanen", count),
+            Err(e) => eprintln!("PURGE ERROR: {:?}", e),
+        }
+    }
+}
+
+//dipakai perintah CLI purge-users
+pub async fn purge_now() -> Result<(), AppError> {
+    let count = purge_once().await?;
+    println!("{} user dihapus permanen", count);
+    Ok(())
+}
@@ -1,4 +1,6 @@
-use axum::{Router, serve};
+use std::net::SocketAddr;
+use axum::{ServiceExt, extract::Request, middleware::from_fn, serve};
+use tower::Layer;
 use tokio::net::TcpListener;
 
 mod routes;
@@ -9,13 +11,28 @@ mod utils;
 mod tests;
 mod middlewares;
 mod errors;
+mod commands;
+mod jobs;
+mod docs;
 
 #[tokio::main]
 async fn main() {
-    let app = Router::new()
-        .merge(routes::user_route());
+    configs::db::run_migrations().await.unwrap();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = args.first() {
+        if commands::run(command, &args[1..]).await.is_err() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    jobs::spawn_jobs();
+
+    //negosiasi versi lewat header Accept harus terjadi sebelum routing
+    let app = from_fn(middlewares::version_middleware::negotiate_version).layer(routes::api_route());
 
     let listener = TcpListener::bind("0.0.0.0:3000").await.unwrap();
     println!("server running in 0.0.0.0:3000");
-    let _=serve(listener, app).await.unwrap();
+    serve(listener, ServiceExt::<Request>::into_make_service_with_connect_info::<SocketAddr>(app)).await.unwrap();
 }
@@ -1,15 +1,30 @@
-use axum::{extract::Request, middleware::Next, response::Response};
+use axum::{extract::Request, middleware::Next, response::{IntoResponse, Response}};
 use axum_extra::extract::CookieJar;
+use http::header;
 
-use crate::{errors::app_error::AppError, utils::utils::{jwt_verify, load_config}};
+use crate::{configs::db, errors::app_error::AppError, utils::{oauth::verify_access_token, session::{remove_session_cookie, touch_session}, utils::{jwt_verify, load_config, load_jwt_keys}}};
 
+//X-API-KEY bersama, atau bearer token OAuth2 dengan scope api
 pub async fn api_key_middleware(req: Request, next: Next)->Result<Response, AppError>{
     let config = load_config()?;
     let valid_key = config.server.api_key;
     let header_key = req.headers().get("X-API-KEY").and_then(|v|v.to_str().ok());
-    if header_key != Some(&valid_key.as_str()){
-        return Err(AppError::Unauthorized);
+    if header_key == Some(valid_key.as_str()){
+        return Ok(next.run(req).await);
+    }
+
+    let bearer = req.headers().get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or(AppError::Unauthorized)?;
+    let pool = db::get_pool().await?;
+    let claims = verify_access_token(&pool, &*load_jwt_keys()?, bearer.trim()).await?;
+    if !claims.has_scope(&config.oauth.api_scope) {
+        return Err(AppError::Forbidden);
     }
+
+    let mut req = req;
+    req.extensions_mut().insert(claims);
     Ok(next.run(req).await)
 }
 
@@ -27,6 +42,12 @@ pub async fn check_login(req: Request, next: Next) -> Result<Response, AppError>
     // Verifikasi token
     let claims = jwt_verify(jwt).await?;
 
+    // Token ditolak jika session-nya sudah dicabut atau tidak ada
+    let pool = db::get_pool().await?;
+    if !touch_session(&pool, &claims).await? {
+        return Err(AppError::Unauthorized);
+    }
+
     // Bisa simpan claims di request extensions untuk handler
     let mut req = req;
     req.extensions_mut().insert(claims);
@@ -34,16 +55,27 @@ pub async fn check_login(req: Request, next: Next) -> Result<Response, AppError>
     Ok(next.run(req).await)
 }
 
+//hanya session yang masih aktif yang dianggap login, sama seperti check_login.
+//cookie jwt dari session yang sudah dicabut atau kedaluwarsa diperlakukan sebagai tamu dan dihapus
 pub async fn check_guest(req: Request, next: Next) -> Result<Response, AppError> {
     let cookies = CookieJar::from_headers(req.headers());
+    let Some(cookie) = cookies.get("jwt") else {
+        return Ok(next.run(req).await);
+    };
 
-    if let Some(cookie) = cookies.get("jwt") {
-        let jwt = cookie.value().trim();
-
-        if jwt_verify(jwt).await.is_ok() {
+    if let Ok(claims) = jwt_verify(cookie.value().trim()).await {
+        let pool = db::get_pool().await?;
+        if touch_session(&pool, &claims).await? {
             return Err(AppError::Forbidden);
         }
     }
 
-    Ok(next.run(req).await)
+    let res = next.run(req).await;
+    //login yang berhasil sudah memasang cookie jwt baru, cookie itu tidak boleh ikut dihapus
+    let sets_session = res.headers().get_all(header::SET_COOKIE).iter()
+        .any(|v| v.to_str().is_ok_and(|v| v.starts_with("jwt=")));
+    if sets_session {
+        return Ok(res);
+    }
+    Ok((remove_session_cookie(CookieJar::new()), res).into_response())
 }
\ No newline at end of file
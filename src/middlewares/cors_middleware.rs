@@ -0,0 +1,68 @@
+use std::time::Duration;
+use http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+
+use crate::models::config_model::CorsConfig;
+
+fn wildcard(values: &[String]) -> bool {
+    values.iter().any(|v| v.trim() == "*")
+}
+
+//origin "*" bersama credentials berarti situs mana pun bisa mengirim request dengan cookie user,
+//kombinasi ini ditolak saat config dimuat
+pub fn check_cors_config(cfg: &CorsConfig) -> Result<(), String> {
+    if cfg.allow_credentials && wildcard(&cfg.allowed_origins) {
+        return Err("cors.allowed_origins tidak boleh \"*\" jika cors.allow_credentials true".to_string());
+    }
+    Ok(())
+}
+
+//membuat cors layer dari konfigurasi, preflight OPTIONS langsung dijawab di sini
+//sehingga tidak sampai ke api_key_middleware maupun check_login
+pub fn cors_layer(cfg: &CorsConfig) -> CorsLayer {
+    //origin "*" tidak pernah memakai credentials walau config lolos tanpa check_cors_config
+    let any_origin = wildcard(&cfg.allowed_origins);
+    let allow_credentials = cfg.allow_credentials && !any_origin;
+
+    let origin = if any_origin {
+        AllowOrigin::any()
+    } else {
+        let origins: Vec<HeaderValue> = cfg.allowed_origins.iter()
+            .filter_map(|o| parse_or_warn(o, "origin", |v| HeaderValue::from_str(v).ok()))
+            .collect();
+        AllowOrigin::list(origins)
+    };
+
+    let methods = if wildcard(&cfg.allowed_methods) {
+        if allow_credentials { AllowMethods::mirror_request() } else { AllowMethods::any() }
+    } else {
+        let methods: Vec<Method> = cfg.allowed_methods.iter()
+            .filter_map(|m| parse_or_warn(m, "method", |v| Method::from_bytes(v.to_uppercase().as_bytes()).ok()))
+            .collect();
+        AllowMethods::list(methods)
+    };
+
+    let headers = if wildcard(&cfg.allowed_headers) {
+        if allow_credentials { AllowHeaders::mirror_request() } else { AllowHeaders::any() }
+    } else {
+        let headers: Vec<HeaderName> = cfg.allowed_headers.iter()
+            .filter_map(|h| parse_or_warn(h, "header", |v| HeaderName::from_bytes(v.as_bytes()).ok()))
+            .collect();
+        AllowHeaders::list(headers)
+    };
+
+    CorsLayer::new()
+        .allow_origin(origin)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(allow_credentials)
+        .max_age(Duration::from_secs(cfg.max_age))
+}
+
+fn parse_or_warn<T>(value: &str, kind: &str, parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+    let parsed = parse(value.trim());
+    if parsed.is_none() {
+        eprintln!("CORS CONFIG: {} tidak valid diabaikan: {:?}", kind, value);
+    }
+    parsed
+}
@@ -0,0 +1,47 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+use http::{HeaderValue, header};
+
+use crate::{errors::app_error::AppError, models::config_model::DeprecationConfig, utils::utils::load_config};
+
+//padanan route lama /user di /users, dipakai untuk header Link
+pub fn successor_path(path: &str, query: Option<&str>) -> String {
+    let rest = path.strip_prefix("/user").unwrap_or(path);
+    match rest {
+        "" | "/search" => "/users".to_string(),
+        //GET/DELETE /user/?id= menjadi /users/{id}
+        "/" => {
+            let id = query.unwrap_or_default()
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("id="))
+                .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()));
+            match id {
+                Some(id) => format!("/users/{}", id),
+                None => "/users".to_string(),
+            }
+        }
+        rest => format!("/users{}", rest),
+    }
+}
+
+//Deprecation (RFC 9745) berupa tanggal terstruktur @epoch, Sunset (RFC 8594) berupa HTTP-date
+pub fn deprecation_headers(cfg: &DeprecationConfig, successor: &str) -> [(&'static str, String); 3] {
+    [
+        ("deprecation", format!("@{}", cfg.deprecated_at.timestamp())),
+        ("sunset", cfg.sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string()),
+        (header::LINK.as_str(), format!("<{}>; rel=\"successor-version\"", successor)),
+    ]
+}
+
+//dipasang paling luar di route lama agar respons error (401, 404) juga membawa header
+pub async fn deprecated_route(req: Request, next: Next) -> Result<Response, AppError> {
+    let cfg = load_config()?.legacy_routes;
+    let successor = successor_path(req.uri().path(), req.uri().query());
+
+    let mut res = next.run(req).await;
+    for (name, value) in deprecation_headers(&cfg, &successor) {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            res.headers_mut().insert(name, value);
+        }
+    }
+    Ok(res)
+}
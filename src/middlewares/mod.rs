@@ -1 +1,3 @@
-pub mod api_middleware;
\ No newline at end of file
+pub mod api_middleware;
+pub mod cors_middleware;pub mod deprecation_middleware;
+pub mod version_middleware;
@@ -0,0 +1,50 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+use http::{HeaderMap, HeaderValue, Uri, header};
+
+use crate::errors::app_error::AppError;
+
+pub const SUPPORTED_VERSIONS: [u32; 2] = [1, 2];
+
+//versi diminta lewat parameter media type, contoh: Accept: application/json; version=2
+pub fn accept_version(headers: &HeaderMap) -> Result<Option<u32>, AppError> {
+    let requested = headers.get_all(header::ACCEPT).iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .flat_map(|range| range.split(';').skip(1))
+        .find_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            key.trim().eq_ignore_ascii_case("version").then(|| value.trim().trim_matches('"').to_string())
+        });
+
+    match requested {
+        None => Ok(None),
+        Some(version) => match version.strip_prefix('v').unwrap_or(&version).parse() {
+            Ok(version) if SUPPORTED_VERSIONS.contains(&version) => Ok(Some(version)),
+            _ => Err(AppError::NotAcceptable),
+        },
+    }
+}
+
+//path tanpa prefix versi diarahkan ke /api/v{n}, path dengan prefix tidak diubah
+pub fn versioned_uri(uri: &Uri, version: u32) -> Result<Uri, AppError> {
+    let path_and_query = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+    format!("/api/v{}{}", version, path_and_query)
+        .parse()
+        .map_err(|_| AppError::BadRequest)
+}
+
+//harus membungkus Router dari luar karena URI diubah sebelum routing
+pub async fn negotiate_version(mut req: Request, next: Next) -> Result<Response, AppError> {
+    if req.uri().path().starts_with("/api/") {
+        return Ok(next.run(req).await);
+    }
+
+    //tanpa parameter version, path tanpa prefix tetap v1
+    if let Some(version) = accept_version(req.headers())? && version != 1 {
+        *req.uri_mut() = versioned_uri(req.uri(), version)?;
+    }
+
+    let mut res = next.run(req).await;
+    res.headers_mut().append(header::VARY, HeaderValue::from_static("accept"));
+    Ok(res)
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use validator::Validate;
+
+//nilai atribut custom per user, key = nama atribut
+pub type AttributeValues = BTreeMap<String, serde_json::Value>;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AttributeKind {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    //YYYY-MM-DD
+    Date,
+}
+
+impl AttributeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttributeKind::String => "string",
+            AttributeKind::Integer => "integer",
+            AttributeKind::Number => "number",
+            AttributeKind::Boolean => "boolean",
+            AttributeKind::Date => "date",
+        }
+    }
+}
+
+impl TryFrom<String> for AttributeKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "string" => Ok(AttributeKind::String),
+            "integer" => Ok(AttributeKind::Integer),
+            "number" => Ok(AttributeKind::Number),
+            "boolean" => Ok(AttributeKind::Boolean),
+            "date" => Ok(AttributeKind::Date),
+            _ => Err(format!("tipe atribut '{}' tidak dikenal", value)),
+        }
+    }
+}
+
+//baris attribute_definitions, aturan yang tidak berlaku untuk tipe-nya selalu None
+#[derive(FromRow, Serialize, Debug, Clone, ToSchema)]
+pub struct AttributeDefinition {
+    pub name: String,
+    pub label: String,
+    #[sqlx(try_from = "String")]
+    pub kind: AttributeKind,
+    pub required: bool,
+    //string: panjang dalam karakter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u32>,
+    //string: regex yang harus cocok dengan seluruh nilai
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    //integer/number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    //string: daftar nilai yang diizinkan
+    #[sqlx(json(nullable))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<String>>,
+}
+
+//body PUT /users/attributes/{name}, membuat atau mengganti definisi
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AttributeDefinitionInput {
+    #[validate(length(min = 1, max = 255, message = "Label wajib diisi, maksimal 255 karakter"))]
+    #[schema(min_length = 1, max_length = 255)]
+    pub label: String,
+    pub kind: AttributeKind,
+    #[serde(default)]
+    pub required: bool,
+    pub min_length: Option<u32>,
+    pub max_length: Option<u32>,
+    #[validate(length(min = 1, max = 255, message = "Pattern maksimal 255 karakter"))]
+    #[schema(max_length = 255)]
+    pub pattern: Option<String>,
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>,
+    #[validate(length(min = 1, max = 100, message = "Choices berisi 1 sampai 100 nilai"))]
+    #[schema(min_items = 1, max_items = 100)]
+    pub choices: Option<Vec<String>>,
+}
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::prelude::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+pub enum AuditAction {
+    #[serde(rename = "user.create")]
+    UserCreate,
+    #[serde(rename = "user.update")]
+    UserUpdate,
+    #[serde(rename = "user.delete")]
+    UserDelete,
+    #[serde(rename = "user.restore")]
+    UserRestore,
+    #[serde(rename = "auth.login")]
+    Login,
+    #[serde(rename = "auth.login_failed")]
+    LoginFailed,
+    //isi password tidak pernah dicatat, hanya kejadiannya
+    #[serde(rename = "auth.password_change")]
+    PasswordChange,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::UserCreate => "user.create",
+            AuditAction::UserUpdate => "user.update",
+            AuditAction::UserDelete => "user.delete",
+            AuditAction::UserRestore => "user.restore",
+            AuditAction::Login => "auth.login",
+            AuditAction::LoginFailed => "auth.login_failed",
+            AuditAction::PasswordChange => "auth.password_change",
+        }
+    }
+}
+
+impl TryFrom<String> for AuditAction {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "user.create" => Ok(AuditAction::UserCreate),
+            "user.update" => Ok(AuditAction::UserUpdate),
+            "user.delete" => Ok(AuditAction::UserDelete),
+            "user.restore" => Ok(AuditAction::UserRestore),
+            "auth.login" => Ok(AuditAction::Login),
+            "auth.login_failed" => Ok(AuditAction::LoginFailed),
+            "auth.password_change" => Ok(AuditAction::PasswordChange),
+            _ => Err(format!("aksi audit '{}' tidak dikenal", value)),
+        }
+    }
+}
+
+//siapa yang melakukan aksi: user dari session, client OAuth2 dari bearer token, pemegang X-API-KEY,
+//atau perintah CLI di server (actor_id berisi nama perintah)
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ActorKind {
+    User,
+    Client,
+    ApiKey,
+    System,
+}
+
+impl ActorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActorKind::User => "user",
+            ActorKind::Client => "client",
+            ActorKind::ApiKey => "api_key",
+            ActorKind::System => "system",
+        }
+    }
+}
+
+impl TryFrom<String> for ActorKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "user" => Ok(ActorKind::User),
+            "client" => Ok(ActorKind::Client),
+            "api_key" => Ok(ActorKind::ApiKey),
+            "system" => Ok(ActorKind::System),
+            _ => Err(format!("tipe aktor '{}' tidak dikenal", value)),
+        }
+    }
+}
+
+#[derive(FromRow, Serialize, Debug, Clone, ToSchema)]
+pub struct AuditEntry {
+    pub id: u64,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    #[schema(value_type = i64)]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(try_from = "String")]
+    pub action: AuditAction,
+    #[sqlx(try_from = "String")]
+    pub actor_type: ActorKind,
+    pub actor_id: Option<String>,
+    pub target_id: Option<u64>,
+    //field yang berubah: {"name": {"before": "..", "after": ".."}}, password tidak pernah dicatat
+    #[sqlx(json(nullable))]
+    #[schema(value_type = Option<Object>)]
+    pub changes: Option<Map<String, Value>>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    #[param(value_type = Option<String>, example = "user.update")]
+    pub action: Option<AuditAction>,
+    #[param(value_type = Option<String>, example = "user")]
+    pub actor_type: Option<ActorKind>,
+    pub actor_id: Option<String>,
+    pub target_id: Option<u64>,
+    //RFC 3339, batas bawah inklusif dan batas atas eksklusif
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[param(minimum = 1)]
+    pub page: Option<u64>,
+    #[param(minimum = 1, maximum = 100)]
+    pub per_page: Option<u64>,
+}
+
+//entri terbaru lebih dulu
+#[derive(Serialize, Debug, ToSchema)]
+pub struct AuditPage {
+    pub items: Vec<AuditEntry>,
+    pub total: u64,
+    pub page: u64,
+    pub per_page: u64,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct AuditVerification {
+    pub valid: bool,
+    pub checked: u64,
+    //id entri pertama yang hash-nya tidak cocok atau prev_hash-nya tidak menyambung
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub broken_at: Option<u64>,
+}
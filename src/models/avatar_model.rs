@@ -0,0 +1,20 @@
+use std::collections::BTreeMap;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+//bentuk body multipart untuk dokumentasi OpenAPI
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct AvatarUpload {
+    //png, jpeg, webp atau gif, tipe dicek dari isi file bukan dari Content-Type
+    #[schema(value_type = String, format = Binary)]
+    pub avatar: Vec<u8>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct AvatarResponse {
+    //sama dengan avatar_url di User, thumbnail terbesar
+    pub avatar_url: String,
+    //URL per ukuran thumbnail (sisi dalam pixel)
+    pub thumbnails: BTreeMap<u32, String>,
+}
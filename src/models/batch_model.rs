@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::user_model::{User, UserInsert, UserUpdate};
+
+#[derive(Deserialize, Debug, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    //sama dengan POST /users
+    Create { data: UserInsert },
+    //sama dengan PUT /users/{id}, if_match berisi ETag dari GET
+    Update {
+        id: u64,
+        data: UserUpdate,
+        #[serde(default)]
+        if_match: Option<String>,
+    },
+    //sama dengan DELETE /users/{id}
+    Delete { id: u64 },
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct BatchRequest {
+    pub operations: Vec<BatchOperation>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct BatchResult {
+    //posisi operasi di request, dimulai dari 0
+    pub index: usize,
+    //status HTTP yang akan didapat jika operasi dikirim sendiri
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<User>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct BatchResponse {
+    pub transactional: bool,
+    //false jika transaksi dibatalkan, tidak ada perubahan yang tersimpan
+    pub committed: bool,
+    pub results: Vec<BatchResult>,
+}
@@ -1,5 +1,8 @@
+use chrono::{DateTime, TimeZone, Utc};
 use serde::Deserialize;
 
+use crate::models::user_model::TokenType;
+
 #[derive(Debug, Deserialize)]
 pub struct DatabaseConfig{
     pub host: String,
@@ -12,14 +15,537 @@ pub struct DatabaseConfig{
 #[derive(Debug, Deserialize)]
 pub struct ServerConfig {
     pub api_key: String,
+    //reverse proxy (IP atau CIDR) yang header X-Forwarded-For/X-Real-IP-nya dipercaya
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+    //atribut Secure pada cookie, false hanya untuk development lewat http
+    #[serde(default = "default_secure_cookies")]
+    pub secure_cookies: bool,
+}
+
+fn default_secure_cookies() -> bool {
+    true
+}
+
+//konfigurasi cors, default hanya mengizinkan same-origin
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub allow_credentials: bool,
+    pub max_age: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE"]
+                .iter().map(|m| m.to_string()).collect(),
+            allowed_headers: ["content-type", "x-api-key"]
+                .iter().map(|h| h.to_string()).collect(),
+            allow_credentials: true,
+            max_age: 600,
+        }
+    }
+}
+
+//satu kunci jwt, private_key_path hanya diperlukan untuk kunci yang dipakai signing
+#[derive(Debug, Deserialize, Clone)]
+pub struct JwtKeyConfig {
+    pub kid: String,
+    pub algorithm: String,
+    pub private_key_path: Option<String>,
+    pub public_key_path: Option<String>,
+    pub secret: Option<String>,
+}
+
+//masa berlaku token dalam detik, per jenis token
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct TokenLifetimes {
+    pub access: i64,
+    pub challenge: i64,
+    pub oauth: i64,
+}
+
+impl Default for TokenLifetimes {
+    fn default() -> Self {
+        Self {
+            access: 24 * 60 * 60,
+            challenge: 5 * 60,
+            oauth: 60 * 60,
+        }
+    }
+}
+
+impl TokenLifetimes {
+    pub fn for_type(&self, typ: TokenType) -> i64 {
+        match typ {
+            TokenType::Access => self.access,
+            TokenType::Challenge => self.challenge,
+            TokenType::OAuth => self.oauth,
+        }
+    }
+}
+
+//konfigurasi jwt, jika keys kosong maka memakai jwt_secret (HS256) seperti sebelumnya
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct JwtConfig {
+    pub active_kid: Option<String>,
+    pub keys: Vec<JwtKeyConfig>,
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+    pub leeway: u64,
+    pub lifetimes: TokenLifetimes,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            active_kid: None,
+            keys: Vec::new(),
+            issuer: None,
+            audience: None,
+            leeway: 60,
+            lifetimes: TokenLifetimes::default(),
+        }
+    }
+}
+
+//konfigurasi TOTP (RFC 6238), skew = jumlah time step toleransi sebelum/sesudah
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct TotpConfig {
+    pub issuer: String,
+    //6 sampai 8, dicek saat config dimuat
+    pub digits: u32,
+    pub period: u64,
+    pub skew: u64,
+    pub recovery_codes: usize,
+    //batas tebakan kode per challenge token, challenge juga tidak bisa dipakai lagi setelah login berhasil
+    pub max_attempts: u32,
+}
+
+impl Default for TotpConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "Backend".to_string(),
+            digits: 6,
+            period: 30,
+            skew: 1,
+            recovery_codes: 10,
+            max_attempts: 5,
+        }
+    }
+}
+
+//konfigurasi passkey (WebAuthn), rp_id harus domain yang sama/induk dari origins
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct WebauthnConfig {
+    pub rp_id: String,
+    pub rp_name: String,
+    pub origins: Vec<String>,
+    pub require_user_verification: bool,
+    pub timeout_ms: u64,
+}
+
+impl Default for WebauthnConfig {
+    fn default() -> Self {
+        Self {
+            rp_id: "localhost".to_string(),
+            rp_name: "Backend".to_string(),
+            origins: vec!["http://localhost:3000".to_string()],
+            require_user_verification: false,
+            timeout_ms: 60_000,
+        }
+    }
+}
+
+fn default_oidc_scopes() -> Vec<String> {
+    vec!["openid".to_string(), "email".to_string(), "profile".to_string()]
+}
+
+//provider OpenID Connect eksternal, endpoint diambil dari discovery document issuer
+#[derive(Debug, Deserialize, Clone)]
+pub struct OidcProviderConfig {
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_uri: String,
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct OidcConfig {
+    pub providers: Vec<OidcProviderConfig>,
+    //buat user baru jika email terverifikasi belum terdaftar
+    pub allow_signup: bool,
+    pub post_login_redirect: String,
+    pub state_ttl: i64,
+}
+
+impl Default for OidcConfig {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            allow_signup: false,
+            post_login_redirect: "/".to_string(),
+            state_ttl: 10 * 60,
+        }
+    }
+}
+
+//konfigurasi authorization server OAuth2 untuk aplikasi internal
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct OAuthConfig {
+    //scope yang boleh didaftarkan oleh client
+    pub scopes: Vec<String>,
+    //scope yang dibutuhkan bearer token untuk menggantikan X-API-KEY
+    pub api_scope: String,
+    pub code_ttl: i64,
+    //client_id aplikasi first-party yang boleh memakai authorization_code tanpa layar consent,
+    //client lain yang terdaftar lewat API ditolak di /oauth/authorize
+    pub first_party_clients: Vec<String>,
+}
+
+impl Default for OAuthConfig {
+    fn default() -> Self {
+        Self {
+            scopes: vec!["api".to_string()],
+            api_scope: "api".to_string(),
+            code_ttl: 60,
+            first_party_clients: Vec::new(),
+        }
+    }
+}
+
+//kebijakan password untuk registrasi dan ganti password,
+//default sengaja longgar agar sama dengan perilaku lama (minimal 5 karakter)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct PasswordPolicyConfig {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    //tolak password yang memuat nama atau bagian lokal email
+    pub disallow_personal_info: bool,
+    //skor kekuatan zxcvbn 0-4
+    pub min_score: u8,
+    //folder daftar hash SHA-1 password bocor per prefix 5 karakter ({PREFIX}.txt berisi SUFFIX:COUNT)
+    pub breached_hashes_dir: Option<String>,
+}
+
+impl Default for PasswordPolicyConfig {
+    fn default() -> Self {
+        Self {
+            min_length: 5,
+            max_length: 128,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            disallow_personal_info: false,
+            min_score: 0,
+            breached_hashes_dir: None,
+        }
+    }
+}
+
+//parameter argon2id, hash lama dengan parameter berbeda di-rehash otomatis saat login
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct PasswordHashConfig {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    //secret tambahan di luar database, jangan diganti setelah dipakai karena hash lama tidak bisa diverifikasi
+    pub pepper: Option<String>,
+    //jumlah hashing yang boleh berjalan bersamaan, dibaca sekali saat hashing pertama
+    pub max_concurrent: usize,
+}
+
+impl Default for PasswordHashConfig {
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+            pepper: None,
+            max_concurrent: 4,
+        }
+    }
+}
+
+//user yang di-soft delete dihapus permanen setelah purge_after_days hari
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct UserRetentionConfig {
+    pub purge_after_days: u32,
+    //jeda antar purge dalam detik, 0 mematikan purge terjadwal
+    pub purge_interval: u64,
+}
+
+impl Default for UserRetentionConfig {
+    fn default() -> Self {
+        Self {
+            purge_after_days: 30,
+            purge_interval: 60 * 60,
+        }
+    }
+}
+
+//route lama /user masih dilayani sampai sunset, responsnya diberi header Deprecation dan Sunset
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct DeprecationConfig {
+    pub deprecated_at: DateTime<Utc>,
+    pub sunset: DateTime<Utc>,
+}
+
+impl Default for DeprecationConfig {
+    fn default() -> Self {
+        Self {
+            deprecated_at: Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap(),
+            sunset: Utc.with_ymd_and_hms(2027, 4, 19, 0, 0, 0).unwrap(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchBackendKind {
+    //FULLTEXT index di tabel users
+    Mysql,
+    //index tantivy di disk, toleran typo
+    Embedded,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SearchConfig {
+    pub backend: SearchBackendKind,
+    pub default_per_page: u64,
+    pub max_per_page: u64,
+    //direktori index untuk backend embedded
+    pub index_dir: String,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            backend: SearchBackendKind::Mysql,
+            default_per_page: 20,
+            max_per_page: 100,
+            index_dir: "data/search-index".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BlobBackendKind {
+    //file di local_dir, dilayani lewat GET /avatars/...
+    Local,
+    //S3 atau layanan kompatibel (MinIO, R2), GET /avatars/... redirect ke presigned URL
+    S3,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct S3Config {
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    //true: {endpoint}/{bucket}/{key}, false: {bucket}.{host}/{key}
+    pub path_style: bool,
+    //batas waktu koneksi dan seluruh request, endpoint yang hang tidak boleh menahan upload
+    pub connect_timeout_ms: u64,
+    pub timeout_ms: u64,
+}
+
+impl Default for S3Config {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://localhost:9000".to_string(),
+            region: "us-east-1".to_string(),
+            bucket: String::new(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            path_style: true,
+            connect_timeout_ms: 5_000,
+            timeout_ms: 30_000,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct BlobStoreConfig {
+    pub backend: BlobBackendKind,
+    pub local_dir: String,
+    //prefix URL publik blob, kosong berarti dilayani server ini sendiri
+    pub public_url: String,
+    pub s3: S3Config,
+    //masa berlaku presigned URL dalam detik
+    pub presign_ttl: u64,
+    //blob tanpa referensi baru dihapus setelah sekian jam, memberi waktu upload yang belum selesai
+    pub gc_grace_hours: u32,
+}
+
+impl Default for BlobStoreConfig {
+    fn default() -> Self {
+        Self {
+            backend: BlobBackendKind::Local,
+            local_dir: "data/blobs".to_string(),
+            public_url: String::new(),
+            s3: S3Config::default(),
+            presign_ttl: 300,
+            gc_grace_hours: 24,
+        }
+    }
+}
+
+//PUT /me/avatar
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct AvatarConfig {
+    pub max_bytes: usize,
+    //batas lebar/tinggi gambar asli, mencegah decompression bomb
+    pub max_dimension: u32,
+    //sisi thumbnail persegi dalam pixel
+    pub sizes: Vec<u32>,
+}
+
+impl Default for AvatarConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: 5 * 1024 * 1024,
+            max_dimension: 4096,
+            sizes: vec![64, 128, 256],
+        }
+    }
+}
+
+//POST /users/batch
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct BatchConfig {
+    //true: semua operasi dalam satu transaksi, false: setiap operasi berdiri sendiri
+    pub transactional: bool,
+    pub max_operations: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            transactional: true,
+            max_operations: 100,
+        }
+    }
+}
+
+fn default_webhook_timeout() -> u64 {
+    10_000
+}
+
+//tujuan event domain, setiap event dikirim ke semua sink
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventSinkConfig {
+    //event dicetak ke stdout, untuk development
+    Log,
+    //POST JSON ke url, ditandatangani HMAC-SHA256 di header X-Signature jika secret diisi
+    Webhook {
+        url: String,
+        #[serde(default)]
+        secret: String,
+        #[serde(default = "default_webhook_timeout")]
+        timeout_ms: u64,
+    },
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct OutboxConfig {
+    //tanpa sink dispatcher tidak berjalan dan event tetap menunggu di outbox
+    pub sinks: Vec<EventSinkConfig>,
+    //jeda polling dalam milidetik
+    pub poll_interval_ms: u64,
+    pub batch_size: u32,
+    //event yang gagal sebanyak ini ditandai failed dan tidak dicoba lagi
+    pub max_attempts: u32,
+    //jeda percobaan ulang dalam detik, berlipat dua setiap kegagalan sampai retry_max_secs
+    pub retry_base_secs: u64,
+    pub retry_max_secs: u64,
+    //event yang diklaim tapi tidak selesai dalam waktu ini (dispatcher mati) dicoba lagi
+    pub lease_secs: u64,
+    //event terkirim dihapus setelah sekian jam, 0 menyimpannya selamanya
+    pub retention_hours: u32,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            sinks: Vec::new(),
+            poll_interval_ms: 1000,
+            batch_size: 100,
+            max_attempts: 10,
+            retry_base_secs: 5,
+            retry_max_secs: 60 * 60,
+            lease_secs: 60,
+            retention_hours: 7 * 24,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
     pub database: DatabaseConfig,
     pub server: ServerConfig,
+    #[serde(default)]
     pub jwt_secret: String,
+    #[serde(default)]
+    pub jwt: JwtConfig,
+    #[serde(default)]
+    pub cors: CorsConfig,
+    #[serde(default)]
+    pub totp: TotpConfig,
+    #[serde(default)]
+    pub webauthn: WebauthnConfig,
+    #[serde(default)]
+    pub oidc: OidcConfig,
+    #[serde(default)]
+    pub oauth: OAuthConfig,
+    #[serde(default)]
+    pub password_policy: PasswordPolicyConfig,
+    #[serde(default)]
+    pub password_hash: PasswordHashConfig,
+    #[serde(default)]
+    pub user_retention: UserRetentionConfig,
+    #[serde(default)]
+    pub legacy_routes: DeprecationConfig,
+    #[serde(default)]
+    pub search: SearchConfig,
+    #[serde(default)]
+    pub batch: BatchConfig,
+    #[serde(default)]
+    pub blob_store: BlobStoreConfig,
+    #[serde(default)]
+    pub avatar: AvatarConfig,
+    #[serde(default)]
+    pub outbox: OutboxConfig,
 }
-
-
-    
\ No newline at end of file
@@ -1,2 +1,14 @@
 pub mod user_model;
-pub mod config_model;
\ No newline at end of file
+pub mod config_model;
+pub mod session_model;
+pub mod totp_model;
+pub mod webauthn_model;
+pub mod oauth_model;
+pub mod user_v2_model;
+pub mod search_model;
+pub mod transfer_model;
+pub mod batch_model;
+pub mod avatar_model;
+pub mod attribute_model;
+pub mod audit_model;
+pub mod outbox_model;
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::models::user_model::TokenType;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GrantType {
+    AuthorizationCode,
+    ClientCredentials,
+}
+
+impl GrantType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GrantType::AuthorizationCode => "authorization_code",
+            GrantType::ClientCredentials => "client_credentials",
+        }
+    }
+}
+
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+pub struct OAuthClientInsert {
+    #[validate(length(min = 3, max = 100, message = "Nama minimal 3 karakter"))]
+    #[schema(min_length = 3, max_length = 100)]
+    pub name: String,
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+    #[validate(length(min = 1, message = "Minimal satu grant type"))]
+    #[schema(min_items = 1)]
+    pub grant_types: Vec<GrantType>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    //client publik (SPA/CLI) tidak punya secret dan wajib memakai PKCE
+    #[serde(default = "default_confidential")]
+    pub confidential: bool,
+}
+
+fn default_confidential() -> bool {
+    true
+}
+
+//baris oauth_clients, daftar dipisah spasi seperti parameter scope OAuth2
+#[derive(FromRow, Debug)]
+pub struct StoredClient {
+    pub id: String,
+    pub name: String,
+    pub secret_hash: Option<String>,
+    pub redirect_uris: String,
+    pub grant_types: String,
+    pub scopes: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl StoredClient {
+    pub fn redirect_uris(&self) -> Vec<&str> {
+        self.redirect_uris.split_whitespace().collect()
+    }
+
+    pub fn scopes(&self) -> Vec<String> {
+        self.scopes.split_whitespace().map(str::to_string).collect()
+    }
+
+    pub fn allows(&self, grant: GrantType) -> bool {
+        self.grant_types.split_whitespace().any(|g| g == grant.as_str())
+    }
+
+    pub fn is_confidential(&self) -> bool {
+        self.secret_hash.is_some()
+    }
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct OAuthClient {
+    pub client_id: String,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub scopes: Vec<String>,
+    pub confidential: bool,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    #[schema(value_type = i64)]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<StoredClient> for OAuthClient {
+    fn from(client: StoredClient) -> Self {
+        Self {
+            redirect_uris: client.redirect_uris().into_iter().map(str::to_string).collect(),
+            grant_types: client.grant_types.split_whitespace().map(str::to_string).collect(),
+            scopes: client.scopes(),
+            confidential: client.is_confidential(),
+            client_id: client.id,
+            name: client.name,
+            created_at: client.created_at,
+        }
+    }
+}
+
+//client_secret hanya ditampilkan sekali saat client dibuat
+#[derive(Serialize, Debug, ToSchema)]
+pub struct OAuthClientCreated {
+    #[serde(flatten)]
+    pub client: OAuthClient,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuthorizeQuery {
+    pub response_type: String,
+    pub client_id: String,
+    pub redirect_uri: Option<String>,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct TokenRequest {
+    pub grant_type: String,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub code_verifier: Option<String>,
+    pub scope: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub scope: String,
+}
+
+//body untuk introspection (RFC 7662) dan revocation (RFC 7009)
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct TokenForm {
+    pub token: String,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
+pub struct Introspection {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+}
+
+//claims access token OAuth2 (RFC 9068), sub = id user atau client_id untuk client credentials
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OAuthClaims {
+    pub sub: String,
+    pub client_id: String,
+    pub scope: String,
+    pub exp: i64,
+    pub iat: i64,
+    pub nbf: i64,
+    pub jti: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<u64>,
+    pub typ: TokenType,
+}
+
+impl OAuthClaims {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scope.split_whitespace().any(|s| s == scope)
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::prelude::FromRow;
+
+//nama event mengikuti kontrak dengan service lain
+#[allow(clippy::enum_variant_names)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum DomainEventKind {
+    UserCreated,
+    UserUpdated,
+    UserDeleted,
+    UserRestored,
+    UserLoggedIn,
+}
+
+impl DomainEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DomainEventKind::UserCreated => "UserCreated",
+            DomainEventKind::UserUpdated => "UserUpdated",
+            DomainEventKind::UserDeleted => "UserDeleted",
+            DomainEventKind::UserRestored => "UserRestored",
+            DomainEventKind::UserLoggedIn => "UserLoggedIn",
+        }
+    }
+}
+
+impl TryFrom<String> for DomainEventKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "UserCreated" => Ok(DomainEventKind::UserCreated),
+            "UserUpdated" => Ok(DomainEventKind::UserUpdated),
+            "UserDeleted" => Ok(DomainEventKind::UserDeleted),
+            "UserRestored" => Ok(DomainEventKind::UserRestored),
+            "UserLoggedIn" => Ok(DomainEventKind::UserLoggedIn),
+            _ => Err(format!("event '{}' tidak dikenal", value)),
+        }
+    }
+}
+
+//baris outbox_events yang dibutuhkan dispatcher, kolom status pengiriman hanya ditulis
+#[derive(FromRow, Debug, Clone)]
+pub struct OutboxEvent {
+    pub id: u64,
+    pub event_id: String,
+    #[sqlx(try_from = "String")]
+    pub event_type: DomainEventKind,
+    pub user_id: u64,
+    #[sqlx(json)]
+    pub payload: Value,
+    pub created_at: DateTime<Utc>,
+    pub attempts: u32,
+}
+
+//bentuk event yang diterima sink
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct EventEnvelope {
+    //sama untuk setiap pengiriman ulang, dipakai consumer untuk deduplikasi
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: DomainEventKind,
+    pub user_id: u64,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub occurred_at: DateTime<Utc>,
+    pub data: Value,
+}
+
+impl From<&OutboxEvent> for EventEnvelope {
+    fn from(event: &OutboxEvent) -> Self {
+        Self {
+            id: event.event_id.clone(),
+            event_type: event.event_type,
+            user_id: event.user_id,
+            occurred_at: event.created_at,
+            data: event.payload.clone(),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::models::user_model::User;
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UserSearchParams {
+    //contoh: name:budi email:*@corp.com created>2025-01-01 (admin OR ops) -email:*@test.com attr.department:eng*
+    pub q: Option<String>,
+    #[param(minimum = 1)]
+    pub page: Option<u64>,
+    #[param(minimum = 1)]
+    pub per_page: Option<u64>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct UserSearchHit {
+    #[serde(flatten)]
+    pub user: User,
+    pub score: f64,
+    //potongan field yang cocok, bagian yang cocok dibungkus <mark> dan sisanya sudah di-escape
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub highlights: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct UserSearchPage {
+    pub items: Vec<UserSearchHit>,
+    pub total: u64,
+    pub page: u64,
+    pub per_page: u64,
+}
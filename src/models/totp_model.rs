@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct TotpSetup {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+#[derive(Deserialize, Validate, Debug, Serialize, ToSchema)]
+pub struct TotpCode {
+    #[validate(length(min = 6, max = 8, message = "Kode minimal 6 digit"))]
+    #[schema(min_length = 6, max_length = 8)]
+    pub code: String,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+//respon login jika user memakai 2FA, token ini ditukar lewat POST /login/totp
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct TwoFactorChallenge {
+    pub challenge_token: String,
+    pub expires_in: i64,
+}
+
+#[derive(Deserialize, Validate, Debug, Serialize, ToSchema)]
+pub struct TotpLogin {
+    pub challenge_token: String,
+    #[validate(length(min = 6, max = 8, message = "Kode minimal 6 digit"))]
+    #[schema(min_length = 6, max_length = 8)]
+    pub code: Option<String>,
+    #[validate(length(min = 10, max = 11, message = "Kode pemulihan tidak valid"))]
+    #[schema(min_length = 10, max_length = 11)]
+    pub recovery_code: Option<String>,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct UserTotp {
+    pub email: String,
+    pub totp_secret: Option<String>,
+    pub totp_enabled: bool,
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferFormat {
+    //header wajib: name,email,password (urutan bebas)
+    Csv,
+    //satu objek UserInsert per baris
+    Ndjson,
+}
+
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(default)]
+pub struct ImportParams {
+    //hanya validasi, tidak ada user yang dibuat
+    pub dry_run: bool,
+    //semua baris dibuat dalam satu transaksi, satu baris gagal membatalkan semuanya
+    pub atomic: bool,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportParams {
+    //default ndjson
+    pub format: Option<TransferFormat>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportRowStatus {
+    Created,
+    //lolos validasi pada dry run
+    Valid,
+    Failed,
+    //valid tetapi dibatalkan karena baris lain gagal pada mode atomic
+    RolledBack,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ImportRowResult {
+    //nomor record data, dimulai dari 1 dan tidak menghitung header CSV
+    pub row: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    pub status: ImportRowStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub atomic: bool,
+    //false jika dry run atau transaksi atomic dibatalkan
+    pub committed: bool,
+    pub total: usize,
+    pub created: usize,
+    pub failed: usize,
+    pub rows: Vec<ImportRowResult>,
+}
@@ -2,69 +2,165 @@ use chrono::{DateTime, Utc};
 use validator::Validate;
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive( FromRow, Debug, Deserialize, Serialize, Validate)]
+use crate::models::attribute_model::AttributeValues;
+
+#[derive( FromRow, Debug, Clone, Deserialize, Serialize, Validate, ToSchema)]
 pub struct User{
     pub id: u64,
     pub name: String,
     #[validate(email(message = "Format email tidak valid"))]
     pub email : String,
-    #[allow(dead_code)]
+    //None untuk user yang hanya login lewat provider eksternal (OIDC)
     #[serde(skip_serializing)]
-    pub password: String,
+    pub password: Option<String>,
     #[serde(with = "chrono::serde::ts_milliseconds")]
+    #[schema(value_type = i64)]
     pub created_at: DateTime<Utc>,
     #[serde(with = "chrono::serde::ts_milliseconds")]
+    #[schema(value_type = i64)]
     pub updated_at: DateTime<Utc>,
+    //terisi jika user dihapus (soft delete), dihapus permanen oleh purge job setelah masa retensi
+    #[serde(with = "chrono::serde::ts_milliseconds_option", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<i64>)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    //atribut custom sesuai attribute_definitions
+    #[sqlx(json)]
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub attributes: AttributeValues,
 }
 
-#[derive(Deserialize, Validate, Debug,Serialize)]
+#[derive(Deserialize, Validate, Debug,Serialize, ToSchema)]
 pub struct UserInsert{
     #[validate(length(min = 3, message = "Nama minimal 3 karakter"))]
+    #[schema(min_length = 3)]
     pub name: String,
     #[validate(custom(function = "crate::utils::utils::validate_email_tld"))]
+    #[schema(format = Email)]
     pub email : String,
-    #[validate(length(min = 5, message = "Password minimal 5 karakter"))]
+    //aturan password dicek oleh password_policy sesuai config
     pub password: String,
+    //divalidasi terhadap attribute_definitions, bukan lewat derive
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub attributes: AttributeValues,
 }
 
-#[derive(Deserialize, Validate, Debug,Serialize)]
+#[derive(Deserialize, Validate, Debug,Serialize, ToSchema)]
 pub struct UserLogin{
     #[validate(custom(function = "crate::utils::utils::validate_email_tld"))]
+    #[schema(format = Email)]
     pub email : String,
     #[validate(length(min = 5, message = "Password minimal 5 karakter"))]
+    #[schema(min_length = 5)]
     pub password: String,
 }
 
-#[derive(Deserialize, Validate, Debug,Serialize)]
+#[derive(Deserialize, Validate, Debug,Serialize, ToSchema)]
 pub struct UserUpdate{
     #[validate(length(min = 3, message = "Nama minimal 3 karakter"))]
+    #[schema(min_length = 3)]
     pub name: String,
     #[validate(custom(function = "crate::utils::utils::validate_email_tld"))]
+    #[schema(format = Email)]
     pub email : String,
+    //jika dikirim menggantikan semua atribut, jika tidak dikirim atribut lama tetap
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub attributes: Option<AttributeValues>,
 }
 
 
-#[derive(Deserialize, Debug,Serialize)]
+//JSON Merge Patch (RFC 7396): field yang tidak dikirim tidak berubah,
+//null ditolak karena name dan email wajib ada
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UserPatch{
+    #[serde(default, deserialize_with = "crate::utils::utils::non_null")]
+    #[validate(length(min = 3, message = "Nama minimal 3 karakter"))]
+    #[schema(min_length = 3, nullable = false)]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "crate::utils::utils::non_null")]
+    #[validate(custom(function = "crate::utils::utils::validate_email_tld"))]
+    #[schema(format = Email, nullable = false)]
+    pub email: Option<String>,
+    //merge patch per atribut: null menghapus atribut
+    #[serde(default, deserialize_with = "crate::utils::utils::non_null")]
+    #[schema(value_type = Option<Object>, nullable = false)]
+    pub attributes: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+//perubahan atribut saat update user
+pub enum AttributeChange<'a> {
+    Replace(&'a AttributeValues),
+    Merge(&'a serde_json::Map<String, serde_json::Value>),
+}
+
+//current_password boleh kosong untuk user yang belum punya password (login lewat OIDC).
+//batas panjang mencegah input raksasa sebelum diverifikasi argon2, aturan lain dari password_policy
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+pub struct PasswordChange{
+    #[validate(length(max = 1024, message = "Password maksimal 1024 karakter"))]
+    #[schema(max_length = 1024)]
+    pub current_password: Option<String>,
+    #[validate(length(min = 1, max = 1024, message = "Password baru harus 1-1024 karakter"))]
+    #[schema(min_length = 1, max_length = 1024)]
+    pub new_password: String,
+}
+
+
+#[derive(Deserialize, Debug,Serialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum SeacrhBy {
     Name,
     Email,
 }
 
-#[derive(Deserialize, Validate, Debug,Serialize)]
+#[derive(Deserialize, Validate, Debug,Serialize, ToSchema)]
 pub struct SearchQuery {
     pub by: SeacrhBy,
     pub value: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct UserQuery {
     pub id: u64,
 }
 
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UserListQuery {
+    pub q: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenType {
+    Access,
+    //token sementara setelah password benar, ditukar dengan access token setelah kode 2FA valid
+    Challenge,
+    //access token OAuth2 yang diterbitkan untuk client internal (bukan cookie)
+    OAuth,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Claims{
     pub sub: u64,
     pub exp: i64,
+    pub iat: i64,
+    pub nbf: i64,
+    pub jti: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    //id session di server, token ikut tidak berlaku jika session dicabut
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    pub typ: TokenType,
 }
\ No newline at end of file
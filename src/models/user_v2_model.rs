@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::models::{attribute_model::AttributeValues, user_model::User};
+
+//representasi user di /api/v2: id berupa string dan timestamp RFC 3339
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserV2 {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    #[schema(value_type = Object)]
+    pub attributes: AttributeValues,
+}
+
+impl From<User> for UserV2 {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id.to_string(),
+            name: user.name,
+            email: user.email,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+            deleted_at: user.deleted_at,
+            avatar_url: user.avatar_url,
+            attributes: user.attributes,
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct RelyingParty {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyUser {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct CredentialParameter {
+    #[serde(rename = "type")]
+    pub typ: &'static str,
+    pub alg: i64,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct CredentialDescriptor {
+    #[serde(rename = "type")]
+    pub typ: &'static str,
+    pub id: String,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelection {
+    pub resident_key: &'static str,
+    pub user_verification: &'static str,
+}
+
+//PublicKeyCredentialCreationOptions dalam bentuk JSON (base64url) untuk navigator.credentials.create
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreationOptions {
+    pub challenge: String,
+    pub rp: RelyingParty,
+    pub user: PublicKeyUser,
+    pub pub_key_cred_params: Vec<CredentialParameter>,
+    pub timeout: u64,
+    pub attestation: &'static str,
+    pub exclude_credentials: Vec<CredentialDescriptor>,
+    pub authenticator_selection: AuthenticatorSelection,
+}
+
+//PublicKeyCredentialRequestOptions untuk navigator.credentials.get
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestOptions {
+    pub challenge: String,
+    pub rp_id: String,
+    pub timeout: u64,
+    pub allow_credentials: Vec<CredentialDescriptor>,
+    pub user_verification: &'static str,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CeremonyOptions<T> {
+    pub challenge_id: String,
+    pub public_key: T,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationResponse {
+    pub client_data_json: String,
+    pub attestation_object: String,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResponse {
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+    pub user_handle: Option<String>,
+}
+
+//hasil PublicKeyCredential.toJSON() dari browser
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct PublicKeyCredential<T> {
+    pub id: String,
+    pub response: T,
+}
+
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+pub struct PasskeyRegister {
+    pub challenge_id: String,
+    #[validate(length(min = 1, max = 100, message = "Nama passkey 1-100 karakter"))]
+    #[schema(min_length = 1, max_length = 100)]
+    pub name: String,
+    pub credential: PublicKeyCredential<AttestationResponse>,
+}
+
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+pub struct PasskeyLoginStart {
+    #[validate(custom(function = "crate::utils::utils::validate_email_tld"))]
+    #[schema(format = Email)]
+    pub email: Option<String>,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct PasskeyLogin {
+    pub challenge_id: String,
+    pub credential: PublicKeyCredential<AssertionResponse>,
+}
+
+#[derive(FromRow, Debug, Serialize, ToSchema)]
+pub struct Passkey {
+    pub id: u64,
+    pub name: String,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    #[schema(value_type = i64)]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_milliseconds_option")]
+    #[schema(value_type = Option<i64>)]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct StoredPasskey {
+    pub id: u64,
+    pub user_id: u64,
+    pub public_key: Vec<u8>,
+    pub sign_count: u32,
+}
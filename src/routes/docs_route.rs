@@ -0,0 +1,11 @@
+use axum::Router;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::docs::openapi::api_doc;
+
+
+//spesifikasi dan Swagger UI terbuka tanpa X-API-KEY
+pub fn routes_docs() -> Router{
+    Router::new()
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", api_doc()))
+}
@@ -1,11 +1,14 @@
 use axum::{Router, middleware::from_fn, routing::{ post}};
 
-use crate::{controllers::user_controller::login_user, middlewares::api_middleware::{api_key_middleware, check_guest}};
+use crate::{controllers::{passkey_controller::{passkey_login, passkey_login_options}, totp_controller::login_totp, user_controller::login_user}, middlewares::api_middleware::{api_key_middleware, check_guest}};
 
 
 pub fn routes_guest() -> Router{
     Router::new()
         .route("/login", post(login_user))
+        .route("/login/totp", post(login_totp))
+        .route("/login/passkey/options", post(passkey_login_options))
+        .route("/login/passkey", post(passkey_login))
         .layer(from_fn(api_key_middleware))
         .layer(from_fn(check_guest))
-}
\ No newline at end of file
+}
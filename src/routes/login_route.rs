@@ -1,16 +1,70 @@
-use axum::{Router, middleware::from_fn, routing::{ delete, get, post, put}};
+use axum::{Router, extract::DefaultBodyLimit, middleware::from_fn, routing::{ delete, get, patch, post, put}};
 
-use crate::{controllers::user_controller::{delete_user, edit_user, get_all_user, get_user, get_user_edit, insert_user}, middlewares::api_middleware::{api_key_middleware, check_login}};
+use crate::{controllers::{audit_controller::{list_audit, verify_audit}, attribute_controller::{delete_attribute, list_attributes, put_attribute}, avatar_controller::upload_avatar, passkey_controller::{delete_passkey, list_passkeys, passkey_register, passkey_register_options}, session_controller::{delete_session, list_sessions}, totp_controller::{confirm_totp, disable_totp, setup_totp}, user_controller::{delete_user, delete_user_by_id, edit_user, get_all_user, get_user, get_user_by_id, change_password, get_deleted_users, get_user_edit, insert_user, list_users, logout_user, patch_user, restore_user, search_users}, user_batch_controller::batch_users, user_transfer_controller::{export_users, import_users}}, middlewares::{api_middleware::{api_key_middleware, check_login}, deprecation_middleware::deprecated_route}};
 
 
-pub fn routes_login() -> Router{
+pub fn routes_users() -> Router{
+    Router::new()
+        .route("/users", get(list_users))
+        .route("/users", post(insert_user))
+        .route("/users/deleted", get(get_deleted_users))
+        .route("/users/search", get(search_users))
+        .route("/users/import", post(import_users))
+        .route("/users/export", get(export_users))
+        .route("/users/batch", post(batch_users))
+        .route("/users/attributes", get(list_attributes))
+        .route("/users/attributes/{name}", put(put_attribute))
+        .route("/users/attributes/{name}", delete(delete_attribute))
+        .route("/users/{id}", get(get_user_by_id))
+        .route("/users/{id}", put(edit_user))
+        .route("/users/{id}", patch(patch_user))
+        .route("/users/{id}", delete(delete_user_by_id))
+        .route("/users/{id}/restore", post(restore_user))
+        .layer(from_fn(check_login))
+        .layer(from_fn(api_key_middleware))
+}
+
+//log audit hanya bisa dibaca, tidak ada route untuk mengubah atau menghapus entri
+pub fn routes_audit() -> Router{
+    Router::new()
+        .route("/audit", get(list_audit))
+        .route("/audit/verify", get(verify_audit))
+        .layer(from_fn(check_login))
+        .layer(from_fn(api_key_middleware))
+}
+
+//route milik user yang sedang login, sama di semua versi
+pub fn routes_me() -> Router{
+    Router::new()
+        .route("/logout", post(logout_user))
+        .route("/me/password", put(change_password))
+        .route("/me/avatar", put(upload_avatar).layer(DefaultBodyLimit::disable()))
+        .route("/me/sessions", get(list_sessions))
+        .route("/me/sessions/{id}", delete(delete_session))
+        .route("/me/totp/setup", post(setup_totp))
+        .route("/me/totp/confirm", post(confirm_totp))
+        .route("/me/totp", delete(disable_totp))
+        .route("/me/passkeys", get(list_passkeys))
+        .route("/me/passkeys", post(passkey_register))
+        .route("/me/passkeys/options", post(passkey_register_options))
+        .route("/me/passkeys/{id}", delete(delete_passkey))
+        .layer(from_fn(check_login))
+        .layer(from_fn(api_key_middleware))
+}
+
+//alias lama untuk /users, dihapus setelah tanggal sunset
+pub fn routes_legacy_user() -> Router{
     Router::new()
         .route("/user", get(get_all_user))
         .route("/user", post(insert_user))
         .route("/user/search", post(get_user))
         .route("/user/", delete(delete_user))
         .route("/user/", get(get_user_edit))
+        .route("/user/deleted", get(get_deleted_users))
         .route("/user/{id}", put(edit_user))
+        .route("/user/{id}", patch(patch_user))
+        .route("/user/{id}/restore", post(restore_user))
         .layer(from_fn(check_login))
         .layer(from_fn(api_key_middleware))
-}
\ No newline at end of file
+        .layer(from_fn(deprecated_route))
+}
@@ -1,15 +1,50 @@
 use axum::Router;
 
-use crate::routes::{fallback::{fallback, not_allowed}, guest_route::routes_guest, login_route::routes_login};
+use crate::{middlewares::cors_middleware::cors_layer, routes::{docs_route::routes_docs, fallback::{fallback, not_allowed}, guest_route::routes_guest, login_route::{routes_audit, routes_legacy_user, routes_me, routes_users}, oauth_route::routes_oauth, oidc_route::routes_oidc, public_route::routes_public, v2_route::routes_users_v2}, utils::utils::load_config};
 
 pub mod fallback;
 pub mod login_route;
 pub mod guest_route;
+pub mod public_route;
+pub mod oidc_route;
+pub mod oauth_route;
+pub mod v2_route;
+pub mod docs_route;
 
-pub fn user_route() -> Router{
+//route yang sama di semua versi
+fn routes_common() -> Router{
     Router::new()
-        .merge(routes_login())
+        .merge(routes_me())
+        .merge(routes_audit())
         .merge(routes_guest())
+        .merge(routes_public())
+        .merge(routes_oidc())
+        .merge(routes_oauth())
+}
+
+fn with_defaults(router: Router) -> Router{
+    //cors dipasang paling luar agar preflight dijawab sebelum middleware auth
+    let cors = load_config().map(|c| c.cors).unwrap_or_default();
+
+    router
         .fallback(fallback)
         .method_not_allowed_fallback(not_allowed)
-}
\ No newline at end of file
+        .layer(cors_layer(&cors))
+}
+
+pub fn user_route() -> Router{
+    with_defaults(routes_users().merge(routes_common()))
+}
+
+pub fn user_route_v2() -> Router{
+    with_defaults(routes_users_v2().merge(routes_common()))
+}
+
+//versi baru cukup di-nest di sini, path tanpa prefix tetap dilayani v1 (plus alias lama /user)
+pub fn api_route() -> Router{
+    Router::new()
+        .nest("/api/v1", user_route())
+        .nest("/api/v2", user_route_v2())
+        .merge(routes_docs())
+        .fallback_service(with_defaults(routes_users().merge(routes_common()).merge(routes_legacy_user())))
+}
@@ -0,0 +1,30 @@
+use axum::{Router, middleware::from_fn, routing::{delete, get, post}};
+
+use crate::{controllers::oauth_controller::{authorize, create_client, delete_client, introspect, list_clients, revoke, token}, middlewares::api_middleware::{api_key_middleware, check_login}};
+
+
+pub fn routes_oauth() -> Router{
+    //pendaftaran client oleh user yang login
+    let clients = Router::new()
+        .route("/oauth/clients", get(list_clients))
+        .route("/oauth/clients", post(create_client))
+        .route("/oauth/clients/{id}", delete(delete_client))
+        .layer(from_fn(check_login))
+        .layer(from_fn(api_key_middleware));
+
+    //authorize dibuka oleh browser (redirect), cukup cookie login tanpa X-API-KEY
+    let authorize = Router::new()
+        .route("/oauth/authorize", get(authorize))
+        .layer(from_fn(check_login));
+
+    //endpoint token diautentikasi dengan kredensial client
+    let endpoints = Router::new()
+        .route("/oauth/token", post(token))
+        .route("/oauth/introspect", post(introspect))
+        .route("/oauth/revoke", post(revoke));
+
+    Router::new()
+        .merge(clients)
+        .merge(authorize)
+        .merge(endpoints)
+}
@@ -0,0 +1,13 @@
+use axum::{Router, middleware::from_fn, routing::get};
+
+use crate::{controllers::oidc_controller::{list_oidc_providers, oidc_callback, oidc_start}, middlewares::api_middleware::check_guest};
+
+
+//route ini dibuka langsung oleh browser (redirect), sehingga tidak bisa memakai X-API-KEY
+pub fn routes_oidc() -> Router{
+    Router::new()
+        .route("/login/oidc", get(list_oidc_providers))
+        .route("/login/oidc/{provider}", get(oidc_start))
+        .route("/login/oidc/{provider}/callback", get(oidc_callback))
+        .layer(from_fn(check_guest))
+}
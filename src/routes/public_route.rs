@@ -0,0 +1,10 @@
+use axum::{Router, routing::get};
+
+use crate::controllers::{avatar_controller::get_avatar, key_controller::jwks};
+
+
+pub fn routes_public() -> Router{
+    Router::new()
+        .route("/.well-known/jwks.json", get(jwks))
+        .route("/avatars/{file}", get(get_avatar))
+}
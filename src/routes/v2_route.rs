@@ -0,0 +1,18 @@
+use axum::{Router, middleware::from_fn, routing::{ delete, get, patch, post, put}};
+
+use crate::{controllers::{user_controller::insert_user, user_v2_controller::{delete_user, edit_user, get_deleted_users, get_user, list_users, patch_user, restore_user}}, middlewares::api_middleware::{api_key_middleware, check_login}};
+
+
+pub fn routes_users_v2() -> Router{
+    Router::new()
+        .route("/users", get(list_users))
+        .route("/users", post(insert_user))
+        .route("/users/deleted", get(get_deleted_users))
+        .route("/users/{id}", get(get_user))
+        .route("/users/{id}", put(edit_user))
+        .route("/users/{id}", patch(patch_user))
+        .route("/users/{id}", delete(delete_user))
+        .route("/users/{id}/restore", post(restore_user))
+        .layer(from_fn(check_login))
+        .layer(from_fn(api_key_middleware))
+}
@@ -0,0 +1,265 @@
+use axum::{Router, routing::{get, patch, post, put}};
+use axum_test::TestServer;
+use chrono::{TimeZone, Utc};
+use http::StatusCode;
+use serde_json::{Value, json};
+
+use crate::{
+    configs::db,
+    controllers::{
+        attribute_controller::{delete_attribute, list_attributes, put_attribute},
+        user_controller::{insert_user, patch_user, search_users},
+    },
+    errors::app_error::AppError,
+    models::{
+        attribute_model::{AttributeDefinition, AttributeDefinitionInput, AttributeKind, AttributeValues},
+        user_model::User,
+    },
+    tests::common::{api_key, protected_server, session_cookie},
+    utils::{
+        attributes::{build_definition, merge_attributes, parse_attribute_text, validate_attributes},
+        search::{count_query, highlights},
+        search_index::UserIndex,
+        search_query::{Filter, SearchExpr, check_attributes, parse_search},
+    },
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn server() -> TestServer {
+    protected_server(
+        Router::new()
+            .route("/users", post(insert_user))
+            .route("/users/search", get(search_users))
+            .route("/users/attributes", get(list_attributes))
+            .route("/users/attributes/{name}", put(put_attribute).delete(delete_attribute))
+            .route("/users/{id}", patch(patch_user))
+    )
+}
+
+fn input(value: Value) -> AttributeDefinitionInput {
+    serde_json::from_value(value).unwrap()
+}
+
+fn definitions() -> Vec<AttributeDefinition> {
+    vec![
+        build_definition("department", input(json!({"label": "Departemen", "kind": "string", "required": true, "choices": ["eng", "ops", "sales"]}))).unwrap(),
+        build_definition("phone", input(json!({"label": "Telepon", "kind": "string", "pattern": "\\+?[0-9]{8,15}"}))).unwrap(),
+        build_definition("level", input(json!({"label": "Level", "kind": "integer", "minimum": 1, "maximum": 10}))).unwrap(),
+        build_definition("joined", input(json!({"label": "Tanggal masuk", "kind": "date"}))).unwrap(),
+        build_definition("remote", input(json!({"label": "Remote", "kind": "boolean"}))).unwrap(),
+    ]
+}
+
+fn values(value: Value) -> AttributeValues {
+    serde_json::from_value(value).unwrap()
+}
+
+fn error_fields(values: &AttributeValues) -> Vec<String> {
+    let errors = validate_attributes(&definitions(), values).unwrap_err();
+    let mut fields: Vec<String> = errors.errors().keys().map(|k| k.to_string()).collect();
+    fields.sort();
+    fields
+}
+
+fn user(id: u64, attributes: Value) -> User {
+    let at = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    User { id, name: format!("User {}", id), email: format!("user{}@corp.com", id), password: None, created_at: at, updated_at: at, deleted_at: None, avatar_url: None, attributes: values(attributes) }
+}
+
+/// =======================
+/// Definition Tests
+/// =======================
+
+#[test]
+fn definition_name_and_rules_must_fit_the_kind() {
+    assert!(build_definition("Department", input(json!({"label": "Dept", "kind": "string"}))).is_err());
+    assert!(build_definition("1st", input(json!({"label": "Dept", "kind": "string"}))).is_err());
+
+    let errors = build_definition("level", input(json!({"label": "Level", "kind": "integer", "max_length": 3, "minimum": 5, "maximum": 1}))).unwrap_err();
+    assert!(errors.errors().contains_key("max_length"));
+    assert!(errors.errors().contains_key("minimum"));
+
+    assert!(build_definition("phone", input(json!({"label": "Telepon", "kind": "string", "pattern": "([0-9]"}))).is_err());
+    assert!(build_definition("dept", input(json!({"label": "Dept", "kind": "string", "choices": ["a", "a"]}))).is_err());
+}
+
+#[test]
+fn unknown_definition_fields_are_rejected() {
+    assert!(serde_json::from_value::<AttributeDefinitionInput>(json!({"label": "Dept", "kind": "string", "unique": true})).is_err());
+    assert!(serde_json::from_value::<AttributeDefinitionInput>(json!({"label": "Dept", "kind": "list"})).is_err());
+}
+
+/// =======================
+/// Value Validation Tests
+/// =======================
+
+#[test]
+fn valid_values_are_normalised() {
+    let clean = validate_attributes(&definitions(), &values(json!({
+        "department": " eng ",
+        "phone": "+628123456789",
+        "level": 3,
+        "joined": "2026-02-01",
+        "remote": true,
+        "phone_ext": null,
+    })));
+    assert!(clean.is_err(), "atribut yang tidak dikenal tetap ditolak walaupun null");
+
+    let clean = validate_attributes(&definitions(), &values(json!({"department": " eng ", "level": 3, "remote": null}))).unwrap();
+    assert_eq!(clean, values(json!({"department": "eng", "level": 3})));
+}
+
+#[test]
+fn invalid_values_are_reported_per_attribute() {
+    assert_eq!(error_fields(&values(json!({}))), vec!["attributes.department"]);
+    assert_eq!(
+        error_fields(&values(json!({"department": "hr", "phone": "12ab", "level": 11, "joined": "01-02-2026", "remote": "ya", "shoe": 42}))),
+        vec!["attributes.department", "attributes.joined", "attributes.level", "attributes.phone", "attributes.remote", "attributes.shoe"]
+    );
+    assert_eq!(error_fields(&values(json!({"department": "eng", "level": 2.5}))), vec!["attributes.level"]);
+}
+
+#[test]
+fn merge_patch_removes_null_attributes() {
+    let current = values(json!({"department": "eng", "level": 3}));
+    let patch = json!({"level": null, "remote": false});
+    let merged = merge_attributes(&current, patch.as_object().unwrap());
+    assert_eq!(merged, values(json!({"department": "eng", "remote": false})));
+}
+
+#[test]
+fn csv_text_is_converted_by_kind() {
+    assert_eq!(parse_attribute_text(AttributeKind::Integer, " 42 "), json!(42));
+    assert_eq!(parse_attribute_text(AttributeKind::Integer, "empat"), json!("empat"));
+    assert_eq!(parse_attribute_text(AttributeKind::Number, "1.5"), json!(1.5));
+    assert_eq!(parse_attribute_text(AttributeKind::Boolean, "Yes"), json!(true));
+    assert_eq!(parse_attribute_text(AttributeKind::String, ""), Value::Null);
+}
+
+/// =======================
+/// Search Tests
+/// =======================
+
+#[test]
+fn attribute_filters_are_parsed_and_checked() {
+    let expr = parse_search("attr.department:Eng*").unwrap();
+    assert_eq!(expr, SearchExpr::Filter(Filter::Attribute { name: "department".into(), pattern: "Eng*".into() }));
+
+    let expr = parse_search("budi -attr.shoe:42").unwrap();
+    assert_eq!(expr.attribute_names(), vec!["shoe"]);
+    assert!(matches!(check_attributes(&expr, &["department"]), Err(AppError::ValidationError(_))));
+    assert!(check_attributes(&expr, &["shoe"]).is_ok());
+
+    assert!(parse_search("attr.level>3").is_err());
+    assert!(parse_search("attr.Dept:eng").is_err());
+}
+
+#[test]
+fn attribute_sql_treats_missing_values_as_false() {
+    let sql = count_query(&parse_search("-attr.department:eng").unwrap()).into_sql();
+    assert_eq!(sql, "SELECT COUNT(*) FROM users WHERE deleted_at IS NULL AND NOT (COALESCE(LOWER(JSON_UNQUOTE(JSON_EXTRACT(attributes, ?))) = ?, FALSE))");
+}
+
+#[test]
+fn embedded_index_matches_attribute_values() {
+    let index = UserIndex::in_memory().unwrap();
+    index.replace_all([Ok(vec![
+        user(1, json!({"department": "Engineering", "level": 3})),
+        user(2, json!({"department": "Ops", "level": 3})),
+        user(3, json!({})),
+    ])]).unwrap();
+    let ids = |q: &str| {
+        let (hits, _) = index.search_ids(&parse_search(q).unwrap(), 0, 20).unwrap();
+        let mut ids: Vec<u64> = hits.into_iter().map(|(id, _)| id).collect();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(ids("attr.department:engineering"), vec![1]);
+    assert_eq!(ids("attr.department:eng*"), vec![1]);
+    assert_eq!(ids("attr.level:3"), vec![1, 2]);
+    assert_eq!(ids("-attr.department:ops"), vec![1, 3]);
+}
+
+#[test]
+fn attribute_matches_are_highlighted() {
+    let expr = parse_search("attr.department:eng*").unwrap();
+    let result = highlights(&expr, &user(1, json!({"department": "Engineering"})));
+    assert_eq!(result["attr.department"], "<mark>Eng</mark>ineering");
+}
+
+/// =======================
+/// API Tests
+/// =======================
+
+#[tokio::test]
+async fn attributes_are_defined_validated_stored_and_searchable() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let pool = db::get_pool().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE email = ?").bind("attr-user@corp.test.com").execute(&pool).await.unwrap();
+
+    let res = server.put("/users/attributes/test_team").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({"label": "Tim", "kind": "string", "choices": ["alpha", "beta"]}))
+        .await;
+    assert!(matches!(res.status_code(), StatusCode::CREATED | StatusCode::OK));
+
+    let res = server.get("/users/attributes").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin).await;
+    assert!(res.json::<Value>().as_array().unwrap().iter().any(|d| d["name"] == "test_team"));
+
+    let res = server.post("/users").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({"name": "Attr User", "email": "attr-user@corp.test.com", "password": "Rahasia-123!", "attributes": {"test_team": "gamma"}}))
+        .await;
+    assert_eq!(res.status_code(), StatusCode::BAD_REQUEST);
+
+    let res = server.post("/users").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({"name": "Attr User", "email": "attr-user@corp.test.com", "password": "Rahasia-123!", "attributes": {"test_team": "alpha"}}))
+        .await;
+    assert_eq!(res.status_code(), StatusCode::CREATED);
+
+    let res = server.get("/users/search").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("q", "attr.test_team:alpha email:attr-user@corp.test.com")
+        .await;
+    let body: Value = res.json();
+    assert_eq!(body["total"], 1);
+    let id = body["items"][0]["id"].as_u64().unwrap();
+    assert_eq!(body["items"][0]["attributes"]["test_team"], "alpha");
+
+    let res = server.patch(&format!("/users/{}", id)).add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({"attributes": {"test_team": null}}))
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+    assert!(res.json::<Value>()["attributes"].get("test_team").is_none());
+
+    let res = server.delete("/users/attributes/test_team").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin).await;
+    assert_eq!(res.status_code(), StatusCode::NO_CONTENT);
+    let res = server.get("/users/search").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("q", "attr.test_team:alpha")
+        .await;
+    assert_eq!(res.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn putting_the_same_definition_again_returns_ok() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let definition = json!({"label": "Lantai", "kind": "integer", "minimum": 1, "maximum": 40});
+
+    let res = server.delete("/users/attributes/test_floor").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin).await;
+    assert!(matches!(res.status_code(), StatusCode::NO_CONTENT | StatusCode::NOT_FOUND));
+
+    let res = server.put("/users/attributes/test_floor").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&definition)
+        .await;
+    assert_eq!(res.status_code(), StatusCode::CREATED);
+    let res = server.put("/users/attributes/test_floor").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&definition)
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+
+    let res = server.delete("/users/attributes/test_floor").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin).await;
+    assert_eq!(res.status_code(), StatusCode::NO_CONTENT);
+}
@@ -0,0 +1,318 @@
+use std::{io::Cursor, net::SocketAddr};
+use axum::{Router, extract::{ConnectInfo, FromRequestParts, Query}, middleware::from_fn, routing::{delete, get, patch, post, put}};
+use axum_test::{TestServer, multipart::{MultipartForm, Part}};
+use chrono::{TimeZone, Utc};
+use http::{Request, StatusCode, Uri};
+use image::{ImageFormat, RgbImage};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    configs::db,
+    controllers::{
+        audit_controller::{audit_select_query, list_audit, verify_audit},
+        avatar_controller::upload_avatar,
+        user_controller::{change_password, delete_user_by_id, insert_user, login_user, patch_user},
+        user_transfer_controller::import_users,
+    },
+    middlewares::api_middleware::{api_key_middleware, check_guest},
+    models::{
+        audit_model::{ActorKind, AuditAction, AuditEntry, AuditQuery},
+        user_model::User,
+    },
+    tests::common::{api_key, protected, session_cookie},
+    utils::{
+        audit::{AuditContext, ChainVerifier, entry_hash, genesis_hash, user_changes},
+        utils::create_jwt,
+    },
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn server() -> TestServer {
+    let users = protected(
+        Router::new()
+            .route("/users", post(insert_user))
+            .route("/users/import", post(import_users))
+            .route("/users/{id}", patch(patch_user))
+            .route("/me/password", put(change_password))
+            .route("/me/avatar", put(upload_avatar))
+            .route("/users/{id}", delete(delete_user_by_id))
+            .route("/audit", get(list_audit))
+            .route("/audit/verify", get(verify_audit))
+    );
+    let guest = Router::new()
+        .route("/login", post(login_user))
+        .layer(from_fn(api_key_middleware))
+        .layer(from_fn(check_guest));
+    TestServer::new(users.merge(guest)).unwrap()
+}
+
+fn user(name: &str, email: &str) -> User {
+    let at = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    User { id: 7, name: name.into(), email: email.into(), password: Some("$argon2id$rahasia".into()), created_at: at, updated_at: at, deleted_at: None, avatar_url: None, attributes: Default::default() }
+}
+
+//rantai entri seperti yang ditulis audit::append
+fn chain(count: u64) -> Vec<AuditEntry> {
+    let mut entries: Vec<AuditEntry> = Vec::new();
+    for id in 1..=count {
+        let mut entry = AuditEntry {
+            id,
+            created_at: Utc.timestamp_millis_opt(1_767_225_600_000 + id as i64).unwrap(),
+            action: AuditAction::UserUpdate,
+            actor_type: ActorKind::User,
+            actor_id: Some("1".into()),
+            target_id: Some(7),
+            changes: user_changes(Some(&user("Budi", "budi@corp.com")), Some(&user(&format!("Budi {}", id), "budi@corp.com"))),
+            ip_address: Some("10.0.0.1".into()),
+            user_agent: None,
+            prev_hash: entries.last().map(|e| e.hash.clone()).unwrap_or_else(genesis_hash),
+            hash: String::new(),
+        };
+        entry.hash = entry_hash(&entry);
+        entries.push(entry);
+    }
+    entries
+}
+
+fn verify(entries: &[AuditEntry], head: &str) -> (bool, u64, Option<u64>) {
+    let mut verifier = ChainVerifier::new(head);
+    for entry in entries {
+        if !verifier.check(entry) {
+            let result = verifier.broken(entry);
+            return (result.valid, result.checked, result.broken_at);
+        }
+    }
+    let result = verifier.finish();
+    (result.valid, result.checked, result.broken_at)
+}
+
+/// =======================
+/// Diff Tests
+/// =======================
+
+#[test]
+fn created_user_diff_has_no_password_or_timestamps() {
+    let changes = user_changes(None, Some(&user("Budi", "budi@corp.com"))).unwrap();
+
+    assert_eq!(changes["name"], json!({"before": null, "after": "Budi"}));
+    assert_eq!(changes["email"], json!({"before": null, "after": "budi@corp.com"}));
+    for hidden in ["password", "id", "created_at", "updated_at"] {
+        assert!(!changes.contains_key(hidden), "{} tidak boleh dicatat", hidden);
+    }
+}
+
+#[test]
+fn update_diff_lists_only_changed_fields() {
+    let before = user("Budi", "budi@corp.com");
+    let mut after = user("Budi", "budi@other.com");
+    after.password = Some("$argon2id$baru".into());
+    after.attributes.insert("department".into(), json!("eng"));
+
+    let changes = user_changes(Some(&before), Some(&after)).unwrap();
+    assert_eq!(changes.keys().collect::<Vec<_>>(), vec!["attributes", "email"]);
+    assert_eq!(changes["attributes"], json!({"before": {}, "after": {"department": "eng"}}));
+
+    assert!(user_changes(Some(&before), Some(&before.clone())).is_none());
+}
+
+/// =======================
+/// Hash Chain Tests
+/// =======================
+
+#[test]
+fn hash_survives_json_roundtrip_of_changes() {
+    let entry = chain(1).remove(0);
+    let mut reloaded = entry.clone();
+    //kolom JSON MySQL bisa mengembalikan key dengan urutan lain
+    let text = serde_json::to_string(&entry.changes).unwrap();
+    reloaded.changes = serde_json::from_str::<Option<Map<String, Value>>>(&text).unwrap();
+
+    assert_eq!(entry_hash(&reloaded), entry.hash);
+    assert_eq!(entry.prev_hash, genesis_hash());
+}
+
+#[test]
+fn intact_chain_is_valid() {
+    let entries = chain(3);
+    assert_eq!(verify(&entries, &entries[2].hash), (true, 3, None));
+    assert_eq!(verify(&[], &genesis_hash()), (true, 0, None));
+}
+
+#[test]
+fn modified_entry_breaks_the_chain() {
+    let mut entries = chain(3);
+    entries[1].actor_id = Some("2".into());
+    let head = entries[2].hash.clone();
+    assert_eq!(verify(&entries, &head), (false, 2, Some(2)));
+}
+
+#[test]
+fn removed_entries_are_detected() {
+    let entries = chain(3);
+    let head = entries[2].hash.clone();
+
+    let without_middle = vec![entries[0].clone(), entries[2].clone()];
+    assert_eq!(verify(&without_middle, &head), (false, 2, Some(3)));
+
+    //entri terakhir hilang: rantai masih menyambung tapi ujungnya tidak ditemukan
+    assert_eq!(verify(&entries[..2], &head), (false, 2, None));
+}
+
+/// =======================
+/// Actor Tests
+/// =======================
+
+#[tokio::test]
+async fn actor_comes_from_session_claims_or_api_key() {
+    let (_, claims) = create_jwt(42, "sid").unwrap();
+    //peer bukan trusted proxy sehingga X-Forwarded-For diabaikan
+    let request = Request::builder()
+        .header("x-forwarded-for", "203.0.113.9")
+        .header("user-agent", "curl/8")
+        .extension(ConnectInfo("198.51.100.7:5000".parse::<SocketAddr>().unwrap()))
+        .extension(claims)
+        .body(())
+        .unwrap();
+    let (mut parts, _) = request.into_parts();
+    let ctx = AuditContext::from_request_parts(&mut parts, &()).await.unwrap();
+    assert_eq!((ctx.actor_type, ctx.actor_id.as_deref()), (ActorKind::User, Some("42")));
+    assert_eq!(ctx.client.ip_address.as_deref(), Some("198.51.100.7"));
+    assert_eq!(ctx.client.user_agent.as_deref(), Some("curl/8"));
+
+    let (mut parts, _) = Request::builder().body(()).unwrap().into_parts();
+    let ctx = AuditContext::from_request_parts(&mut parts, &()).await.unwrap();
+    assert_eq!((ctx.actor_type, ctx.actor_id), (ActorKind::ApiKey, None));
+}
+
+/// =======================
+/// Query Tests
+/// =======================
+
+#[test]
+fn audit_filters_use_bind_parameters() {
+    let uri: Uri = "/audit?action=user.delete&actor_type=user&actor_id=1&target_id=7&from=2026-01-01T00:00:00Z".parse().unwrap();
+    let Query(query) = Query::<AuditQuery>::try_from_uri(&uri).unwrap();
+    assert_eq!(query.action, Some(AuditAction::UserDelete));
+
+    let sql = audit_select_query(&query, 0, 50).into_sql();
+    assert_eq!(sql, "SELECT * FROM audit_log WHERE TRUE AND action = ? AND actor_type = ? AND actor_id = ? AND target_id = ? AND created_at >= ? ORDER BY id DESC LIMIT ? OFFSET ?");
+
+    let uri: Uri = "/audit?action=user.drop".parse().unwrap();
+    assert!(Query::<AuditQuery>::try_from_uri(&uri).is_err());
+}
+
+/// =======================
+/// API Tests
+/// =======================
+
+#[tokio::test]
+async fn user_changes_are_audited_and_chain_verifies() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let pool = db::get_pool().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE email = ?").bind("audit-user@corp.test.com").execute(&pool).await.unwrap();
+
+    let res = server.post("/users").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({"name": "Audit User", "email": "audit-user@corp.test.com", "password": "Rahasia-123!"}))
+        .await;
+    assert_eq!(res.status_code(), StatusCode::CREATED);
+    let (id,): (u64,) = sqlx::query_as("SELECT id FROM users WHERE email = ?").bind("audit-user@corp.test.com").fetch_one(&pool).await.unwrap();
+
+    let res = server.patch(&format!("/users/{}", id)).add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_header("User-Agent", "audit-test")
+        .json(&json!({"name": "Audit User Baru"}))
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+
+    let res = server.delete(&format!("/users/{}", id)).add_header("X-API-KEY", api_key()).add_header("Cookie", &admin).await;
+    assert_eq!(res.status_code(), StatusCode::NO_CONTENT);
+
+    let res = server.get("/audit").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("target_id", id)
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+    let body: Value = res.json();
+    let actions: Vec<&str> = body["items"].as_array().unwrap().iter().map(|e| e["action"].as_str().unwrap()).collect();
+    assert_eq!(actions, vec!["user.delete", "user.update", "user.create"]);
+
+    let update = &body["items"][1];
+    assert_eq!(update["actor_type"], "user");
+    assert_eq!(update["actor_id"], "1");
+    assert_eq!(update["user_agent"], "audit-test");
+    assert_eq!(update["changes"], json!({"name": {"before": "Audit User", "after": "Audit User Baru"}}));
+    assert_eq!(body["items"][0]["prev_hash"], update["hash"]);
+
+    let res = server.get("/audit/verify").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin).await;
+    assert_eq!(res.json::<Value>()["valid"], true);
+}
+
+#[tokio::test]
+async fn failed_and_successful_logins_are_audited() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let pool = db::get_pool().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE email = ?").bind("audit-login@corp.test.com").execute(&pool).await.unwrap();
+    server.post("/users").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({"name": "Audit Login", "email": "audit-login@corp.test.com", "password": "Rahasia-123!"}))
+        .await;
+    let (id,): (u64,) = sqlx::query_as("SELECT id FROM users WHERE email = ?").bind("audit-login@corp.test.com").fetch_one(&pool).await.unwrap();
+
+    let res = server.post("/login").add_header("X-API-KEY", api_key())
+        .json(&json!({"email": "audit-login@corp.test.com", "password": "salah-sekali"}))
+        .await;
+    assert_eq!(res.status_code(), StatusCode::UNAUTHORIZED);
+    let res = server.post("/login").add_header("X-API-KEY", api_key())
+        .json(&json!({"email": "audit-login@corp.test.com", "password": "Rahasia-123!"}))
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+
+    let res = server.get("/audit").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("target_id", id)
+        .add_query_param("per_page", 2)
+        .await;
+    let body: Value = res.json();
+    assert_eq!(body["items"][0]["action"], "auth.login");
+    assert_eq!(body["items"][0]["actor_id"], id.to_string());
+    assert_eq!(body["items"][1]["action"], "auth.login_failed");
+    assert_eq!(body["items"][1]["actor_type"], "api_key");
+}
+
+#[tokio::test]
+async fn import_avatar_and_password_change_are_audited() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let pool = db::get_pool().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE email = ?").bind("audit-import@corp.test.com").execute(&pool).await.unwrap();
+
+    let res = server.post("/users/import").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_header("Content-Type", "application/x-ndjson")
+        .text(r#"{"name": "Audit Import", "email": "audit-import@corp.test.com", "password": "Rahasia-123!"}"#)
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+    let id = res.json::<Value>()["rows"][0]["id"].as_u64().unwrap();
+    let cookie = session_cookie(id).await;
+
+    let mut png = Vec::new();
+    RgbImage::from_pixel(32, 32, image::Rgb([10, 120, 200])).write_to(&mut Cursor::new(&mut png), ImageFormat::Png).unwrap();
+    let form = MultipartForm::new().add_part("avatar", Part::bytes(png).file_name("foto.png").mime_type("image/png"));
+    let res = server.put("/me/avatar").add_header("X-API-KEY", api_key()).add_header("Cookie", &cookie).multipart(form).await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+
+    let res = server.put("/me/password").add_header("X-API-KEY", api_key()).add_header("Cookie", &cookie)
+        .json(&json!({"current_password": "Rahasia-123!", "new_password": "Rahasia-456!"}))
+        .await;
+    assert_eq!(res.status_code(), StatusCode::NO_CONTENT);
+
+    let res = server.get("/audit").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("target_id", id)
+        .await;
+    let body: Value = res.json();
+    let actions: Vec<&str> = body["items"].as_array().unwrap().iter().map(|e| e["action"].as_str().unwrap()).collect();
+    assert_eq!(actions, vec!["auth.password_change", "user.update", "user.create"]);
+    assert_eq!(body["items"][0]["changes"], Value::Null);
+    assert!(body["items"][1]["changes"]["avatar_url"]["after"].is_string());
+    assert_eq!(body["items"][2]["actor_id"], "1");
+}
@@ -0,0 +1,239 @@
+use std::{io::Cursor, time::Duration};
+use axum::{Router, routing::{get, put}};
+use axum_test::{TestServer, multipart::{MultipartForm, Part}};
+use http::StatusCode;
+use image::{ImageFormat, RgbImage};
+use serde_json::Value;
+
+use crate::{
+    configs::db,
+    controllers::avatar_controller::{get_avatar, make_thumbnails, sniff_image, upload_avatar},
+    errors::app_error::AppError,
+    jobs::legacy_avatars::migrate_legacy_avatars,
+    models::config_model::AvatarConfig,
+    tests::common::{api_key, protected, session_cookie},
+    utils::{
+        blob_store::{BlobStore, LocalBlobStore, content_key, is_valid_key},
+    },
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn server() -> TestServer {
+    let app = protected(Router::new().route("/me/avatar", put(upload_avatar)))
+        .route("/avatars/{file}", get(get_avatar));
+    TestServer::new(app).unwrap()
+}
+
+fn image(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+    let mut out = Vec::new();
+    RgbImage::from_pixel(width, height, image::Rgb([200, 40, 40]))
+        .write_to(&mut Cursor::new(&mut out), format)
+        .unwrap();
+    out
+}
+
+fn dimensions(png: &[u8]) -> (u32, u32) {
+    let decoded = image::load_from_memory_with_format(png, ImageFormat::Png).unwrap();
+    (decoded.width(), decoded.height())
+}
+
+/// =======================
+/// Image Tests
+/// =======================
+
+#[test]
+fn image_type_is_sniffed_from_content() {
+    assert_eq!(sniff_image(&image(4, 4, ImageFormat::Png)).unwrap(), ImageFormat::Png);
+    assert_eq!(sniff_image(&image(4, 4, ImageFormat::Jpeg)).unwrap(), ImageFormat::Jpeg);
+    assert!(matches!(sniff_image(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"), Err(AppError::UnsupportedMediaType)));
+    assert!(matches!(sniff_image(b"GIF89a-bukan-gambar"), Ok(ImageFormat::Gif)));
+}
+
+#[test]
+fn thumbnails_are_square_and_sorted() {
+    let cfg = AvatarConfig { sizes: vec![128, 32, 128], ..AvatarConfig::default() };
+    let thumbnails = make_thumbnails(&image(300, 120, ImageFormat::Png), ImageFormat::Png, &cfg).unwrap();
+
+    let sizes: Vec<u32> = thumbnails.iter().map(|(size, _)| *size).collect();
+    assert_eq!(sizes, vec![32, 128]);
+    assert_eq!(dimensions(&thumbnails[1].1), (128, 128));
+}
+
+#[test]
+fn oversized_or_broken_images_are_rejected() {
+    let cfg = AvatarConfig { max_dimension: 100, ..AvatarConfig::default() };
+    assert!(matches!(make_thumbnails(&image(101, 10, ImageFormat::Png), ImageFormat::Png, &cfg), Err(AppError::PayloadTooLarge)));
+
+    let mut broken = image(10, 10, ImageFormat::Png);
+    broken.truncate(40);
+    assert!(matches!(make_thumbnails(&broken, ImageFormat::Png, &cfg), Err(AppError::BadRequest)));
+}
+
+/// =======================
+/// Blob Store Tests
+/// =======================
+
+#[test]
+fn keys_cannot_escape_the_store() {
+    assert!(is_valid_key("avatars/1/abc/64.png"));
+    assert!(!is_valid_key("avatars/../secret"));
+    assert!(!is_valid_key("/etc/passwd"));
+    assert!(!is_valid_key("avatars//64.png"));
+    assert!(!is_valid_key("avatars/1/a b.png"));
+}
+
+#[tokio::test]
+async fn local_store_puts_gets_and_deletes() {
+    let root = std::env::temp_dir().join(format!("blob-test-{}", uuid::Uuid::new_v4().simple()));
+    let store = LocalBlobStore::new(&root, "https://cdn.test/");
+
+    store.put("avatars/1/v1/64.png", b"png".to_vec(), "image/png").await.unwrap();
+    let blob = store.get("avatars/1/v1/64.png").await.unwrap().unwrap();
+    assert_eq!(blob.data, b"png");
+    assert_eq!(blob.content_type, "image/png");
+    assert_eq!(store.url("avatars/1/v1/64.png"), "https://cdn.test/avatars/1/v1/64.png");
+    assert_eq!(store.presigned_url("avatars/1/v1/64.png", Duration::from_secs(60)).unwrap(), "https://cdn.test/avatars/1/v1/64.png");
+
+    store.delete("avatars/1/v1/64.png").await.unwrap();
+    assert!(store.get("avatars/1/v1/64.png").await.unwrap().is_none());
+    store.delete("avatars/1/v1/64.png").await.unwrap();
+    assert!(store.get("../outside").await.is_err());
+
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[test]
+fn content_key_is_derived_from_content() {
+    let key = content_key("avatars", b"abc", "png");
+    assert_eq!(key, "avatars/ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad.png");
+    assert_eq!(content_key("avatars", b"abc", "png"), key);
+    assert_ne!(content_key("avatars", b"abd", "png"), key);
+    assert!(is_valid_key(&key));
+}
+
+#[test]
+fn relative_url_is_used_without_public_url() {
+    let store = LocalBlobStore::new("data/blobs", "");
+    assert_eq!(store.url("avatars/abc.png"), "/avatars/abc.png");
+}
+
+/// =======================
+/// PUT /me/avatar Tests
+/// =======================
+
+#[tokio::test]
+async fn upload_stores_thumbnails_and_sets_avatar_url() {
+    let server = server();
+    let cookie = session_cookie(1).await;
+
+    let form = MultipartForm::new().add_part("avatar", Part::bytes(image(300, 200, ImageFormat::Jpeg)).file_name("foto.jpg").mime_type("image/jpeg"));
+    let res = server.put("/me/avatar").add_header("X-API-KEY", api_key()).add_header("Cookie", &cookie).multipart(form).await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+
+    let body: Value = res.json();
+    let url = body["avatar_url"].as_str().unwrap();
+    assert!(url.starts_with("/avatars/") && url.ends_with(".png"));
+    assert_eq!(body["thumbnails"].as_object().unwrap().len(), 3);
+    assert_eq!(body["thumbnails"]["256"].as_str(), Some(url));
+
+    let res = server.get(url).await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+    assert_eq!(res.header("content-type"), "image/png");
+    assert_eq!(dimensions(res.as_bytes()), (256, 256));
+
+    let pool = db::get_pool().await.unwrap();
+    let (stored,): (Option<String>,) = sqlx::query_as("SELECT avatar_url FROM users WHERE id = 1").fetch_one(&pool).await.unwrap();
+    assert_eq!(stored.as_deref(), Some(url));
+    let (refs,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM user_blobs WHERE user_id = 1 AND purpose LIKE 'avatar\\_%'").fetch_one(&pool).await.unwrap();
+    assert_eq!(refs, 3);
+}
+
+#[tokio::test]
+async fn identical_uploads_share_the_same_blobs() {
+    let server = server();
+    let cookie = session_cookie(1).await;
+    let data = image(64, 64, ImageFormat::Png);
+
+    let mut urls = Vec::new();
+    for _ in 0..2 {
+        let form = MultipartForm::new().add_part("avatar", Part::bytes(data.clone()).file_name("foto.png").mime_type("image/png"));
+        let res = server.put("/me/avatar").add_header("X-API-KEY", api_key()).add_header("Cookie", &cookie).multipart(form).await;
+        assert_eq!(res.status_code(), StatusCode::OK);
+        urls.push(res.json::<Value>()["avatar_url"].as_str().unwrap().to_string());
+    }
+    assert_eq!(urls[0], urls[1]);
+
+    let key = urls[0].trim_start_matches('/');
+    let pool = db::get_pool().await.unwrap();
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM blobs WHERE blob_key = ?").bind(key).fetch_one(&pool).await.unwrap();
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn upload_rejects_non_images_even_with_image_content_type() {
+    let server = server();
+    let cookie = session_cookie(1).await;
+
+    let form = MultipartForm::new().add_part("avatar", Part::bytes(b"<html>bukan gambar</html>".to_vec()).file_name("foto.png").mime_type("image/png"));
+    let res = server.put("/me/avatar").add_header("X-API-KEY", api_key()).add_header("Cookie", &cookie).multipart(form).await;
+    assert_eq!(res.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+/// =======================
+/// Legacy Avatar Tests
+/// =======================
+
+#[tokio::test]
+async fn legacy_avatars_are_moved_to_user_blobs() {
+    let pool = db::get_pool().await.unwrap();
+    let root = std::env::temp_dir().join(format!("legacy-avatar-{}", uuid::Uuid::new_v4().simple()));
+    let store = LocalBlobStore::new(&root, "");
+    let email = format!("legacy-{}@test.com", uuid::Uuid::new_v4().simple());
+    let user_id = sqlx::query("INSERT INTO users (name, email, password) VALUES ('Legacy Avatar', ?, NULL)")
+        .bind(&email)
+        .execute(&pool)
+        .await
+        .unwrap()
+        .last_insert_id();
+
+    //susunan lama: avatars/{user_id}/{versi}/{ukuran}.png, ukuran 256 tidak pernah dibuat
+    let prefix = format!("avatars/{}/old", user_id);
+    store.put(&format!("{}/64.png", prefix), image(64, 64, ImageFormat::Png), "image/png").await.unwrap();
+    store.put(&format!("{}/128.png", prefix), image(128, 128, ImageFormat::Png), "image/png").await.unwrap();
+    sqlx::query("INSERT INTO legacy_avatar_keys (user_id, avatar_key) VALUES (?, ?)")
+        .bind(user_id)
+        .bind(&prefix)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert!(migrate_legacy_avatars(&pool, &store, &[64, 128, 256]).await.unwrap() >= 1);
+
+    let (avatar_url,): (Option<String>,) = sqlx::query_as("SELECT avatar_url FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let legacy: Option<(String,)> = sqlx::query_as("SELECT avatar_key FROM legacy_avatar_keys WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_optional(&pool)
+        .await
+        .unwrap();
+    let purposes: Vec<(String, String)> = sqlx::query_as("SELECT purpose, blob_key FROM user_blobs WHERE user_id = ? ORDER BY purpose")
+        .bind(user_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert!(legacy.is_none());
+    assert_eq!(purposes.iter().map(|(p, _)| p.as_str()).collect::<Vec<_>>(), vec!["avatar_128", "avatar_64"]);
+    assert_eq!(avatar_url, Some(store.url(&purposes[0].1)));
+    assert_eq!(dimensions(&store.get(&purposes[0].1).await.unwrap().unwrap().data), (128, 128));
+    assert!(store.get(&format!("{}/64.png", prefix)).await.unwrap().is_none());
+
+    sqlx::query("DELETE FROM user_blobs WHERE user_id = ?").bind(user_id).execute(&pool).await.unwrap();
+    sqlx::query("DELETE FROM users WHERE id = ?").bind(user_id).execute(&pool).await.unwrap();
+    let _ = std::fs::remove_dir_all(root);
+}
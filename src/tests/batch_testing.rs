@@ -0,0 +1,134 @@
+use axum::{Router, routing::post};
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{Value, json};
+
+use crate::{
+    configs::db,
+    controllers::user_batch_controller::batch_users,
+    models::batch_model::{BatchOperation, BatchRequest},
+    tests::common::{api_key, protected_server, session_cookie},
+    utils::{
+        etag::IfMatch,
+    },
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn server() -> TestServer {
+    protected_server(
+        Router::new()
+            .route("/users/batch", post(batch_users))
+    )
+}
+
+async fn insert_user(name: &str, email: &str) -> u64 {
+    let pool = db::get_pool().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE email = ?").bind(email).execute(&pool).await.unwrap();
+    sqlx::query("INSERT INTO users (name, email, password) VALUES (?, ?, ?)")
+        .bind(name)
+        .bind(email)
+        .bind("123456")
+        .execute(&pool)
+        .await
+        .unwrap()
+        .last_insert_id()
+}
+
+async fn user_name(id: u64) -> String {
+    let pool = db::get_pool().await.unwrap();
+    let (name,): (String,) = sqlx::query_as("SELECT name FROM users WHERE id = ?").bind(id).fetch_one(&pool).await.unwrap();
+    name
+}
+
+/// =======================
+/// Request Tests
+/// =======================
+
+#[test]
+fn operations_are_tagged_by_op() {
+    let request: BatchRequest = serde_json::from_value(json!({
+        "operations": [
+            { "op": "create", "data": { "name": "Budi", "email": "budi@corp.com", "password": "rahasia" } },
+            { "op": "update", "id": 5, "data": { "name": "Budi", "email": "budi@corp.com" }, "if_match": "\"5-1\"" },
+            { "op": "delete", "id": 5 }
+        ]
+    })).unwrap();
+
+    assert!(matches!(request.operations[0], BatchOperation::Create { .. }));
+    assert!(matches!(&request.operations[1], BatchOperation::Update { id: 5, if_match: Some(tag), .. } if tag == "\"5-1\""));
+    assert!(matches!(request.operations[2], BatchOperation::Delete { id: 5 }));
+    assert!(serde_json::from_value::<BatchRequest>(json!({ "operations": [{ "op": "restore", "id": 5 }] })).is_err());
+}
+
+#[test]
+fn if_match_value_is_parsed_like_header() {
+    assert_eq!(IfMatch::parse("*"), IfMatch::Any);
+    assert_eq!(IfMatch::parse("\"1-2\", W/\"1-3\""), IfMatch::Tags(vec!["\"1-2\"".into(), "W/\"1-3\"".into()]));
+}
+
+/// =======================
+/// POST /users/batch Tests
+/// =======================
+
+#[tokio::test]
+async fn transactional_batch_rolls_back_every_operation_on_failure() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let id = insert_user("Batch Awal", "batch-awal@corp.test.com").await;
+
+    let res = server.post("/users/batch").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({
+            "operations": [
+                { "op": "update", "id": id, "data": { "name": "Batch Diubah", "email": "batch-awal@corp.test.com" } },
+                { "op": "create", "data": { "name": "X", "email": "batch-baru@corp.test.com", "password": "Rahasia-Panjang-123" } },
+                { "op": "delete", "id": id }
+            ]
+        }))
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+
+    let body: Value = res.json();
+    assert_eq!(body["committed"], false);
+    assert_eq!(body["results"][0]["status"], 424);
+    assert_eq!(body["results"][1]["status"], 400);
+    assert!(body["results"][1]["errors"][0].as_str().unwrap().starts_with("name:"));
+    assert_eq!(body["results"][2]["status"], 424);
+    assert_eq!(user_name(id).await, "Batch Awal");
+}
+
+#[tokio::test]
+async fn batch_reports_status_per_operation() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let id = insert_user("Batch Ubah", "batch-ubah@corp.test.com").await;
+
+    let res = server.post("/users/batch").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({
+            "operations": [
+                { "op": "update", "id": id, "data": { "name": "Batch Sudah Diubah", "email": "batch-ubah@corp.test.com" } },
+                { "op": "delete", "id": id }
+            ]
+        }))
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+
+    let body: Value = res.json();
+    assert_eq!(body["committed"], true);
+    assert_eq!(body["results"][0]["status"], 200);
+    assert_eq!(body["results"][0]["user"]["name"], "Batch Sudah Diubah");
+    assert_eq!(body["results"][1]["status"], 204);
+}
+
+#[tokio::test]
+async fn empty_batch_is_bad_request() {
+    let server = server();
+    let admin = session_cookie(1).await;
+
+    let res = server.post("/users/batch").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({ "operations": [] }))
+        .await;
+    assert_eq!(res.status_code(), StatusCode::BAD_REQUEST);
+}
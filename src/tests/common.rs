@@ -0,0 +1,42 @@
+use axum::{Router, middleware::from_fn};
+use axum_test::TestServer;
+
+use crate::{
+    configs::db,
+    middlewares::api_middleware::{api_key_middleware, check_login},
+    utils::{client_info::ClientInfo, session::{create_session, new_session_id}, utils::create_jwt},
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+//sama dengan server.api_key di config.yaml untuk test
+pub fn api_key() -> &'static str {
+    "hgdshdfrhdrhdftjdftjfdtjdf"
+}
+
+//route dilindungi seperti di routes: X-API-KEY lalu session login
+pub fn protected(routes: Router) -> Router {
+    routes
+        .layer(from_fn(check_login))
+        .layer(from_fn(api_key_middleware))
+}
+
+pub fn protected_server(routes: Router) -> TestServer {
+    TestServer::new(protected(routes)).unwrap()
+}
+
+//session tercatat di db seperti hasil login, mengembalikan id session dan header Cookie
+pub async fn login_session(user_id: u64, client: &ClientInfo) -> (String, String) {
+    let pool = db::get_pool().await.unwrap();
+    let sid = new_session_id();
+    let (token, claims) = create_jwt(user_id, &sid).unwrap();
+    let expires_at = chrono::DateTime::from_timestamp(claims.exp, 0).unwrap();
+    create_session(&pool, user_id, &sid, &claims.jti, expires_at, client).await.unwrap();
+    (sid, format!("jwt={}", token))
+}
+
+pub async fn session_cookie(user_id: u64) -> String {
+    login_session(user_id, &ClientInfo::default()).await.1
+}
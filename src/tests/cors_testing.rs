@@ -0,0 +1,93 @@
+use axum::{Router, middleware::from_fn, routing::get};
+use axum_test::TestServer;
+use http::{Method, StatusCode};
+
+use crate::{
+    controllers::user_controller::get_all_user,
+    middlewares::{api_middleware::{api_key_middleware, check_login}, cors_middleware::{check_cors_config, cors_layer}},
+    models::config_model::CorsConfig,
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn cors_server(cfg: CorsConfig) -> TestServer {
+    let app = Router::new()
+        .route("/user", get(get_all_user))
+        .layer(from_fn(check_login))
+        .layer(from_fn(api_key_middleware))
+        .layer(cors_layer(&cfg));
+    TestServer::new(app).unwrap()
+}
+
+fn spa_config() -> CorsConfig {
+    CorsConfig {
+        allowed_origins: vec!["http://app.test".to_string()],
+        ..CorsConfig::default()
+    }
+}
+
+/// =======================
+/// Preflight Tests
+/// =======================
+
+#[tokio::test]
+async fn preflight_skips_auth_middleware() {
+    let server = cors_server(spa_config());
+    let res = server.method(Method::OPTIONS, "/user")
+        .add_header("Origin", "http://app.test")
+        .add_header("Access-Control-Request-Method", "GET")
+        .add_header("Access-Control-Request-Headers", "x-api-key")
+        .await;
+
+    assert_eq!(res.status_code(), StatusCode::OK);
+    assert_eq!(res.header("access-control-allow-origin"), "http://app.test");
+    assert_eq!(res.header("access-control-allow-credentials"), "true");
+    assert_eq!(res.header("access-control-max-age"), "600");
+}
+
+#[tokio::test]
+async fn preflight_unknown_origin_not_allowed() {
+    let server = cors_server(spa_config());
+    let res = server.method(Method::OPTIONS, "/user")
+        .add_header("Origin", "http://evil.test")
+        .add_header("Access-Control-Request-Method", "GET")
+        .await;
+
+    assert!(res.maybe_header("access-control-allow-origin").is_none());
+}
+
+#[test]
+fn wildcard_origin_with_credentials_is_rejected() {
+    let wildcard = CorsConfig { allowed_origins: vec!["*".to_string()], ..CorsConfig::default() };
+    assert!(check_cors_config(&wildcard).is_err());
+    assert!(check_cors_config(&CorsConfig { allow_credentials: false, ..wildcard }).is_ok());
+    assert!(check_cors_config(&spa_config()).is_ok());
+}
+
+#[tokio::test]
+async fn wildcard_origin_never_allows_credentials() {
+    let server = cors_server(CorsConfig {
+        allowed_origins: vec!["*".to_string()],
+        ..CorsConfig::default()
+    });
+    let res = server.method(Method::OPTIONS, "/user")
+        .add_header("Origin", "http://other.test")
+        .add_header("Access-Control-Request-Method", "GET")
+        .await;
+
+    assert_eq!(res.header("access-control-allow-origin"), "*");
+    assert!(res.maybe_header("access-control-allow-credentials").is_none());
+}
+
+#[tokio::test]
+async fn actual_request_still_requires_api_key() {
+    let server = cors_server(spa_config());
+    let res = server.get("/user")
+        .add_header("Origin", "http://app.test")
+        .await;
+
+    assert_eq!(res.status_code(), StatusCode::UNAUTHORIZED);
+    assert_eq!(res.header("access-control-allow-origin"), "http://app.test");
+}
@@ -0,0 +1,75 @@
+use axum_test::TestServer;
+use chrono::{TimeZone, Utc};
+use http::StatusCode;
+
+use crate::{
+    middlewares::deprecation_middleware::{deprecation_headers, successor_path},
+    models::config_model::DeprecationConfig,
+    routes::login_route::{routes_legacy_user, routes_users},
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn server() -> TestServer {
+    TestServer::new(routes_users().merge(routes_legacy_user())).unwrap()
+}
+
+/// =======================
+/// Successor Path Tests
+/// =======================
+
+#[test]
+fn legacy_paths_map_to_users_resource() {
+    assert_eq!(successor_path("/user", None), "/users");
+    assert_eq!(successor_path("/user/search", None), "/users");
+    assert_eq!(successor_path("/user/deleted", None), "/users/deleted");
+    assert_eq!(successor_path("/user/12", None), "/users/12");
+    assert_eq!(successor_path("/user/12/restore", None), "/users/12/restore");
+}
+
+#[test]
+fn legacy_query_id_becomes_path_id() {
+    assert_eq!(successor_path("/user/", Some("id=7")), "/users/7");
+    assert_eq!(successor_path("/user/", Some("x=1&id=42")), "/users/42");
+    assert_eq!(successor_path("/user/", Some("id=abc")), "/users");
+    assert_eq!(successor_path("/user/", None), "/users");
+}
+
+/// =======================
+/// Header Format Tests
+/// =======================
+
+#[test]
+fn deprecation_and_sunset_use_rfc_formats() {
+    let cfg = DeprecationConfig {
+        deprecated_at: Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap(),
+        sunset: Utc.with_ymd_and_hms(2027, 4, 19, 0, 0, 0).unwrap(),
+    };
+    let headers = deprecation_headers(&cfg, "/users/7");
+
+    assert_eq!(headers[0], ("deprecation", "@1792368000".to_string()));
+    assert_eq!(headers[1], ("sunset", "Mon, 19 Apr 2027 00:00:00 GMT".to_string()));
+    assert_eq!(headers[2], ("link", "</users/7>; rel=\"successor-version\"".to_string()));
+}
+
+/// =======================
+/// Route Tests
+/// =======================
+
+#[tokio::test]
+async fn legacy_route_response_carries_deprecation_headers() {
+    let res = server().get("/user/").add_query_param("id", "7").await;
+    assert_eq!(res.status_code(), StatusCode::UNAUTHORIZED);
+    assert!(res.headers().contains_key("deprecation"));
+    assert!(res.headers().contains_key("sunset"));
+    assert_eq!(res.headers().get("link").unwrap(), "</users/7>; rel=\"successor-version\"");
+}
+
+#[tokio::test]
+async fn users_route_is_not_deprecated() {
+    let res = server().get("/users/7").await;
+    assert_eq!(res.status_code(), StatusCode::UNAUTHORIZED);
+    assert!(!res.headers().contains_key("deprecation"));
+}
@@ -0,0 +1,218 @@
+use chrono::Utc;
+use jsonwebtoken::{decode_header, jwk::AlgorithmParameters};
+
+use crate::{
+    errors::app_error::AppError,
+    models::{config_model::{JwtConfig, JwtKeyConfig, TokenLifetimes}, user_model::{Claims, TokenType}},
+    utils::{jwt_keys::JwtKeys, utils::{build_claims, load_jwt_keys, reload_jwt_keys}},
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+const FIXTURES: &str = "src/tests/fixtures/jwt";
+
+fn key(kid: &str, algorithm: &str, file: &str, private: bool) -> JwtKeyConfig {
+    JwtKeyConfig {
+        kid: kid.to_string(),
+        algorithm: algorithm.to_string(),
+        private_key_path: private.then(|| format!("{}/{}.key", FIXTURES, file)),
+        public_key_path: Some(format!("{}/{}.pub", FIXTURES, file)),
+        secret: None,
+    }
+}
+
+fn claims(keys: &JwtKeys) -> Claims {
+    build_claims(&JwtConfig::default(), keys, 7, TokenType::Access, Some("sid-1"))
+}
+
+/// =======================
+/// Signing & Verification Tests
+/// =======================
+
+#[test]
+fn legacy_secret_still_works_without_keys() {
+    let keys = JwtKeys::from_config(&JwtConfig::default(), "legacy-secret").unwrap();
+    let token = keys.sign(&claims(&keys)).unwrap();
+
+    assert_eq!(decode_header(&token).unwrap().kid, None);
+    assert_eq!(keys.verify::<Claims>(&token).unwrap().sub, 7);
+    assert!(keys.jwks().keys.is_empty());
+}
+
+#[test]
+fn no_signing_key_is_config_error() {
+    let res = JwtKeys::from_config(&JwtConfig::default(), "");
+    assert!(matches!(res, Err(AppError::ConfigError(_))));
+}
+
+#[test]
+fn rs256_token_carries_kid() {
+    let cfg = JwtConfig { keys: vec![key("2025", "RS256", "rs256_2025", true)], ..JwtConfig::default() };
+    let keys = JwtKeys::from_config(&cfg, "").unwrap();
+    let token = keys.sign(&claims(&keys)).unwrap();
+
+    let header = decode_header(&token).unwrap();
+    assert_eq!(header.kid.as_deref(), Some("2025"));
+    assert_eq!(keys.verify::<Claims>(&token).unwrap().sub, 7);
+}
+
+#[test]
+fn eddsa_roundtrip() {
+    let cfg = JwtConfig { keys: vec![key("ed", "EdDSA", "ed25519", true)], ..JwtConfig::default() };
+    let keys = JwtKeys::from_config(&cfg, "").unwrap();
+    let token = keys.sign(&claims(&keys)).unwrap();
+
+    assert_eq!(keys.verify::<Claims>(&token).unwrap().sub, 7);
+}
+
+#[test]
+fn rotated_key_still_verifies_old_tokens() {
+    let old_cfg = JwtConfig { keys: vec![key("2025", "RS256", "rs256_2025", true)], ..JwtConfig::default() };
+    let old_token = {
+        let keys = JwtKeys::from_config(&old_cfg, "").unwrap();
+        keys.sign(&claims(&keys)).unwrap()
+    };
+
+    let rotated = JwtConfig {
+        active_kid: Some("2026".to_string()),
+        keys: vec![
+            key("2026", "RS256", "rs256_2026", true),
+            key("2025", "RS256", "rs256_2025", false),
+        ],
+        ..JwtConfig::default()
+    };
+    let keys = JwtKeys::from_config(&rotated, "").unwrap();
+    assert!(keys.verify::<Claims>(&old_token).is_ok());
+
+    let new_token = keys.sign(&claims(&keys)).unwrap();
+    assert_eq!(decode_header(&new_token).unwrap().kid.as_deref(), Some("2026"));
+
+    let retired = JwtConfig { keys: vec![key("2026", "RS256", "rs256_2026", true)], ..JwtConfig::default() };
+    let keys = JwtKeys::from_config(&retired, "").unwrap();
+    assert!(matches!(keys.verify::<Claims>(&old_token), Err(AppError::Unauthorized)));
+}
+
+#[test]
+fn active_kid_without_private_key_is_rejected() {
+    let cfg = JwtConfig {
+        active_kid: Some("2025".to_string()),
+        keys: vec![key("2025", "RS256", "rs256_2025", false)],
+        ..JwtConfig::default()
+    };
+    assert!(JwtKeys::from_config(&cfg, "").is_err());
+}
+
+#[test]
+fn issuer_and_audience_are_validated() {
+    let cfg = JwtConfig {
+        keys: vec![key("2025", "RS256", "rs256_2025", true)],
+        issuer: Some("https://auth.example.test".to_string()),
+        audience: Some("backend".to_string()),
+        ..JwtConfig::default()
+    };
+    let keys = JwtKeys::from_config(&cfg, "").unwrap();
+    assert!(keys.verify::<Claims>(&keys.sign(&claims(&keys)).unwrap()).is_ok());
+
+    let mut wrong = claims(&keys);
+    wrong.aud = Some("other-service".to_string());
+    assert!(keys.verify::<Claims>(&keys.sign(&wrong).unwrap()).is_err());
+
+    let mut missing = claims(&keys);
+    missing.iss = None;
+    assert!(keys.verify::<Claims>(&keys.sign(&missing).unwrap()).is_err());
+}
+
+#[test]
+fn hmac_token_cannot_impersonate_rsa_key() {
+    let cfg = JwtConfig { keys: vec![key("2025", "RS256", "rs256_2025", true)], ..JwtConfig::default() };
+    let keys = JwtKeys::from_config(&cfg, "").unwrap();
+
+    let forged = JwtKeys::from_config(&JwtConfig::default(), "guess").unwrap()
+        .sign(&claims(&keys)).unwrap();
+    assert!(keys.verify::<Claims>(&forged).is_err());
+}
+
+#[test]
+fn keys_are_cached_until_reload() {
+    let first = load_jwt_keys().unwrap();
+    let second = load_jwt_keys().unwrap();
+    assert!(std::sync::Arc::ptr_eq(&first, &second));
+
+    let reloaded = reload_jwt_keys().unwrap();
+    assert!(!std::sync::Arc::ptr_eq(&second, &reloaded));
+    assert!(std::sync::Arc::ptr_eq(&reloaded, &load_jwt_keys().unwrap()));
+}
+
+/// =======================
+/// JWKS Tests
+/// =======================
+
+#[test]
+fn jwks_publishes_only_public_keys() {
+    let cfg = JwtConfig {
+        keys: vec![
+            key("2026", "RS256", "rs256_2026", true),
+            key("ed", "EdDSA", "ed25519", false),
+            JwtKeyConfig {
+                kid: "internal".to_string(),
+                algorithm: "HS256".to_string(),
+                private_key_path: None,
+                public_key_path: None,
+                secret: Some("shared".to_string()),
+            },
+        ],
+        ..JwtConfig::default()
+    };
+    let jwks = JwtKeys::from_config(&cfg, "legacy").unwrap().jwks();
+
+    assert_eq!(jwks.keys.len(), 2);
+    assert!(matches!(jwks.find("2026").unwrap().algorithm, AlgorithmParameters::RSA(_)));
+    assert!(matches!(jwks.find("ed").unwrap().algorithm, AlgorithmParameters::OctetKeyPair(_)));
+    assert!(jwks.find("internal").is_none());
+}
+
+/// =======================
+/// Registered Claims Tests
+/// =======================
+
+#[test]
+fn claims_are_fully_populated() {
+    let cfg = JwtConfig {
+        lifetimes: TokenLifetimes { access: 900, ..TokenLifetimes::default() },
+        issuer: Some("https://auth.example.test".to_string()),
+        ..JwtConfig::default()
+    };
+    let keys = JwtKeys::from_config(&cfg, "secret").unwrap();
+    let claims = build_claims(&cfg, &keys, 7, TokenType::Access, Some("sid-1"));
+
+    assert_eq!(claims.exp - claims.iat, 900);
+    assert_eq!(claims.nbf, claims.iat);
+    assert_eq!(claims.jti.len(), 36);
+    assert_eq!(claims.sid.as_deref(), Some("sid-1"));
+    assert_eq!(claims.iss.as_deref(), Some("https://auth.example.test"));
+
+    let other = build_claims(&cfg, &keys, 7, TokenType::Access, Some("sid-1"));
+    assert_ne!(claims.jti, other.jti);
+}
+
+#[test]
+fn token_not_yet_valid_is_rejected() {
+    let keys = JwtKeys::from_config(&JwtConfig::default(), "secret").unwrap();
+    let mut early = claims(&keys);
+    early.nbf = Utc::now().timestamp() + 600;
+
+    assert!(keys.verify::<Claims>(&keys.sign(&early).unwrap()).is_err());
+}
+
+#[test]
+fn expired_token_is_rejected_after_leeway() {
+    let keys = JwtKeys::from_config(&JwtConfig::default(), "secret").unwrap();
+    let mut expired = claims(&keys);
+    expired.exp = Utc::now().timestamp() - 30;
+    assert!(keys.verify::<Claims>(&keys.sign(&expired).unwrap()).is_ok());
+
+    expired.exp = Utc::now().timestamp() - 120;
+    assert!(keys.verify::<Claims>(&keys.sign(&expired).unwrap()).is_err());
+}
@@ -0,0 +1,94 @@
+use argon2::password_hash::{PasswordHasher, SaltString, rand_core::OsRng};
+use pbkdf2::Pbkdf2;
+use scrypt::Scrypt;
+
+use crate::{
+    commands::import_users::parse_line,
+    models::config_model::PasswordHashConfig,
+    utils::{legacy_hash::{LegacyFormat, detect, is_supported_hash}, password_hash::{needs_rehash, verify_blocking}},
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+const PASSWORD: &str = "rahasia123";
+//dibuat dengan hashlib.pbkdf2_hmac('sha256', b'rahasia123', b'Wq3d9xT2', 1000)
+const DJANGO: &str = "pbkdf2_sha256$1000$Wq3d9xT2$BR+LEr8m/GpJPP8zFlS8I6LU3CFt/O+fHM1mIpPIB2U=";
+const WERKZEUG: &str = "pbkdf2:sha256:1000$Wq3d9xT2$051f8b12bf26fc6a493cff331654bc23a2d4dc216dfcef9f1ccd662293c80765";
+
+fn bcrypt_hash() -> String {
+    bcrypt::hash(PASSWORD, 4).unwrap()
+}
+
+fn pbkdf2_phc() -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    let params = pbkdf2::Params { rounds: 1000, output_length: 32 };
+    Pbkdf2.hash_password_customized(PASSWORD.as_bytes(), Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()), None, params, &salt).unwrap().to_string()
+}
+
+fn scrypt_phc() -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    let params = scrypt::Params::new(4, 8, 1, 32).unwrap();
+    Scrypt.hash_password_customized(PASSWORD.as_bytes(), None, None, params, &salt).unwrap().to_string()
+}
+
+/// =======================
+/// Verification Tests
+/// =======================
+
+#[test]
+fn legacy_formats_are_detected() {
+    assert_eq!(detect(&bcrypt_hash()), Some(LegacyFormat::Bcrypt));
+    assert_eq!(detect(&pbkdf2_phc()), Some(LegacyFormat::Pbkdf2Phc));
+    assert_eq!(detect(DJANGO), Some(LegacyFormat::Pbkdf2Django));
+    assert_eq!(detect(WERKZEUG), Some(LegacyFormat::Pbkdf2Werkzeug));
+    assert_eq!(detect(&scrypt_phc()), Some(LegacyFormat::Scrypt));
+    assert_eq!(detect("$argon2id$v=19$m=19456,t=2,p=1$c2FsdA$aGFzaA"), None);
+}
+
+#[test]
+fn legacy_hashes_verify_and_need_rehash() {
+    let cfg = PasswordHashConfig::default();
+    for hash in [bcrypt_hash(), pbkdf2_phc(), DJANGO.to_string(), WERKZEUG.to_string(), scrypt_phc()] {
+        assert!(verify_blocking(&cfg, &hash, PASSWORD).unwrap(), "{}", hash);
+        assert!(!verify_blocking(&cfg, &hash, "salah-password").unwrap(), "{}", hash);
+        assert!(needs_rehash(&cfg, &hash), "{}", hash);
+    }
+}
+
+#[test]
+fn pepper_is_not_applied_to_legacy_hashes() {
+    let cfg = PasswordHashConfig { pepper: Some("pepper".to_string()), ..PasswordHashConfig::default() };
+    assert!(verify_blocking(&cfg, DJANGO, PASSWORD).unwrap());
+}
+
+#[test]
+fn malformed_legacy_hash_is_rejected() {
+    let cfg = PasswordHashConfig::default();
+    assert!(verify_blocking(&cfg, "pbkdf2_sha256$abc$salt$hash", PASSWORD).is_err());
+    assert!(verify_blocking(&cfg, "$2b$04$short", PASSWORD).is_err());
+    assert!(!is_supported_hash("$2b$04$short"));
+    assert!(!is_supported_hash("md5$abc"));
+    assert!(!is_supported_hash("plaintext"));
+}
+
+/// =======================
+/// Import Tests
+/// =======================
+
+#[test]
+fn import_line_is_normalized_and_validated() {
+    let line = format!(r#"{{"name": " Budi ", "email": "Budi@Example.COM", "password_hash": "{}"}}"#, bcrypt_hash());
+    let user = parse_line(&line).unwrap();
+    assert_eq!(user.name, "Budi");
+    assert_eq!(user.email, "budi@example.com");
+    assert!(user.password_hash.starts_with("$2b$"));
+}
+
+#[test]
+fn import_line_with_unknown_hash_or_bad_email_is_rejected() {
+    assert!(parse_line(r#"{"name": "Budi", "email": "budi@example.com", "password_hash": "5f4dcc3b5aa765d61d8327deb882cf99"}"#).is_err());
+    assert!(parse_line(&format!(r#"{{"name": "Budi", "email": "budi.example.com", "password_hash": "{}"}}"#, DJANGO)).is_err());
+    assert!(parse_line(r#"{"name": "Budi"}"#).is_err());
+}
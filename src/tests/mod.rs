@@ -1,2 +1,52 @@
 #[cfg(test)]
-pub mod user_testing;
\ No newline at end of file
+pub mod common;
+#[cfg(test)]
+pub mod user_testing;
+#[cfg(test)]
+pub mod cors_testing;
+#[cfg(test)]
+pub mod jwt_testing;
+#[cfg(test)]
+pub mod session_testing;
+#[cfg(test)]
+pub mod totp_testing;
+#[cfg(test)]
+pub mod webauthn_testing;
+#[cfg(test)]
+pub mod oidc_testing;
+#[cfg(test)]
+pub mod oauth_testing;
+#[cfg(test)]
+pub mod password_policy_testing;
+#[cfg(test)]
+pub mod password_hash_testing;
+#[cfg(test)]
+pub mod legacy_hash_testing;
+#[cfg(test)]
+pub mod soft_delete_testing;
+#[cfg(test)]
+pub mod user_patch_testing;
+#[cfg(test)]
+pub mod deprecation_testing;
+#[cfg(test)]
+pub mod version_testing;
+#[cfg(test)]
+pub mod openapi_testing;
+#[cfg(test)]
+pub mod search_testing;
+#[cfg(test)]
+pub mod search_index_testing;
+#[cfg(test)]
+pub mod user_transfer_testing;
+#[cfg(test)]
+pub mod batch_testing;
+#[cfg(test)]
+pub mod avatar_testing;
+#[cfg(test)]
+pub mod blob_store_testing;
+#[cfg(test)]
+pub mod attribute_testing;
+#[cfg(test)]
+pub mod audit_testing;
+#[cfg(test)]
+pub mod outbox_testing;
@@ -0,0 +1,173 @@
+use axum::response::IntoResponse;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use http::{HeaderMap, HeaderValue, StatusCode, header};
+
+use crate::{
+    errors::{app_error::AppError, oauth_error::OAuthError},
+    models::{config_model::{JwtConfig, OAuthConfig}, oauth_model::OAuthClaims, user_model::{Claims, TokenType}},
+    utils::{
+        jwt_keys::JwtKeys,
+        oauth::{build_oauth_claims, client_credentials, decode_access_token, grant_scope, hash_code, is_first_party, redirect_with, validate_redirect_uri},
+        utils::build_claims,
+    },
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn scopes(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+fn basic(id: &str, secret: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let value = format!("Basic {}", STANDARD.encode(format!("{}:{}", id, secret)));
+    headers.insert(header::AUTHORIZATION, HeaderValue::from_str(&value).unwrap());
+    headers
+}
+
+fn keys() -> JwtKeys {
+    JwtKeys::from_config(&JwtConfig::default(), "oauth-test-secret").unwrap()
+}
+
+/// =======================
+/// Scope Tests
+/// =======================
+
+#[test]
+fn empty_scope_grants_all_client_scopes() {
+    let granted = grant_scope(None, &scopes(&["api", "users:read"]), &scopes(&["api", "users:read"])).unwrap();
+    assert_eq!(granted, "api users:read");
+}
+
+#[test]
+fn requested_scope_must_be_subset_of_client_scopes() {
+    let client = scopes(&["api", "users:read"]);
+    let server = scopes(&["api", "users:read", "users:write"]);
+
+    assert_eq!(grant_scope(Some("users:read"), &client, &server).unwrap(), "users:read");
+    assert!(matches!(grant_scope(Some("users:write"), &client, &server), Err(OAuthError::InvalidScope)));
+}
+
+#[test]
+fn scope_removed_from_config_is_no_longer_granted() {
+    let client = scopes(&["api", "legacy"]);
+    let server = scopes(&["api"]);
+
+    assert_eq!(grant_scope(None, &client, &server).unwrap(), "api");
+    assert!(matches!(grant_scope(Some("legacy"), &client, &server), Err(OAuthError::InvalidScope)));
+    assert!(matches!(grant_scope(None, &scopes(&["legacy"]), &server), Err(OAuthError::InvalidScope)));
+}
+
+/// =======================
+/// Client Authentication Tests
+/// =======================
+
+#[test]
+fn client_credentials_from_basic_header_or_form() {
+    let (id, secret) = client_credentials(&basic("client-1", "s3cret"), None, None).unwrap();
+    assert_eq!((id.as_str(), secret.as_deref()), ("client-1", Some("s3cret")));
+
+    let (id, secret) = client_credentials(&HeaderMap::new(), Some("public-app"), None).unwrap();
+    assert_eq!((id.as_str(), secret), ("public-app", None));
+}
+
+#[test]
+fn client_credentials_in_header_and_form_is_rejected() {
+    let res = client_credentials(&basic("client-1", "s3cret"), Some("client-1"), None);
+    assert!(matches!(res, Err(OAuthError::InvalidRequest)));
+
+    let res = client_credentials(&HeaderMap::new(), None, None);
+    assert!(matches!(res, Err(OAuthError::InvalidClient)));
+}
+
+/// =======================
+/// Redirect Tests
+/// =======================
+
+#[test]
+fn redirect_uri_must_be_absolute_without_fragment() {
+    assert!(validate_redirect_uri("https://tool.internal/callback"));
+    assert!(validate_redirect_uri("http://localhost:8080/cb"));
+    assert!(!validate_redirect_uri("/callback"));
+    assert!(!validate_redirect_uri("https://tool.internal/cb#token"));
+    assert!(!validate_redirect_uri("javascript:alert(1)"));
+}
+
+#[test]
+fn redirect_keeps_existing_query() {
+    let url = redirect_with("https://tool.internal/cb?tenant=a", &[("code", "abc"), ("state", "x y")]).unwrap();
+    assert_eq!(url, "https://tool.internal/cb?tenant=a&code=abc&state=x+y");
+}
+
+#[test]
+fn authorization_code_is_stored_hashed() {
+    let hash = hash_code("the-code");
+    assert_eq!(hash.len(), 64);
+    assert_ne!(hash, "the-code");
+    assert_eq!(hash, hash_code("the-code"));
+}
+
+#[test]
+fn only_first_party_clients_get_codes() {
+    let cfg = OAuthConfig { first_party_clients: vec!["admin-panel".to_string()], ..Default::default() };
+    assert!(is_first_party(&cfg, "admin-panel"));
+    assert!(!is_first_party(&cfg, "registered-by-someone"));
+    assert!(!is_first_party(&OAuthConfig::default(), "admin-panel"));
+}
+
+/// =======================
+/// Access Token Tests
+/// =======================
+
+#[test]
+fn oauth_token_signed_with_jwt_keys() {
+    let keys = keys();
+    let claims = build_oauth_claims(&JwtConfig::default(), &keys, "client-1", Some(7), "api");
+    let token = keys.sign(&claims).unwrap();
+
+    let decoded = decode_access_token(&keys, &token).unwrap();
+    assert_eq!(decoded.sub, "7");
+    assert_eq!(decoded.client_id, "client-1");
+    assert_eq!(decoded.exp - decoded.iat, 60 * 60);
+    assert!(decoded.has_scope("api"));
+    assert!(!decoded.has_scope("ap"));
+}
+
+#[test]
+fn client_credentials_token_subject_is_client() {
+    let keys = keys();
+    let claims = build_oauth_claims(&JwtConfig::default(), &keys, "client-1", None, "api");
+    assert_eq!(claims.sub, "client-1");
+    assert_eq!(claims.user_id, None);
+}
+
+#[test]
+fn oauth_token_and_cookie_token_are_not_interchangeable() {
+    let keys = keys();
+    let oauth = keys.sign(&build_oauth_claims(&JwtConfig::default(), &keys, "client-1", Some(7), "api")).unwrap();
+    assert!(keys.verify::<Claims>(&oauth).is_err());
+
+    let cookie = keys.sign(&build_claims(&JwtConfig::default(), &keys, 7, TokenType::Access, Some("sid"))).unwrap();
+    assert!(matches!(decode_access_token(&keys, &cookie), Err(AppError::Unauthorized)));
+    assert!(keys.verify::<OAuthClaims>(&cookie).is_err());
+}
+
+/// =======================
+/// Error Response Tests
+/// =======================
+
+#[test]
+fn invalid_client_is_401_with_www_authenticate() {
+    let res = OAuthError::InvalidClient.into_response();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert!(res.headers().contains_key(header::WWW_AUTHENTICATE));
+}
+
+#[test]
+fn grant_errors_are_400() {
+    for error in [OAuthError::InvalidGrant, OAuthError::InvalidScope, OAuthError::UnsupportedGrantType] {
+        assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+}
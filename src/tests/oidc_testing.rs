@@ -0,0 +1,210 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+use axum::{Form, Json, Router, extract::State, routing::{get, post}};
+use chrono::Utc;
+use http::StatusCode;
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+use url::Url;
+
+use axum_extra::extract::{CookieJar, cookie::Cookie};
+
+use crate::{
+    configs::db,
+    controllers::oidc_controller::{STATE_COOKIE, link_account, state_matches},
+    errors::app_error::AppError,
+    models::config_model::{JwtConfig, JwtKeyConfig, OidcProviderConfig},
+    utils::{
+        client_info::ClientInfo,
+        jwt_keys::JwtKeys,
+        oidc::{IdTokenClaims, authorization_url, discover, exchange_code, fetch_jwks, http_client, pkce_challenge, random_token, verify_id_token},
+    },
+};
+
+// =======================
+// Mock OIDC Provider
+// =======================
+
+#[derive(Default)]
+struct MockState {
+    issuer: String,
+    code_challenge: Option<String>,
+    nonce: String,
+}
+
+type Shared = Arc<Mutex<MockState>>;
+
+fn provider_keys() -> JwtKeys {
+    let cfg = JwtConfig {
+        keys: vec![JwtKeyConfig {
+            kid: "mock-1".to_string(),
+            algorithm: "RS256".to_string(),
+            private_key_path: Some("src/tests/fixtures/jwt/rs256_2025.key".to_string()),
+            public_key_path: Some("src/tests/fixtures/jwt/rs256_2025.pub".to_string()),
+            secret: None,
+        }],
+        ..JwtConfig::default()
+    };
+    JwtKeys::from_config(&cfg, "").unwrap()
+}
+
+async fn discovery(State(state): State<Shared>) -> Json<Value> {
+    let issuer = state.lock().unwrap().issuer.clone();
+    Json(json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{}/authorize", issuer),
+        "token_endpoint": format!("{}/token", issuer),
+        "jwks_uri": format!("{}/jwks", issuer),
+    }))
+}
+
+async fn jwks() -> Json<Value> {
+    Json(serde_json::to_value(provider_keys().jwks()).unwrap())
+}
+
+async fn token(State(state): State<Shared>, Form(form): Form<HashMap<String, String>>) -> Result<Json<Value>, StatusCode> {
+    let state = state.lock().unwrap();
+    let verifier = form.get("code_verifier").ok_or(StatusCode::BAD_REQUEST)?;
+    if form.get("code").map(String::as_str) != Some("good-code") || state.code_challenge.as_deref() != Some(pkce_challenge(verifier).as_str()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let now = Utc::now().timestamp();
+    let id_token = provider_keys().sign(&json!({
+        "iss": state.issuer,
+        "aud": "backend-client",
+        "sub": "provider-user-42",
+        "email": "budi@corp.test",
+        "email_verified": true,
+        "name": "Budi",
+        "nonce": state.nonce,
+        "iat": now,
+        "exp": now + 300,
+    })).unwrap();
+    Ok(Json(json!({"access_token": "opaque", "token_type": "Bearer", "id_token": id_token})))
+}
+
+async fn mock_provider() -> (String, Shared) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let issuer = format!("http://{}", listener.local_addr().unwrap());
+    let state: Shared = Arc::new(Mutex::new(MockState { issuer: issuer.clone(), ..MockState::default() }));
+
+    let app = Router::new()
+        .route("/.well-known/openid-configuration", get(discovery))
+        .route("/jwks", get(jwks))
+        .route("/token", post(token))
+        .with_state(state.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    (issuer, state)
+}
+
+fn provider(issuer: &str) -> OidcProviderConfig {
+    OidcProviderConfig {
+        name: "mock".to_string(),
+        issuer: issuer.to_string(),
+        client_id: "backend-client".to_string(),
+        client_secret: Some("client-secret".to_string()),
+        redirect_uri: "http://localhost:3000/login/oidc/mock/callback".to_string(),
+        scopes: vec!["openid".to_string(), "email".to_string()],
+    }
+}
+
+/// =======================
+/// Authorization Code Flow Tests
+/// =======================
+
+#[tokio::test]
+async fn code_flow_with_pkce_and_nonce() {
+    let (issuer, state) = mock_provider().await;
+    let provider = provider(&issuer);
+    let http = http_client().unwrap();
+    let metadata = discover(&http, &issuer).await.unwrap();
+
+    let (oauth_state, nonce, verifier) = (random_token(), random_token(), random_token());
+    let url = Url::parse(&authorization_url(&metadata, &provider, &oauth_state, &nonce, &verifier).unwrap()).unwrap();
+    let params: HashMap<_, _> = url.query_pairs().into_owned().collect();
+    assert_eq!(params["state"], oauth_state);
+    assert_eq!(params["code_challenge_method"], "S256");
+    assert_eq!(params["scope"], "openid email");
+
+    {
+        let mut state = state.lock().unwrap();
+        state.code_challenge = Some(params["code_challenge"].clone());
+        state.nonce = nonce.clone();
+    }
+
+    let tokens = exchange_code(&http, &metadata, &provider, "good-code", &verifier).await.unwrap();
+    let jwks = fetch_jwks(&http, &metadata).await.unwrap();
+    let claims = verify_id_token(&jwks, &tokens.id_token, &issuer, &provider.client_id, &nonce).unwrap();
+
+    assert_eq!(claims.sub, "provider-user-42");
+    assert_eq!(claims.email.as_deref(), Some("budi@corp.test"));
+    assert!(claims.email_verified);
+}
+
+#[tokio::test]
+async fn wrong_code_verifier_is_rejected() {
+    let (issuer, state) = mock_provider().await;
+    let provider = provider(&issuer);
+    let http = http_client().unwrap();
+    let metadata = discover(&http, &issuer).await.unwrap();
+    state.lock().unwrap().code_challenge = Some(pkce_challenge("real-verifier"));
+
+    let res = exchange_code(&http, &metadata, &provider, "good-code", "stolen-code-without-verifier").await;
+    assert!(matches!(res, Err(AppError::Unauthorized)));
+}
+
+#[tokio::test]
+async fn id_token_nonce_and_audience_are_checked() {
+    let (issuer, state) = mock_provider().await;
+    let provider = provider(&issuer);
+    let http = http_client().unwrap();
+    let metadata = discover(&http, &issuer).await.unwrap();
+    {
+        let mut state = state.lock().unwrap();
+        state.code_challenge = Some(pkce_challenge("verifier"));
+        state.nonce = "nonce-1".to_string();
+    }
+
+    let tokens = exchange_code(&http, &metadata, &provider, "good-code", "verifier").await.unwrap();
+    let jwks = fetch_jwks(&http, &metadata).await.unwrap();
+
+    assert!(verify_id_token(&jwks, &tokens.id_token, &issuer, "backend-client", "nonce-2").is_err());
+    assert!(verify_id_token(&jwks, &tokens.id_token, &issuer, "other-client", "nonce-1").is_err());
+    assert!(verify_id_token(&jwks, &tokens.id_token, "https://other-issuer.test", "backend-client", "nonce-1").is_err());
+}
+
+#[tokio::test]
+async fn discovery_with_mismatched_issuer_is_rejected() {
+    let (issuer, state) = mock_provider().await;
+    state.lock().unwrap().issuer = "https://impostor.test".to_string();
+
+    let res = discover(&http_client().unwrap(), &issuer).await;
+    assert!(matches!(res, Err(AppError::Unauthorized)));
+}
+
+/// =======================
+/// Callback Tests
+/// =======================
+
+#[test]
+fn callback_state_must_match_browser_cookie() {
+    let jar = CookieJar::new().add(Cookie::new(STATE_COOKIE, "state-from-this-browser"));
+    assert!(state_matches(&jar, "state-from-this-browser"));
+    assert!(!state_matches(&jar, "state-from-attacker"));
+    assert!(!state_matches(&CookieJar::new(), "state-from-this-browser"));
+}
+
+#[tokio::test]
+async fn signup_records_audit_and_user_created_event() {
+    let pool = db::get_pool().await.unwrap();
+    let email = "oidc-signup@corp.test.com";
+    sqlx::query("DELETE FROM users WHERE email = ?").bind(email).execute(&pool).await.unwrap();
+    let claims = IdTokenClaims { sub: random_token(), email: Some(email.to_string()), email_verified: true, name: Some("Oidc User".to_string()), nonce: None };
+
+    let user_id = link_account(&pool, "mock", &claims, true, &ClientInfo::default()).await.unwrap();
+
+    let (audits,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM audit_log WHERE action = 'user.create' AND target_id = ?").bind(user_id).fetch_one(&pool).await.unwrap();
+    let (events,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM outbox_events WHERE event_type = 'UserCreated' AND user_id = ?").bind(user_id).fetch_one(&pool).await.unwrap();
+    assert_eq!((audits, events), (1, 1));
+}
@@ -0,0 +1,203 @@
+use std::collections::BTreeSet;
+use axum::body::{Body, to_bytes};
+use http::{Method, Request, StatusCode};
+use serde_json::Value;
+use tower::ServiceExt;
+
+use crate::{docs::openapi::api_doc, routes::api_route};
+
+// =======================
+// Helper Functions
+// =======================
+
+//semua route yang dilayani api_route: path tanpa prefix (v1 dan alias /user) plus route /users di /api/v2.
+//route baru wajib ditambahkan di sini, test di bawah memastikan daftar ini cocok dengan router dan OpenAPI
+const ROUTES: &[(&str, &str)] = &[
+    ("get", "/users"),
+    ("post", "/users"),
+    ("get", "/users/deleted"),
+    ("get", "/users/search"),
+    ("post", "/users/import"),
+    ("get", "/users/export"),
+    ("post", "/users/batch"),
+    ("get", "/users/attributes"),
+    ("put", "/users/attributes/{name}"),
+    ("delete", "/users/attributes/{name}"),
+    ("get", "/users/{id}"),
+    ("put", "/users/{id}"),
+    ("patch", "/users/{id}"),
+    ("delete", "/users/{id}"),
+    ("post", "/users/{id}/restore"),
+    ("get", "/user"),
+    ("post", "/user"),
+    ("post", "/user/search"),
+    ("delete", "/user/"),
+    ("get", "/user/"),
+    ("get", "/user/deleted"),
+    ("put", "/user/{id}"),
+    ("patch", "/user/{id}"),
+    ("post", "/user/{id}/restore"),
+    ("get", "/audit"),
+    ("get", "/audit/verify"),
+    ("post", "/logout"),
+    ("put", "/me/password"),
+    ("put", "/me/avatar"),
+    ("get", "/me/sessions"),
+    ("delete", "/me/sessions/{id}"),
+    ("post", "/me/totp/setup"),
+    ("post", "/me/totp/confirm"),
+    ("delete", "/me/totp"),
+    ("get", "/me/passkeys"),
+    ("post", "/me/passkeys"),
+    ("post", "/me/passkeys/options"),
+    ("delete", "/me/passkeys/{id}"),
+    ("post", "/login"),
+    ("post", "/login/totp"),
+    ("post", "/login/passkey/options"),
+    ("post", "/login/passkey"),
+    ("get", "/.well-known/jwks.json"),
+    ("get", "/avatars/{file}"),
+    ("get", "/login/oidc"),
+    ("get", "/login/oidc/{provider}"),
+    ("get", "/login/oidc/{provider}/callback"),
+    ("get", "/oauth/clients"),
+    ("post", "/oauth/clients"),
+    ("delete", "/oauth/clients/{id}"),
+    ("get", "/oauth/authorize"),
+    ("post", "/oauth/token"),
+    ("post", "/oauth/introspect"),
+    ("post", "/oauth/revoke"),
+    ("get", "/api/v2/users"),
+    ("post", "/api/v2/users"),
+    ("get", "/api/v2/users/deleted"),
+    ("get", "/api/v2/users/{id}"),
+    ("put", "/api/v2/users/{id}"),
+    ("patch", "/api/v2/users/{id}"),
+    ("delete", "/api/v2/users/{id}"),
+    ("post", "/api/v2/users/{id}/restore"),
+];
+
+fn registered_routes() -> BTreeSet<(String, String)> {
+    ROUTES.iter().map(|(method, path)| (method.to_string(), path.to_string())).collect()
+}
+
+//parameter path diganti nilai contoh agar request bisa dikirim ke router
+fn concrete_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| if segment.starts_with('{') { "1" } else { segment })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+//true jika router punya handler untuk method dan path ini (bukan fallback 404/405)
+async fn is_routed(method: &str, path: &str) -> bool {
+    let request = Request::builder()
+        .method(Method::from_bytes(method.to_uppercase().as_bytes()).unwrap())
+        .uri(concrete_path(path))
+        .body(Body::empty())
+        .unwrap();
+    let res = api_route().oneshot(request).await.unwrap();
+    let status = res.status();
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let fallback = status == StatusCode::NOT_FOUND && body.starts_with(b"Page ");
+    !fallback && status != StatusCode::METHOD_NOT_ALLOWED
+}
+
+fn spec() -> Value {
+    serde_json::to_value(api_doc()).unwrap()
+}
+
+fn documented_routes() -> BTreeSet<(String, String)> {
+    spec()["paths"].as_object().unwrap().iter()
+        .flat_map(|(path, item)| {
+            item.as_object().unwrap().keys()
+                .filter(|method| ["get", "post", "put", "patch", "delete"].contains(&method.as_str()))
+                .map(move |method| (method.clone(), path.clone()))
+        })
+        .collect()
+}
+
+/// =======================
+/// Drift Tests
+/// =======================
+
+#[tokio::test]
+async fn route_list_matches_the_router() {
+    let mut unrouted = Vec::new();
+    for (method, path) in ROUTES {
+        if !is_routed(method, path).await {
+            unrouted.push((method, path));
+        }
+    }
+    assert!(unrouted.is_empty(), "route di ROUTES tidak dilayani router: {:?}", unrouted);
+    assert!(!is_routed("get", "/tidak-ada").await);
+    assert!(!is_routed("delete", "/oauth/token").await);
+}
+
+#[test]
+fn every_route_is_documented_and_every_operation_is_routed() {
+    let registered = registered_routes();
+    let documented = documented_routes();
+
+    let undocumented: Vec<_> = registered.difference(&documented).collect();
+    let unrouted: Vec<_> = documented.difference(&registered).collect();
+    assert!(undocumented.is_empty(), "route belum ada di OpenAPI: {:?}", undocumented);
+    assert!(unrouted.is_empty(), "OpenAPI mendokumentasikan route yang tidak ada: {:?}", unrouted);
+}
+
+#[test]
+fn legacy_routes_are_deprecated() {
+    let spec = spec();
+    assert_eq!(spec["paths"]["/user/{id}"]["patch"]["deprecated"], true);
+    assert_eq!(spec["paths"]["/user/search"]["post"]["deprecated"], true);
+    assert!(spec["paths"]["/users/{id}"]["patch"].get("deprecated").is_none());
+}
+
+#[test]
+fn v2_routes_use_string_ids() {
+    let spec = spec();
+    let get = &spec["paths"]["/api/v2/users/{id}"]["get"];
+    assert_eq!(get["parameters"][0]["schema"]["type"], "string");
+    assert_eq!(get["responses"]["200"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/UserV2");
+    assert_eq!(spec["paths"]["/api/v2/users"]["post"]["operationId"], "insert_user_v2");
+}
+
+/// =======================
+/// Schema Tests
+/// =======================
+
+#[test]
+fn validator_constraints_are_in_schema() {
+    let spec = spec();
+    let insert = &spec["components"]["schemas"]["UserInsert"]["properties"];
+    assert_eq!(insert["name"]["minLength"], 3);
+    assert_eq!(insert["email"]["format"], "email");
+
+    let totp = &spec["components"]["schemas"]["TotpCode"]["properties"]["code"];
+    assert_eq!((totp["minLength"].as_u64(), totp["maxLength"].as_u64()), (Some(6), Some(8)));
+}
+
+#[test]
+fn app_error_responses_are_documented() {
+    let spec = spec();
+    let responses = &spec["paths"]["/users/{id}"]["put"]["responses"];
+    for status in ["404", "409", "412"] {
+        assert!(responses.get(status).is_some(), "status {} tidak ada", status);
+    }
+}
+
+/// =======================
+/// Serving Tests
+/// =======================
+
+#[tokio::test]
+async fn openapi_json_and_swagger_ui_are_served() {
+    let res = api_route().oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert!(body["openapi"].as_str().unwrap().starts_with("3.1"));
+
+    let res = api_route().oneshot(Request::get("/docs/").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
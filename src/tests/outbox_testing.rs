@@ -0,0 +1,277 @@
+use std::{sync::{Arc, Mutex}, time::Duration};
+use axum::{Router, body::{Body, Bytes}, extract::{Query, State}, http::{HeaderMap, HeaderValue, StatusCode, header}, routing::post};
+use chrono::{TimeZone, Utc};
+use serde_json::{Value, json};
+
+use crate::{
+    configs::db,
+    controllers::{user_controller::create_user, user_transfer_controller::import_users},
+    errors::app_error::AppError,
+    jobs::outbox_dispatcher::dispatch_once,
+    models::{
+        audit_model::ActorKind,
+        config_model::{EventSinkConfig, OutboxConfig},
+        outbox_model::{DomainEventKind, EventEnvelope},
+        transfer_model::ImportParams,
+        user_model::UserInsert,
+    },
+    utils::{
+        audit::AuditContext,
+        client_info::ClientInfo,
+        outbox::{EventSink, WebhookSink, enqueue, retry_delay, webhook_signature},
+    },
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+//sink untuk test: menyimpan event yang diterima, atau selalu gagal
+#[derive(Default)]
+struct RecordingSink {
+    events: Mutex<Vec<EventEnvelope>>,
+    fail: bool,
+}
+
+impl RecordingSink {
+    fn failing() -> Self {
+        Self { fail: true, ..Self::default() }
+    }
+
+    fn ids(&self) -> Vec<String> {
+        self.events.lock().unwrap().iter().map(|e| e.id.clone()).collect()
+    }
+}
+
+impl EventSink for RecordingSink {
+    fn name(&self) -> &str {
+        "recording"
+    }
+
+    async fn publish(&self, event: &EventEnvelope) -> Result<(), AppError> {
+        if self.fail {
+            return Err(AppError::BadRequest);
+        }
+        self.events.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+}
+
+fn envelope() -> EventEnvelope {
+    EventEnvelope {
+        id: "0b6f5c1e-8f4e-4f7a-9d0a-2f1c2b3a4d5e".into(),
+        event_type: DomainEventKind::UserCreated,
+        user_id: 7,
+        occurred_at: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+        data: json!({"user": {"id": 7, "name": "Budi"}}),
+    }
+}
+
+type Received = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+async fn receive(State(received): State<Received>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    received.lock().unwrap().push((headers, body));
+    StatusCode::ACCEPTED
+}
+
+//penerima webhook di 127.0.0.1, /down selalu menjawab 503
+async fn fake_webhook() -> (String, Received) {
+    let received = Received::default();
+    let app = Router::new()
+        .route("/hooks", post(receive))
+        .route("/down", post(|| async { StatusCode::SERVICE_UNAVAILABLE }))
+        .with_state(received.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}", addr), received)
+}
+
+fn ctx() -> AuditContext {
+    AuditContext { actor_type: ActorKind::ApiKey, actor_id: None, client: ClientInfo::default() }
+}
+
+async fn event_state(event_id: &str) -> (u32, Option<String>, bool, bool) {
+    let pool = db::get_pool().await.unwrap();
+    let (attempts, last_error, published, failed): (u32, Option<String>, bool, bool) = sqlx::query_as(
+        "SELECT attempts, last_error, published_at IS NOT NULL, failed_at IS NOT NULL FROM outbox_events WHERE event_id = ?"
+    )
+        .bind(event_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    (attempts, last_error, published, failed)
+}
+
+async fn event_ids(user_id: u64) -> Vec<String> {
+    let pool = db::get_pool().await.unwrap();
+    let rows: Vec<(String,)> = sqlx::query_as("SELECT event_id FROM outbox_events WHERE user_id = ? ORDER BY id")
+        .bind(user_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    rows.into_iter().map(|(id,)| id).collect()
+}
+
+/// =======================
+/// Config & Envelope Tests
+/// =======================
+
+#[test]
+fn retry_delay_doubles_up_to_the_limit() {
+    let cfg = OutboxConfig { retry_base_secs: 5, retry_max_secs: 60, ..OutboxConfig::default() };
+    let delays: Vec<u64> = (1..=6).map(|attempt| retry_delay(&cfg, attempt)).collect();
+    assert_eq!(delays, vec![5, 10, 20, 40, 60, 60]);
+    assert_eq!(retry_delay(&cfg, 200), 60);
+}
+
+#[test]
+fn sinks_are_configured_by_kind() {
+    let cfg: OutboxConfig = serde_json::from_value(json!({
+        "sinks": [{"kind": "log"}, {"kind": "webhook", "url": "https://example.com/hooks", "secret": "s3cret"}]
+    })).unwrap();
+    assert_eq!(cfg.sinks, vec![
+        EventSinkConfig::Log,
+        EventSinkConfig::Webhook { url: "https://example.com/hooks".into(), secret: "s3cret".into(), timeout_ms: 10_000 },
+    ]);
+    assert_eq!(cfg.max_attempts, 10);
+
+    assert!(serde_json::from_value::<OutboxConfig>(json!({"sinks": [{"kind": "kafka"}]})).is_err());
+    assert!(OutboxConfig::default().sinks.is_empty());
+}
+
+#[test]
+fn envelope_carries_type_and_stable_id() {
+    let body = serde_json::to_value(envelope()).unwrap();
+    assert_eq!(body, json!({
+        "id": "0b6f5c1e-8f4e-4f7a-9d0a-2f1c2b3a4d5e",
+        "type": "UserCreated",
+        "user_id": 7,
+        "occurred_at": 1_767_225_600_000i64,
+        "data": {"user": {"id": 7, "name": "Budi"}},
+    }));
+
+    for kind in [DomainEventKind::UserCreated, DomainEventKind::UserUpdated, DomainEventKind::UserDeleted, DomainEventKind::UserRestored, DomainEventKind::UserLoggedIn] {
+        assert_eq!(DomainEventKind::try_from(kind.as_str().to_string()), Ok(kind));
+    }
+}
+
+/// =======================
+/// Webhook Sink Tests
+/// =======================
+
+#[tokio::test]
+async fn webhook_posts_signed_json() {
+    let (base, received) = fake_webhook().await;
+    let sink = WebhookSink::new(&format!("{}/hooks", base), "s3cret", Duration::from_secs(5)).unwrap();
+    sink.publish(&envelope()).await.unwrap();
+
+    let received = received.lock().unwrap();
+    let (headers, body) = &received[0];
+    assert_eq!(headers["x-event-type"], "UserCreated");
+    assert_eq!(headers["x-event-id"], "0b6f5c1e-8f4e-4f7a-9d0a-2f1c2b3a4d5e");
+    assert_eq!(headers["x-signature"].to_str().unwrap(), format!("sha256={}", webhook_signature("s3cret", body)));
+    assert_eq!(serde_json::from_slice::<Value>(body).unwrap()["type"], "UserCreated");
+}
+
+#[tokio::test]
+async fn webhook_error_status_is_a_failure() {
+    let (base, _) = fake_webhook().await;
+    let sink = WebhookSink::new(&format!("{}/down", base), "", Duration::from_secs(5)).unwrap();
+    assert!(matches!(sink.publish(&envelope()).await, Err(AppError::HttpError(_))));
+}
+
+/// =======================
+/// Outbox Tests
+/// =======================
+
+#[tokio::test]
+async fn event_is_written_only_when_the_change_commits() {
+    let pool = db::get_pool().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE email IN (?, ?)").bind("outbox-rollback@corp.test.com").bind("outbox-commit@corp.test.com").execute(&pool).await.unwrap();
+    let insert = |email: &str| UserInsert { name: "Outbox User".into(), email: email.into(), password: "Rahasia-123!".into(), attributes: Default::default() };
+
+    let mut tx = pool.begin().await.unwrap();
+    let rolled_back = create_user(&mut tx, &insert("outbox-rollback@corp.test.com"), &ctx()).await.unwrap();
+    tx.rollback().await.unwrap();
+    assert!(event_ids(rolled_back).await.is_empty());
+
+    let mut tx = pool.begin().await.unwrap();
+    let committed = create_user(&mut tx, &insert("outbox-commit@corp.test.com"), &ctx()).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let (event_type, payload): (String, Value) = sqlx::query_as("SELECT event_type, payload FROM outbox_events WHERE user_id = ?")
+        .bind(committed)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(event_type, "UserCreated");
+    assert_eq!(payload["user"]["email"], "outbox-commit@corp.test.com");
+    assert!(payload["user"].get("password").is_none());
+}
+
+#[tokio::test]
+async fn imported_user_gets_user_created_event() {
+    let pool = db::get_pool().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE email = ?").bind("outbox-import@corp.test.com").execute(&pool).await.unwrap();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/x-ndjson"));
+    let body = Body::from(r#"{"name": "Outbox Import", "email": "outbox-import@corp.test.com", "password": "Rahasia-123!"}"#);
+    let (_, report) = import_users(ctx(), Query(ImportParams::default()), headers, body).await.unwrap();
+    let id = report.rows[0].id.unwrap();
+
+    let (event_type, payload): (String, Value) = sqlx::query_as("SELECT event_type, payload FROM outbox_events WHERE user_id = ?")
+        .bind(id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(event_type, "UserCreated");
+    assert_eq!(payload["user"]["email"], "outbox-import@corp.test.com");
+}
+
+//satu test agar dispatcher dengan sink gagal tidak mengganggu event test lain yang berjalan paralel
+#[tokio::test]
+async fn failed_delivery_is_retried_in_order_until_published_or_given_up() {
+    let pool = db::get_pool().await.unwrap();
+    let cfg = OutboxConfig { max_attempts: 2, retry_base_secs: 3600, ..OutboxConfig::default() };
+    //user id yang tidak dipakai user lain agar event test ini terpisah
+    let user_id = 4_000_000_000 + (Utc::now().timestamp_millis() as u64 % 1_000_000);
+
+    let mut tx = pool.begin().await.unwrap();
+    enqueue(&mut tx, DomainEventKind::UserUpdated, user_id, json!({"changed": ["name"]})).await.unwrap();
+    enqueue(&mut tx, DomainEventKind::UserDeleted, user_id, json!({})).await.unwrap();
+    tx.commit().await.unwrap();
+    let ids = event_ids(user_id).await;
+
+    //event kedua menunggu event pertama walaupun sudah jatuh tempo
+    let failing = RecordingSink::failing();
+    dispatch_once(&pool, std::slice::from_ref(&failing), &cfg).await.unwrap();
+    assert_eq!(event_state(&ids[0]).await, (1, Some("recording: Bad request".into()), false, false));
+    assert_eq!(event_state(&ids[1]).await.0, 0);
+
+    //jadwal ulang dipercepat, sink sekarang menerima
+    sqlx::query("UPDATE outbox_events SET next_attempt_at = NOW(3) WHERE user_id = ?").bind(user_id).execute(&pool).await.unwrap();
+    let sink = RecordingSink::default();
+    dispatch_once(&pool, std::slice::from_ref(&sink), &cfg).await.unwrap();
+    dispatch_once(&pool, std::slice::from_ref(&sink), &cfg).await.unwrap();
+
+    let received: Vec<String> = sink.ids().into_iter().filter(|id| ids.contains(id)).collect();
+    assert_eq!(received, ids);
+    assert_eq!(event_state(&ids[0]).await, (2, None, true, false));
+    assert_eq!(event_state(&ids[1]).await, (1, None, true, false));
+
+    //percobaan habis: event ditandai failed dan tidak diklaim lagi
+    let mut tx = pool.begin().await.unwrap();
+    enqueue(&mut tx, DomainEventKind::UserLoggedIn, user_id, json!({})).await.unwrap();
+    tx.commit().await.unwrap();
+    let last = event_ids(user_id).await.pop().unwrap();
+
+    let give_up = OutboxConfig { max_attempts: 1, ..cfg };
+    let report = dispatch_once(&pool, std::slice::from_ref(&failing), &give_up).await.unwrap();
+    assert!(report.failed >= 1);
+    assert_eq!(event_state(&last).await, (1, Some("recording: Bad request".into()), false, true));
+
+    dispatch_once(&pool, std::slice::from_ref(&sink), &give_up).await.unwrap();
+    assert!(!sink.ids().contains(&last));
+}
@@ -0,0 +1,102 @@
+use argon2::{Argon2, PasswordHasher, password_hash::{SaltString, rand_core::OsRng}};
+
+use crate::{
+    errors::app_error::AppError,
+    models::config_model::PasswordHashConfig,
+    utils::password_hash::{hash_blocking, needs_rehash, run_limited, verify_blocking},
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+//parameter kecil agar test cepat
+fn cheap() -> PasswordHashConfig {
+    PasswordHashConfig { memory_kib: 1024, iterations: 1, parallelism: 1, pepper: None, max_concurrent: 2 }
+}
+
+fn peppered() -> PasswordHashConfig {
+    PasswordHashConfig { pepper: Some("server-side-pepper".to_string()), ..cheap() }
+}
+
+/// =======================
+/// Hash & Verify Tests
+/// =======================
+
+#[test]
+fn hash_uses_configured_params() {
+    let hash = hash_blocking(&cheap(), "rahasia123").unwrap();
+    assert!(hash.starts_with("$argon2id$v=19$m=1024,t=1,p=1$"));
+    assert!(verify_blocking(&cheap(), &hash, "rahasia123").unwrap());
+    assert!(!verify_blocking(&cheap(), &hash, "salah").unwrap());
+}
+
+#[test]
+fn peppered_hash_needs_the_pepper() {
+    let hash = hash_blocking(&peppered(), "rahasia123").unwrap();
+    assert!(hash.contains("keyid="));
+    assert!(verify_blocking(&peppered(), &hash, "rahasia123").unwrap());
+
+    let other_pepper = PasswordHashConfig { pepper: Some("another".to_string()), ..cheap() };
+    assert!(!verify_blocking(&other_pepper, &hash, "rahasia123").unwrap());
+    assert!(matches!(verify_blocking(&cheap(), &hash, "rahasia123"), Err(AppError::InternalServerError)));
+}
+
+#[test]
+fn hash_without_pepper_still_verifies_after_pepper_is_enabled() {
+    let hash = hash_blocking(&cheap(), "rahasia123").unwrap();
+    assert!(verify_blocking(&peppered(), &hash, "rahasia123").unwrap());
+    assert!(needs_rehash(&peppered(), &hash));
+}
+
+/// =======================
+/// Rehash Tests
+/// =======================
+
+#[test]
+fn legacy_default_hash_needs_rehash_when_params_differ() {
+    let salt = SaltString::generate(&mut OsRng);
+    let legacy = Argon2::default().hash_password(b"rahasia123", &salt).unwrap().to_string();
+
+    assert!(verify_blocking(&cheap(), &legacy, "rahasia123").unwrap());
+    assert!(needs_rehash(&cheap(), &legacy));
+    assert!(!needs_rehash(&PasswordHashConfig::default(), &legacy));
+}
+
+#[test]
+fn current_hash_does_not_need_rehash() {
+    let hash = hash_blocking(&peppered(), "rahasia123").unwrap();
+    assert!(!needs_rehash(&peppered(), &hash));
+
+    let stronger = PasswordHashConfig { iterations: 3, ..peppered() };
+    assert!(needs_rehash(&stronger, &hash));
+}
+
+#[test]
+fn argon2i_hash_needs_rehash() {
+    let salt = SaltString::generate(&mut OsRng);
+    let params = argon2::Params::new(1024, 1, 1, None).unwrap();
+    let hash = Argon2::new(argon2::Algorithm::Argon2i, argon2::Version::V0x13, params)
+        .hash_password(b"rahasia123", &salt).unwrap().to_string();
+
+    assert!(verify_blocking(&cheap(), &hash, "rahasia123").unwrap());
+    assert!(needs_rehash(&cheap(), &hash));
+}
+
+/// =======================
+/// Limiter Tests
+/// =======================
+
+#[tokio::test]
+async fn hashing_runs_on_blocking_pool() {
+    let cfg = cheap();
+    let jobs = (0..4).map(|i| {
+        let cfg = cfg.clone();
+        tokio::spawn(run_limited(cfg.max_concurrent, move || hash_blocking(&cfg, &format!("password-{}", i))))
+    });
+
+    for job in jobs {
+        let hash = job.await.unwrap().unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+    }
+}
@@ -0,0 +1,96 @@
+use std::path::Path;
+use validator::{Validate, ValidationError};
+
+use crate::{models::{config_model::PasswordPolicyConfig, user_model::PasswordChange}, utils::password_policy::{check_password, is_breached}};
+
+// =======================
+// Helper Functions
+// =======================
+
+const PWNED: &str = "src/tests/fixtures/pwned";
+
+fn codes(errors: &[ValidationError]) -> Vec<&str> {
+    errors.iter().map(|e| e.code.as_ref()).collect()
+}
+
+fn strict() -> PasswordPolicyConfig {
+    PasswordPolicyConfig {
+        min_length: 10,
+        max_length: 64,
+        require_lowercase: true,
+        require_uppercase: true,
+        require_digit: true,
+        require_symbol: true,
+        disallow_personal_info: true,
+        min_score: 3,
+        breached_hashes_dir: None,
+    }
+}
+
+/// =======================
+/// Policy Rule Tests
+/// =======================
+
+#[test]
+fn default_policy_keeps_old_minimum() {
+    let policy = PasswordPolicyConfig::default();
+    assert!(check_password(&policy, "123456", "TestUser", "valid@test.com").is_empty());
+    assert!(check_password(&policy, "testuser1", "TestUser", "testuser@test.com").is_empty());
+    assert_eq!(codes(&check_password(&policy, "1234", "TestUser", "valid@test.com")), vec!["password_too_short"]);
+}
+
+#[test]
+fn length_limits_are_enforced() {
+    let policy = PasswordPolicyConfig { max_length: 12, ..PasswordPolicyConfig::default() };
+    assert_eq!(codes(&check_password(&policy, "abcdefghijklm", "Budi", "budi@test.com")), vec!["password_too_long"]);
+}
+
+#[test]
+fn all_missing_character_classes_are_reported() {
+    let policy = PasswordPolicyConfig { min_score: 0, ..strict() };
+    let errors = check_password(&policy, "alllowercase", "Budi", "budi@test.com");
+    assert_eq!(codes(&errors), vec!["password_no_uppercase", "password_no_digit", "password_no_symbol"]);
+}
+
+#[test]
+fn name_and_email_inside_password_are_rejected() {
+    let policy = PasswordPolicyConfig { disallow_personal_info: true, ..PasswordPolicyConfig::default() };
+    assert_eq!(codes(&check_password(&policy, "xxSantoso2024", "Budi Santoso", "b.s@test.com")), vec!["password_personal_info"]);
+    assert_eq!(codes(&check_password(&policy, "my-budi.s-pass", "Andi", "budi.s@test.com")), vec!["password_personal_info"]);
+
+    let allowed = PasswordPolicyConfig { disallow_personal_info: false, ..PasswordPolicyConfig::default() };
+    assert!(check_password(&allowed, "xxSantoso2024", "Budi Santoso", "b.s@test.com").is_empty());
+}
+
+#[test]
+fn weak_password_fails_strength_score() {
+    let policy = strict();
+    assert!(codes(&check_password(&policy, "Password1!", "Budi", "budi@test.com")).contains(&"password_too_weak"));
+    assert!(check_password(&policy, "Gr4vel-Ocean-Tulip-Mirth", "Budi", "budi@test.com").is_empty());
+}
+
+#[test]
+fn password_change_payload_is_length_bounded() {
+    let change = |current: Option<usize>, new: usize| PasswordChange { current_password: current.map(|n| "a".repeat(n)), new_password: "b".repeat(new) };
+    assert!(change(Some(20), 20).validate().is_ok());
+    assert!(change(None, 20).validate().is_ok());
+    assert!(change(None, 0).validate().is_err());
+    assert!(change(None, 1025).validate().is_err());
+    assert!(change(Some(1025), 20).validate().is_err());
+}
+
+/// =======================
+/// Breached Password Tests
+/// =======================
+
+#[tokio::test]
+async fn breached_password_found_by_prefix_file() {
+    assert!(is_breached(Path::new(PWNED), "P@ssw0rd!2024").await.unwrap());
+}
+
+#[tokio::test]
+async fn unknown_or_missing_prefix_is_not_breached() {
+    //prefix ABF7A tidak punya file di fixture
+    assert!(!is_breached(Path::new(PWNED), "correct horse battery staple").await.unwrap());
+    assert!(!is_breached(Path::new(PWNED), "Gr4vel-Ocean-Tulip-Mirth").await.unwrap());
+}
@@ -0,0 +1,137 @@
+use chrono::{TimeZone, Utc};
+
+use crate::{
+    errors::app_error::AppError,
+    models::user_model::User,
+    utils::{search_index::UserIndex, search_query::parse_search},
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn user(id: u64, name: &str, email: &str, day: u32) -> User {
+    let at = Utc.with_ymd_and_hms(2025, 1, day, 12, 0, 0).unwrap();
+    User { id, name: name.into(), email: email.into(), password: None, created_at: at, updated_at: at, deleted_at: None, avatar_url: None, attributes: Default::default() }
+}
+
+fn index() -> UserIndex {
+    let index = UserIndex::in_memory().unwrap();
+    index.replace_all([Ok(vec![
+        user(1, "Budi Santoso", "budi@corp.com", 1),
+        user(2, "Santi Wijaya", "santi@corp.com", 2),
+        user(3, "Andreas Hartono", "andreas@other.com", 3),
+        user(4, "Budiman", "budiman@test.com", 4),
+    ])]).unwrap();
+    index
+}
+
+fn ids(index: &UserIndex, q: &str) -> Vec<u64> {
+    let (hits, total) = index.search_ids(&parse_search(q).unwrap(), 0, 20).unwrap();
+    assert_eq!(total as usize, hits.len());
+    let mut ids: Vec<u64> = hits.into_iter().map(|(id, _)| id).collect();
+    ids.sort();
+    ids
+}
+
+/// =======================
+/// Fuzzy Match Tests
+/// =======================
+
+#[test]
+fn names_tolerate_typos() {
+    let index = index();
+    assert_eq!(ids(&index, "santoso"), vec![1]);
+    assert_eq!(ids(&index, "sntoso"), vec![1]);
+    assert_eq!(ids(&index, "andraes hartono"), vec![3]);
+}
+
+#[test]
+fn short_words_must_match_exactly_as_prefix() {
+    let index = index();
+    assert_eq!(ids(&index, "bud"), vec![1, 4]);
+    assert_eq!(ids(&index, "bdi"), Vec::<u64>::new());
+}
+
+#[test]
+fn exact_match_ranks_above_fuzzy_match() {
+    let index = index();
+    let (hits, _) = index.search_ids(&parse_search("santi").unwrap(), 0, 20).unwrap();
+    assert_eq!(hits[0].0, 2);
+}
+
+/// =======================
+/// Query Language Tests
+/// =======================
+
+#[test]
+fn field_filters_use_whole_values() {
+    let index = index();
+    assert_eq!(ids(&index, "email:*@corp.com"), vec![1, 2]);
+    assert_eq!(ids(&index, "name:\"budi santoso\""), vec![1]);
+    assert_eq!(ids(&index, "name:budi"), Vec::<u64>::new());
+}
+
+#[test]
+fn ranges_and_boolean_operators() {
+    let index = index();
+    assert_eq!(ids(&index, "id>=3"), vec![3, 4]);
+    assert_eq!(ids(&index, "created:2025-01-02"), vec![2]);
+    assert_eq!(ids(&index, "updated<2025-01-03"), vec![1, 2]);
+    assert_eq!(ids(&index, "email:*@corp.com -wijaya"), vec![1]);
+    assert_eq!(ids(&index, "wijaya OR hartono"), vec![2, 3]);
+    assert_eq!(ids(&index, ""), vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn pagination_reports_total() {
+    let index = index();
+    let (hits, total) = index.search_ids(&parse_search("").unwrap(), 2, 1).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(total, 4);
+}
+
+/// =======================
+/// Sync Tests
+/// =======================
+
+#[test]
+fn upsert_replaces_and_remove_deletes() {
+    let index = index();
+    index.upsert(&user(2, "Santi Rahma", "santi@corp.com", 2)).unwrap();
+    assert_eq!(ids(&index, "rahma"), vec![2]);
+    assert_eq!(ids(&index, "wijaya"), Vec::<u64>::new());
+
+    index.remove(2).unwrap();
+    assert_eq!(ids(&index, "rahma"), Vec::<u64>::new());
+    assert_eq!(ids(&index, "id>0"), vec![1, 3, 4]);
+}
+
+/// =======================
+/// Rebuild Tests
+/// =======================
+
+#[test]
+fn rebuild_is_written_in_batches_and_committed_once() {
+    let index = index();
+    let count = index.replace_all([
+        Ok(vec![user(5, "Dewi Lestari", "dewi@corp.com", 5)]),
+        Ok(vec![user(6, "Eko Prasetyo", "eko@corp.com", 6)]),
+    ]).unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(ids(&index, ""), vec![5, 6]);
+}
+
+#[test]
+fn failed_batch_keeps_the_old_index() {
+    let index = index();
+    let result = index.replace_all([
+        Ok(vec![user(5, "Dewi Lestari", "dewi@corp.com", 5)]),
+        Err(AppError::InternalServerError),
+    ]);
+    assert!(result.is_err());
+    assert_eq!(ids(&index, ""), vec![1, 2, 3, 4]);
+
+    index.upsert(&user(6, "Eko Prasetyo", "eko@corp.com", 6)).unwrap();
+    assert_eq!(ids(&index, ""), vec![1, 2, 3, 4, 6]);
+}
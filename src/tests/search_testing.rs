@@ -0,0 +1,218 @@
+use axum::{Router, routing::get};
+use axum_test::TestServer;
+use chrono::{TimeZone, Utc};
+use http::StatusCode;
+use serde_json::Value;
+
+use crate::{
+    configs::db,
+    controllers::user_controller::search_users,
+    errors::app_error::AppError,
+    models::user_model::User,
+    tests::common::{api_key, protected_server, session_cookie},
+    utils::{
+        search::{count_query, fulltext_term, highlight, highlights, select_query},
+        search_query::{CmpOp, DateField, Filter, SearchExpr, TextField, parse_search},
+    },
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn server() -> TestServer {
+    protected_server(
+        Router::new()
+            .route("/users/search", get(search_users))
+    )
+}
+
+async fn insert_user(name: &str, email: &str) -> u64 {
+    let pool = db::get_pool().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE email = ?").bind(email).execute(&pool).await.unwrap();
+    sqlx::query("INSERT INTO users (name, email, password) VALUES (?, ?, ?)")
+        .bind(name)
+        .bind(email)
+        .bind("123456")
+        .execute(&pool)
+        .await
+        .unwrap()
+        .last_insert_id()
+}
+
+fn filter(expr: SearchExpr) -> Filter {
+    match expr {
+        SearchExpr::Filter(filter) => filter,
+        other => panic!("bukan filter tunggal: {:?}", other),
+    }
+}
+
+fn user(name: &str, email: &str) -> User {
+    let at = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    User { id: 1, name: name.into(), email: email.into(), password: None, created_at: at, updated_at: at, deleted_at: None, avatar_url: None, attributes: Default::default() }
+}
+
+/// =======================
+/// Query Language Tests
+/// =======================
+
+#[test]
+fn field_filters_are_parsed() {
+    assert_eq!(filter(parse_search("name:budi").unwrap()), Filter::Match { field: TextField::Name, pattern: "budi".into() });
+    assert_eq!(filter(parse_search("email:*@corp.com").unwrap()), Filter::Match { field: TextField::Email, pattern: "*@corp.com".into() });
+    assert_eq!(filter(parse_search("id>=10").unwrap()), Filter::Id { op: CmpOp::Gte, value: 10 });
+    assert_eq!(filter(parse_search("name:\"budi santoso\"").unwrap()), Filter::Match { field: TextField::Name, pattern: "budi santoso".into() });
+}
+
+#[test]
+fn date_filters_cover_whole_days() {
+    let day = |d| Utc.with_ymd_and_hms(2025, 1, d, 0, 0, 0).unwrap();
+
+    assert_eq!(filter(parse_search("created>2025-01-01").unwrap()), Filter::Date { field: DateField::Created, from: Some(day(2)), to: None });
+    assert_eq!(filter(parse_search("created:2025-01-01").unwrap()), Filter::Date { field: DateField::Created, from: Some(day(1)), to: Some(day(2)) });
+    assert_eq!(filter(parse_search("updated<=2025-01-01").unwrap()), Filter::Date { field: DateField::Updated, from: None, to: Some(day(2)) });
+}
+
+#[test]
+fn adjacent_terms_are_and_and_or_binds_looser() {
+    let expr = parse_search("name:budi email:*@corp.com OR admin").unwrap();
+    let SearchExpr::Or(items) = expr else { panic!("harus OR") };
+    assert!(matches!(&items[0], SearchExpr::And(and) if and.len() == 2));
+    assert_eq!(items[1], SearchExpr::Filter(Filter::Text("admin".into())));
+}
+
+#[test]
+fn parentheses_and_negation() {
+    let expr = parse_search("(budi OR santi) -email:*@test.com").unwrap();
+    let SearchExpr::And(items) = expr else { panic!("harus AND") };
+    assert!(matches!(&items[0], SearchExpr::Or(_)));
+    assert!(matches!(&items[1], SearchExpr::Not(_)));
+}
+
+#[test]
+fn invalid_queries_are_validation_errors() {
+    for query in ["(budi", "budi)", "\"budi", "age:20", "id:abc", "created>kemarin", "name>budi", "name:"] {
+        assert!(matches!(parse_search(query), Err(AppError::ValidationError(_))), "query {:?} harus ditolak", query);
+    }
+}
+
+#[test]
+fn query_length_and_term_count_are_limited() {
+    assert!(parse_search(&"a".repeat(501)).is_err());
+    assert!(parse_search(&vec!["budi"; 21].join(" ")).is_err());
+    assert!(parse_search(&vec!["budi"; 20].join(" ")).is_ok());
+}
+
+/// =======================
+/// SQL Tests
+/// =======================
+
+#[test]
+fn sql_uses_bind_parameters_only() {
+    let expr = parse_search("name:budi' email:*@corp.com -(id<5 OR created>2025-01-01)").unwrap();
+    let sql = count_query(&expr).into_sql();
+
+    assert!(!sql.contains("budi'"));
+    assert_eq!(sql, "SELECT COUNT(*) FROM users WHERE deleted_at IS NULL AND (name = ? AND email LIKE ? AND NOT ((id < ? OR (TRUE AND created_at >= ?))))");
+}
+
+#[test]
+fn free_text_uses_fulltext_and_ranks_by_score() {
+    let expr = parse_search("santoso").unwrap();
+    let sql = select_query(&expr, 0, 20).into_sql();
+
+    assert!(sql.contains("MATCH(name, email) AGAINST (? IN NATURAL LANGUAGE MODE)"));
+    assert!(sql.contains("MATCH(name, email) AGAINST (? IN BOOLEAN MODE)"));
+    assert!(sql.ends_with("ORDER BY score DESC, id ASC LIMIT ? OFFSET ?"));
+}
+
+#[test]
+fn fulltext_term_strips_operators_and_skips_short_words() {
+    assert_eq!(fulltext_term("budi"), Some("budi*".into()));
+    assert_eq!(fulltext_term("+budi*"), Some("budi*".into()));
+    assert_eq!(fulltext_term("budi santoso"), Some("\"budi santoso\"".into()));
+    assert_eq!(fulltext_term("bu"), None);
+}
+
+#[test]
+fn fulltext_term_splits_emails_into_words() {
+    assert_eq!(fulltext_term("budi@corp.com"), Some("\"budi corp com\"".into()));
+    assert_eq!(fulltext_term("budi.santoso"), Some("\"budi santoso\"".into()));
+    assert_eq!(fulltext_term("a.b@x.io"), None);
+}
+
+/// =======================
+/// Highlight Tests
+/// =======================
+
+#[test]
+fn highlight_marks_matches_and_escapes_html() {
+    assert_eq!(highlight("Budi <b>Santoso</b>", &["budi", "santo"]).unwrap(), "<mark>Budi</mark> &lt;b&gt;<mark>Santo</mark>so&lt;/b&gt;");
+    assert_eq!(highlight("Budi", &["xyz"]), None);
+}
+
+#[test]
+fn highlights_respect_field_filters() {
+    let expr = parse_search("name:bud* email:*@corp.com").unwrap();
+    let result = highlights(&expr, &user("Budi", "budi@corp.com"));
+
+    assert_eq!(result["name"], "<mark>Bud</mark>i");
+    assert_eq!(result["email"], "budi<mark>@corp.com</mark>");
+}
+
+#[test]
+fn negated_terms_are_not_highlighted() {
+    let expr = parse_search("budi -santoso").unwrap();
+    let result = highlights(&expr, &user("Budi Santoso", "budi@corp.com"));
+    assert_eq!(result["name"], "<mark>Budi</mark> Santoso");
+}
+
+/// =======================
+/// GET /users/search Tests
+/// =======================
+
+#[tokio::test]
+async fn search_combines_filters_and_paginates() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    insert_user("Search Budi", "search-budi@corp.test.com").await;
+    insert_user("Search Santi", "search-santi@corp.test.com").await;
+    insert_user("Search Budi Lain", "search-budi@other.test.com").await;
+
+    let res = server.get("/users/search").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("q", "name:Search* email:*@corp.test.com")
+        .add_query_param("per_page", "1")
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+
+    let body: Value = res.json();
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["items"].as_array().unwrap().len(), 1);
+    assert!(body["items"][0]["highlights"]["email"].as_str().unwrap().contains("<mark>"));
+}
+
+#[tokio::test]
+async fn search_syntax_error_is_bad_request() {
+    let server = server();
+    let admin = session_cookie(1).await;
+
+    let res = server.get("/users/search").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("q", "(budi")
+        .await;
+    assert_eq!(res.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn free_text_email_query_finds_the_user() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let id = insert_user("Search Email", "search-email@corp.test.com").await;
+
+    let res = server.get("/users/search").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("q", "search-email@corp.test.com")
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+
+    let body: Value = res.json();
+    assert!(body["items"].as_array().unwrap().iter().any(|u| u["id"] == id));
+}
@@ -0,0 +1,149 @@
+use std::net::SocketAddr;
+use axum::{Router, routing::{delete, get}};
+use axum_test::TestServer;
+use axum_extra::extract::cookie::SameSite;
+use http::{HeaderMap, HeaderValue, StatusCode};
+
+use crate::{
+    configs::db,
+    controllers::session_controller::{delete_session, list_sessions},
+    tests::common::{api_key, login_session, protected_server},
+    utils::{client_info::{ClientInfo, is_trusted_proxy}, session::server_cookie},
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn server() -> TestServer {
+    protected_server(
+        Router::new()
+            .route("/me/sessions", get(list_sessions))
+            .route("/me/sessions/{id}", delete(delete_session))
+    )
+}
+
+async fn login(user_id: u64, user_agent: &str) -> (String, String) {
+    let client = ClientInfo { ip_address: Some("10.0.0.1".to_string()), user_agent: Some(user_agent.to_string()) };
+    login_session(user_id, &client).await
+}
+
+fn trusted() -> Vec<String> {
+    vec!["127.0.0.1".to_string(), "10.0.0.0/8".to_string()]
+}
+
+/// =======================
+/// ClientInfo Tests
+/// =======================
+
+#[test]
+fn client_info_prefers_forwarded_for_from_trusted_proxy() {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-forwarded-for", HeaderValue::from_static("198.51.100.1, 203.0.113.9, 10.0.0.2"));
+    headers.insert("user-agent", HeaderValue::from_static("Firefox"));
+    let peer: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+
+    //alamat paling kanan yang bukan proxy tepercaya, bagian kiri bisa dikirim client sendiri
+    let info = ClientInfo::from_headers(&headers, Some(peer), &trusted());
+    assert_eq!(info.ip_address.as_deref(), Some("203.0.113.9"));
+    assert_eq!(info.user_agent.as_deref(), Some("Firefox"));
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-real-ip", HeaderValue::from_static("203.0.113.7"));
+    let info = ClientInfo::from_headers(&headers, Some(peer), &trusted());
+    assert_eq!(info.ip_address.as_deref(), Some("203.0.113.7"));
+}
+
+#[test]
+fn client_info_ignores_forwarded_headers_from_untrusted_peer() {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.9"));
+    headers.insert("x-real-ip", HeaderValue::from_static("203.0.113.9"));
+    let peer: SocketAddr = "192.0.2.4:5000".parse().unwrap();
+
+    let info = ClientInfo::from_headers(&headers, Some(peer), &trusted());
+    assert_eq!(info.ip_address.as_deref(), Some("192.0.2.4"));
+    assert_eq!(ClientInfo::from_headers(&headers, None, &trusted()).ip_address, None);
+}
+
+#[test]
+fn client_info_falls_back_to_peer_address() {
+    let peer: SocketAddr = "192.0.2.4:5000".parse().unwrap();
+    let info = ClientInfo::from_headers(&HeaderMap::new(), Some(peer), &trusted());
+
+    assert_eq!(info.ip_address.as_deref(), Some("192.0.2.4"));
+    assert_eq!(info.user_agent, None);
+}
+
+#[test]
+fn trusted_proxies_match_ip_and_cidr() {
+    let trusted = vec!["10.0.0.0/8".to_string(), "192.0.2.10".to_string(), "fd00::/8".to_string()];
+    assert!(is_trusted_proxy("10.20.30.40".parse().unwrap(), &trusted));
+    assert!(is_trusted_proxy("192.0.2.10".parse().unwrap(), &trusted));
+    assert!(is_trusted_proxy("fd12::1".parse().unwrap(), &trusted));
+    assert!(!is_trusted_proxy("11.0.0.1".parse().unwrap(), &trusted));
+    assert!(!is_trusted_proxy("192.0.2.11".parse().unwrap(), &trusted));
+    assert!(!is_trusted_proxy("10.0.0.1".parse().unwrap(), &[]));
+}
+
+/// =======================
+/// Cookie Tests
+/// =======================
+
+#[test]
+fn session_cookie_is_http_only_secure_and_same_site() {
+    let cookie = server_cookie("jwt", "token".to_string()).unwrap();
+    assert_eq!(cookie.http_only(), Some(true));
+    assert_eq!(cookie.secure(), Some(true));
+    assert_eq!(cookie.same_site(), Some(SameSite::Lax));
+    assert_eq!(cookie.path(), Some("/"));
+}
+
+/// =======================
+/// /me/sessions Tests
+/// =======================
+
+#[tokio::test]
+async fn sign_out_other_device() {
+    let server = server();
+    let (_, laptop) = login(900_001, "Laptop").await;
+    let (phone_sid, phone) = login(900_001, "Phone").await;
+
+    let res = server.get("/me/sessions")
+        .add_header("X-API-KEY", api_key())
+        .add_header("Cookie", &laptop)
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+    let sessions = res.json::<Vec<serde_json::Value>>();
+    assert!(sessions.iter().any(|s| s["id"] == phone_sid.as_str() && s["current"] == false));
+
+    let res = server.delete(&format!("/me/sessions/{}", phone_sid))
+        .add_header("X-API-KEY", api_key())
+        .add_header("Cookie", &laptop)
+        .await;
+    assert_eq!(res.status_code(), StatusCode::NO_CONTENT);
+
+    let res = server.get("/me/sessions")
+        .add_header("X-API-KEY", api_key())
+        .add_header("Cookie", &phone)
+        .await;
+    assert_eq!(res.status_code(), StatusCode::UNAUTHORIZED);
+
+    //session dicabut, bukan dihapus
+    let pool = db::get_pool().await.unwrap();
+    let (revoked,): (bool,) = sqlx::query_as("SELECT revoked_at IS NOT NULL FROM sessions WHERE id = ?").bind(&phone_sid).fetch_one(&pool).await.unwrap();
+    assert!(revoked);
+}
+
+#[tokio::test]
+async fn cannot_delete_session_of_other_user() {
+    let server = server();
+    let (_, mine) = login(900_002, "Laptop").await;
+    let (other_sid, _) = login(900_003, "Laptop").await;
+
+    let res = server.delete(&format!("/me/sessions/{}", other_sid))
+        .add_header("X-API-KEY", api_key())
+        .add_header("Cookie", &mine)
+        .await;
+    assert_eq!(res.status_code(), StatusCode::NOT_FOUND);
+}
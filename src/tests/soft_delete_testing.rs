@@ -0,0 +1,125 @@
+use axum::{Router, routing::{delete, get, post}};
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::Value;
+
+use crate::{
+    configs::db,
+    controllers::user_controller::{delete_user, get_all_user, get_deleted_users, restore_user},
+    jobs::purge_users::{purge_deleted_users, purge_user},
+    tests::common::{api_key, protected_server, session_cookie},
+    utils::{client_info::ClientInfo, session::start_session},
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn server() -> TestServer {
+    protected_server(
+        Router::new()
+            .route("/user", get(get_all_user))
+            .route("/user", delete(delete_user))
+            .route("/user/deleted", get(get_deleted_users))
+            .route("/user/{id}/restore", post(restore_user))
+    )
+}
+
+async fn insert_user(email: &str) -> u64 {
+    let pool = db::get_pool().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE email = ?").bind(email).execute(&pool).await.unwrap();
+    sqlx::query("INSERT INTO users (name, email, password) VALUES (?, ?, ?)")
+        .bind("SoftUser")
+        .bind(email)
+        .bind("123456")
+        .execute(&pool)
+        .await
+        .unwrap()
+        .last_insert_id()
+}
+
+fn ids(body: &Value) -> Vec<u64> {
+    body.as_array().unwrap().iter().map(|u| u["id"].as_u64().unwrap()).collect()
+}
+
+/// =======================
+/// Soft Delete & Restore Tests
+/// =======================
+
+#[tokio::test]
+async fn deleted_user_is_hidden_and_can_be_restored() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let user_id = insert_user("softdelete@test.com").await;
+
+    let res = server.delete("/user").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("id", user_id.to_string()).await;
+    assert_eq!(res.status_code(), StatusCode::NO_CONTENT);
+
+    let all = server.get("/user").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin).await;
+    assert!(!ids(&all.json()).contains(&user_id));
+    let deleted = server.get("/user/deleted").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin).await;
+    assert!(ids(&deleted.json()).contains(&user_id));
+
+    let res = server.post(&format!("/user/{}/restore", user_id)).add_header("X-API-KEY", api_key()).add_header("Cookie", &admin).await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+    let all = server.get("/user").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin).await;
+    assert!(ids(&all.json()).contains(&user_id));
+
+    let again = server.post(&format!("/user/{}/restore", user_id)).add_header("X-API-KEY", api_key()).add_header("Cookie", &admin).await;
+    assert_eq!(again.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn deleting_user_revokes_sessions_and_blocks_login() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let user_id = insert_user("softdelete-session@test.com").await;
+    let victim = session_cookie(user_id).await;
+
+    server.delete("/user").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("id", user_id.to_string()).await;
+
+    let res = server.get("/user").add_header("X-API-KEY", api_key()).add_header("Cookie", &victim).await;
+    assert_eq!(res.status_code(), StatusCode::UNAUTHORIZED);
+
+    let pool = db::get_pool().await.unwrap();
+    assert!(start_session(&pool, user_id, &ClientInfo::default()).await.is_err());
+}
+
+/// =======================
+/// Purge Tests
+/// =======================
+
+#[tokio::test]
+async fn purge_removes_only_users_past_retention() {
+    let pool = db::get_pool().await.unwrap();
+    let old = insert_user("purge-old@test.com").await;
+    let recent = insert_user("purge-recent@test.com").await;
+    sqlx::query("UPDATE users SET deleted_at = NOW() - INTERVAL 40 DAY WHERE id = ?").bind(old).execute(&pool).await.unwrap();
+    sqlx::query("UPDATE users SET deleted_at = NOW() - INTERVAL 1 DAY WHERE id = ?").bind(recent).execute(&pool).await.unwrap();
+
+    assert!(purge_deleted_users(&pool, 30).await.unwrap() >= 1);
+
+    let remaining: Vec<(u64,)> = sqlx::query_as("SELECT id FROM users WHERE id IN (?, ?)")
+        .bind(old)
+        .bind(recent)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, vec![(recent,)]);
+}
+
+#[tokio::test]
+async fn restored_user_keeps_credentials_when_purge_reaches_it() {
+    let pool = db::get_pool().await.unwrap();
+    let user_id = insert_user("purge-restored@test.com").await;
+    session_cookie(user_id).await;
+    //dipilih purge saat masih terhapus, lalu di-restore sebelum purge_user berjalan
+    sqlx::query("UPDATE users SET deleted_at = NULL WHERE id = ?").bind(user_id).execute(&pool).await.unwrap();
+
+    assert!(!purge_user(&pool, user_id, 30).await.unwrap());
+
+    let (sessions,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM sessions WHERE user_id = ?").bind(user_id).fetch_one(&pool).await.unwrap();
+    assert_eq!(sessions, 1);
+}
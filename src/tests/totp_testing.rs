@@ -0,0 +1,150 @@
+use axum::Json;
+use chrono::Utc;
+use data_encoding::BASE32_NOPAD;
+use http::StatusCode;
+
+use crate::{
+    configs::db,
+    controllers::totp_controller::login_totp,
+    errors::app_error::AppError,
+    models::{config_model::TotpConfig, totp_model::TotpLogin, user_model::TokenType},
+    utils::{
+        client_info::ClientInfo,
+        totp::{generate_recovery_code, generate_secret, hotp, normalize_recovery_code, provisioning_uri, verify_code},
+        utils::{check_totp_config, create_token, load_config},
+    },
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+// secret contoh dari RFC 6238 lampiran B (SHA1)
+fn rfc_secret() -> String {
+    BASE32_NOPAD.encode(b"12345678901234567890")
+}
+
+fn eight_digits() -> TotpConfig {
+    TotpConfig { digits: 8, ..TotpConfig::default() }
+}
+
+//user dengan 2FA aktif memakai secret RFC, mengembalikan id user
+async fn totp_user(email: &str) -> u64 {
+    let pool = db::get_pool().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE email = ?").bind(email).execute(&pool).await.unwrap();
+    sqlx::query("INSERT INTO users (name, email, password, totp_secret, totp_enabled_at) VALUES (?, ?, NULL, ?, NOW())")
+        .bind("Totp User")
+        .bind(email)
+        .bind(rfc_secret())
+        .execute(&pool)
+        .await
+        .unwrap()
+        .last_insert_id()
+}
+
+fn current_code() -> String {
+    let cfg = load_config().unwrap().totp;
+    hotp(b"12345678901234567890", Utc::now().timestamp() as u64 / cfg.period, cfg.digits)
+}
+
+async fn try_login(challenge_token: &str, code: &str) -> Result<StatusCode, AppError> {
+    let payload = TotpLogin { challenge_token: challenge_token.to_string(), code: Some(code.to_string()), recovery_code: None };
+    login_totp(ClientInfo::default(), Json(payload)).await.map(|(status, _)| status)
+}
+
+/// =======================
+/// RFC Test Vectors
+/// =======================
+
+#[test]
+fn hotp_matches_rfc4226_vectors() {
+    let expected = ["755224", "287082", "359152", "969429", "338314"];
+    for (counter, code) in expected.iter().enumerate() {
+        assert_eq!(hotp(b"12345678901234567890", counter as u64, 6), *code);
+    }
+}
+
+#[test]
+fn totp_matches_rfc6238_vectors() {
+    let cfg = eight_digits();
+    for (time, code) in [(59, "94287082"), (1111111109, "07081804"), (1234567890, "89005924"), (2000000000, "69279037")] {
+        assert_eq!(verify_code(&cfg, &rfc_secret(), code, time), Some(time / 30));
+    }
+}
+
+/// =======================
+/// Verification Tests
+/// =======================
+
+#[test]
+fn code_within_skew_is_accepted() {
+    let cfg = eight_digits();
+    assert_eq!(verify_code(&cfg, &rfc_secret(), "94287082", 59 + 30), Some(1));
+    assert_eq!(verify_code(&cfg, &rfc_secret(), "94287082", 59 + 90), None);
+}
+
+#[test]
+fn wrong_length_or_secret_is_rejected() {
+    let cfg = TotpConfig::default();
+    assert_eq!(verify_code(&cfg, &rfc_secret(), "94287082", 59), None);
+    assert_eq!(verify_code(&cfg, "not base32!", "287082", 59), None);
+}
+
+#[test]
+fn generated_secret_is_160_bit_base32() {
+    let secret = generate_secret();
+    assert_eq!(BASE32_NOPAD.decode(secret.as_bytes()).unwrap().len(), 20);
+}
+
+#[test]
+fn provisioning_uri_is_escaped() {
+    let cfg = TotpConfig { issuer: "My App".to_string(), ..TotpConfig::default() };
+    let uri = provisioning_uri(&cfg, "budi@corp.com", "ABC");
+
+    assert_eq!(uri, "otpauth://totp/My%20App:budi@corp.com?secret=ABC&issuer=My%20App&algorithm=SHA1&digits=6&period=30");
+}
+
+#[test]
+fn recovery_codes_normalize() {
+    let code = generate_recovery_code();
+    assert_eq!(code.len(), 11);
+    assert_eq!(normalize_recovery_code(&code.to_uppercase()), code.replace('-', ""));
+}
+
+#[test]
+fn digits_outside_six_to_eight_are_rejected() {
+    assert!(check_totp_config(&TotpConfig::default()).is_ok());
+    assert!(check_totp_config(&eight_digits()).is_ok());
+    assert!(check_totp_config(&TotpConfig { digits: 10, ..TotpConfig::default() }).is_err());
+    assert!(check_totp_config(&TotpConfig { digits: 5, ..TotpConfig::default() }).is_err());
+    assert!(check_totp_config(&TotpConfig { period: 0, ..TotpConfig::default() }).is_err());
+}
+
+/// =======================
+/// Challenge Tests
+/// =======================
+
+#[tokio::test]
+async fn challenge_is_locked_after_max_attempts() {
+    let user_id = totp_user("totp-attempts@test.com").await;
+    let max_attempts = load_config().unwrap().totp.max_attempts;
+    let (challenge, _) = create_token(user_id, TokenType::Challenge, None).unwrap();
+
+    for _ in 0..max_attempts {
+        assert!(matches!(try_login(&challenge, "000000").await, Err(AppError::Unauthorized)));
+    }
+    //kode benar pun ditolak setelah batas percobaan habis
+    assert!(matches!(try_login(&challenge, &current_code()).await, Err(AppError::Unauthorized)));
+}
+
+#[tokio::test]
+async fn challenge_can_only_be_used_once() {
+    let user_id = totp_user("totp-once@test.com").await;
+    let (challenge, _) = create_token(user_id, TokenType::Challenge, None).unwrap();
+
+    assert_eq!(try_login(&challenge, &current_code()).await.unwrap(), StatusCode::OK);
+
+    let pool = db::get_pool().await.unwrap();
+    sqlx::query("UPDATE users SET totp_last_step = NULL WHERE id = ?").bind(user_id).execute(&pool).await.unwrap();
+    assert!(matches!(try_login(&challenge, &current_code()).await, Err(AppError::Unauthorized)));
+}
@@ -0,0 +1,150 @@
+use axum::{Router, routing::{get, patch, put}};
+use axum_test::TestServer;
+use http::{HeaderMap, HeaderValue, StatusCode, header};
+use serde_json::json;
+
+use crate::{
+    configs::db,
+    controllers::user_controller::{edit_user, get_user_edit, patch_user},
+    models::user_model::UserPatch,
+    tests::common::{api_key, protected_server, session_cookie},
+    utils::etag::{IfMatch, if_match},
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn server() -> TestServer {
+    protected_server(
+        Router::new()
+            .route("/user/", get(get_user_edit))
+            .route("/user/{id}", put(edit_user))
+            .route("/user/{id}", patch(patch_user))
+    )
+}
+
+async fn insert_user(email: &str) -> u64 {
+    let pool = db::get_pool().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE email = ?").bind(email).execute(&pool).await.unwrap();
+    sqlx::query("INSERT INTO users (name, email, password) VALUES (?, ?, ?)")
+        .bind("PatchUser")
+        .bind(email)
+        .bind("123456")
+        .execute(&pool)
+        .await
+        .unwrap()
+        .last_insert_id()
+}
+
+fn if_match_header(value: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::IF_MATCH, HeaderValue::from_str(value).unwrap());
+    headers
+}
+
+/// =======================
+/// If-Match Parsing Tests
+/// =======================
+
+#[test]
+fn missing_if_match_is_unconditional() {
+    assert_eq!(if_match(&HeaderMap::new()), None);
+}
+
+#[test]
+fn if_match_star_matches_any_etag() {
+    let condition = if_match(&if_match_header("*")).unwrap();
+    assert_eq!(condition, IfMatch::Any);
+    assert!(condition.matches("\"1-1700000000000\""));
+}
+
+#[test]
+fn if_match_list_uses_strong_comparison() {
+    let condition = if_match(&if_match_header("\"1-100\", W/\"1-200\"")).unwrap();
+    assert!(condition.matches("\"1-100\""));
+    assert!(!condition.matches("\"1-200\""));
+    assert!(!condition.matches("\"1-300\""));
+}
+
+/// =======================
+/// Merge Patch Body Tests
+/// =======================
+
+#[test]
+fn patch_body_fields_are_optional() {
+    let patch: UserPatch = serde_json::from_value(json!({ "name": "Budi Baru" })).unwrap();
+    assert_eq!(patch.name.as_deref(), Some("Budi Baru"));
+    assert_eq!(patch.email, None);
+}
+
+#[test]
+fn patch_body_rejects_null_and_unknown_fields() {
+    assert!(serde_json::from_value::<UserPatch>(json!({ "email": null })).is_err());
+    assert!(serde_json::from_value::<UserPatch>(json!({ "password": "rahasia" })).is_err());
+}
+
+/// =======================
+/// PATCH /user/{id} Tests
+/// =======================
+
+#[tokio::test]
+async fn patch_updates_only_sent_fields_and_returns_etag() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let user_id = insert_user("patch-partial@test.com").await;
+
+    let res = server.patch(&format!("/user/{}", user_id)).add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({ "name": "Nama Baru" })).await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+    assert!(res.headers().contains_key(header::ETAG));
+
+    let body: serde_json::Value = res.json();
+    assert_eq!(body["name"], "Nama Baru");
+    assert_eq!(body["email"], "patch-partial@test.com");
+}
+
+#[tokio::test]
+async fn stale_if_match_is_rejected_with_412() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let user_id = insert_user("patch-etag@test.com").await;
+
+    let res = server.get("/user/").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("id", user_id.to_string()).await;
+    let etag = res.headers().get(header::ETAG).unwrap().to_str().unwrap().to_string();
+
+    let first = server.patch(&format!("/user/{}", user_id)).add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_header("If-Match", &etag).json(&json!({ "name": "Update Pertama" })).await;
+    assert_eq!(first.status_code(), StatusCode::OK);
+
+    let second = server.patch(&format!("/user/{}", user_id)).add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_header("If-Match", &etag).json(&json!({ "name": "Update Kedua" })).await;
+    assert_eq!(second.status_code(), StatusCode::PRECONDITION_FAILED);
+}
+
+#[tokio::test]
+async fn update_to_taken_email_is_conflict() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    insert_user("patch-taken@test.com").await;
+    let user_id = insert_user("patch-owner@test.com").await;
+
+    let res = server.patch(&format!("/user/{}", user_id)).add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({ "email": "patch-taken@test.com" })).await;
+    assert_eq!(res.status_code(), StatusCode::CONFLICT);
+
+    let res = server.put(&format!("/user/{}", user_id)).add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({ "name": "PatchUser", "email": "patch-taken@test.com" })).await;
+    assert_eq!(res.status_code(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn update_unknown_user_is_not_found() {
+    let server = server();
+    let admin = session_cookie(1).await;
+
+    let res = server.patch("/user/999999999").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .json(&json!({ "name": "Tidak Ada" })).await;
+    assert_eq!(res.status_code(), StatusCode::NOT_FOUND);
+}
@@ -10,16 +10,13 @@ use crate::{
     controllers::user_controller::{delete_user, edit_user, get_all_user, get_user, get_user_edit, insert_user, login_user},
     middlewares::api_middleware::{api_key_middleware},
     routes::fallback::{fallback, not_allowed},
-    utils::utils::create_jwt
+    tests::common::{api_key, login_session, session_cookie},
+    utils::{client_info::ClientInfo, session::revoke_session}
 };
 
-/// =======================
-/// Helper Functions
-/// =======================
-
-fn api_key() -> &'static str {
-    "hgdshdfrhdrhdftjdftjfdtjdf"
-}
+// =======================
+// Helper Functions
+// =======================
 
 fn app() -> Router {
     Router::new()
@@ -58,8 +55,10 @@ async fn cleanup_users() {
         .unwrap();
 }
 
-async fn get_jwt(user_id: u64) -> String {
-    create_jwt(user_id).unwrap()
+// token dengan session tercatat di db, untuk route yang melewati check_login atau check_guest
+async fn get_session_jwt(user_id: u64) -> String {
+    let cookie = session_cookie(user_id).await;
+    cookie.trim_start_matches("jwt=").to_string()
 }
 
 /// =======================
@@ -79,7 +78,7 @@ async fn guest_can_access_login() {
 #[tokio::test]
 async fn logged_in_cannot_access_login() {
     let server = guest_server();
-    let token = get_jwt(1).await;
+    let token = get_session_jwt(1).await;
     let cookie = format!("jwt={}", token);
 
     let res = server.post("/login")
@@ -91,6 +90,26 @@ async fn logged_in_cannot_access_login() {
     assert_eq!(res.status_code(), StatusCode::FORBIDDEN);
 }
 
+#[tokio::test]
+async fn revoked_session_cookie_can_reach_login() {
+    let server = guest_server();
+    let (sid, cookie) = login_session(1, &ClientInfo::default()).await;
+    let pool = db::get_pool().await.unwrap();
+    assert!(revoke_session(&pool, 1, &sid).await.unwrap());
+
+    let res = server.post("/login")
+        .add_header("X-API-KEY", api_key())
+        .add_header("Cookie", &cookie)
+        .json(&json!({"email": "guest@test.com","password": "123456"}))
+        .await;
+
+    assert_ne!(res.status_code(), StatusCode::FORBIDDEN);
+    //cookie lama dihapus agar browser tidak terus mengirimnya
+    let cleared = res.headers().get_all("set-cookie").iter()
+        .any(|v| v.to_str().is_ok_and(|v| v.starts_with("jwt=") && v.contains("Max-Age=0")));
+    assert!(cleared);
+}
+
 /// =======================
 /// GET /user Tests (all combinations)
 /// =======================
@@ -114,7 +133,7 @@ async fn get_user_api_key_no_token() {
 #[tokio::test]
 async fn get_user_no_api_token() {
     let server = server();
-    let token = get_jwt(1).await;
+    let token = get_session_jwt(1).await;
     let cookie = format!("jwt={}", token);
     let res = server.get("/user")
         .add_header("Cookie", &cookie)
@@ -126,7 +145,7 @@ async fn get_user_no_api_token() {
 async fn get_user_api_key_valid_token() {
     cleanup_users().await;
     let server = server();
-    let token = get_jwt(1).await;
+    let token = get_session_jwt(1).await;
     let cookie = format!("jwt={}", token);
 
     let res = server.get("/user")
@@ -166,7 +185,7 @@ async fn insert_user_api_key_no_token() {
 async fn insert_user_no_api_token() {
     cleanup_users().await;
     let server = server();
-    let token = get_jwt(1).await;
+    let token = get_session_jwt(1).await;
     let cookie = format!("jwt={}", token);
     let res = server.post("/user")
         .add_header("Cookie", &cookie)
@@ -179,7 +198,7 @@ async fn insert_user_no_api_token() {
 async fn insert_user_api_key_valid_token() {
     cleanup_users().await;
     let server = server();
-    let token = get_jwt(1).await;
+    let token = get_session_jwt(1).await;
     let cookie = format!("jwt={}", token);
 
     let res = server.post("/user")
@@ -200,7 +219,7 @@ async fn insert_user_api_key_valid_token() {
 async fn delete_user_api_key_valid_token() {
     cleanup_users().await;
     let server = server();
-    let token = get_jwt(1).await;
+    let token = get_session_jwt(1).await;
     let cookie = format!("jwt={}", token);
 
     // Insert dulu
@@ -217,7 +236,7 @@ async fn delete_user_api_key_valid_token() {
     let res = server.delete("/user")
         .add_header("X-API-KEY", api_key())
         .add_header("Cookie", &cookie)
-        .add_query_param("id", &user_id.to_string())
+        .add_query_param("id", user_id.to_string())
         .await;
 
     assert_eq!(res.status_code(), StatusCode::NO_CONTENT);
@@ -232,7 +251,7 @@ async fn delete_user_api_key_valid_token() {
 async fn edit_user_api_key_valid_token() {
     cleanup_users().await;
     let server = server();
-    let token = get_jwt(1).await;
+    let token = get_session_jwt(1).await;
     let cookie = format!("jwt={}", token);
 
     let pool = db::get_pool().await.unwrap();
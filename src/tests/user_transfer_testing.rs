@@ -0,0 +1,159 @@
+use axum::{Router, routing::{get, post}};
+use axum_test::TestServer;
+use chrono::{TimeZone, Utc};
+use http::StatusCode;
+use serde_json::Value;
+
+use crate::{
+    configs::db,
+    controllers::user_transfer_controller::{export_header, export_row, export_users, import_users},
+    models::{transfer_model::TransferFormat, user_model::User},
+    tests::common::{api_key, protected_server, session_cookie},
+    utils::{
+        csv::{escape_field, is_incomplete, parse_record},
+    },
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn server() -> TestServer {
+    protected_server(
+        Router::new()
+            .route("/users/import", post(import_users))
+            .route("/users/export", get(export_users))
+    )
+}
+
+async fn delete_emails(emails: &[&str]) {
+    let pool = db::get_pool().await.unwrap();
+    for email in emails {
+        sqlx::query("DELETE FROM users WHERE email = ?").bind(email).execute(&pool).await.unwrap();
+    }
+}
+
+async fn count_emails(emails: &[&str]) -> usize {
+    let pool = db::get_pool().await.unwrap();
+    let mut count = 0;
+    for email in emails {
+        let (n,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users WHERE email = ?").bind(email).fetch_one(&pool).await.unwrap();
+        count += n as usize;
+    }
+    count
+}
+
+/// =======================
+/// CSV Tests
+/// =======================
+
+#[test]
+fn csv_records_support_quotes() {
+    assert_eq!(parse_record("Budi,budi@corp.com,rahasia").unwrap(), vec!["Budi", "budi@corp.com", "rahasia"]);
+    assert_eq!(parse_record("\"Santoso, Budi\",\"a \"\"b\"\"\",").unwrap(), vec!["Santoso, Budi", "a \"b\"", ""]);
+    assert!(parse_record("\"Budi\"x,budi@corp.com").is_err());
+    assert!(parse_record("Bu\"di,budi@corp.com").is_err());
+}
+
+#[test]
+fn csv_record_with_open_quote_continues_on_next_line() {
+    assert!(is_incomplete("\"Budi"));
+    assert!(!is_incomplete("\"Budi\nSantoso\",budi@corp.com"));
+}
+
+#[test]
+fn export_rows_escape_values_and_never_include_password() {
+    let at = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    let user = User { id: 7, name: "Santoso, Budi".into(), email: "budi@corp.com".into(), password: Some("hash".into()), created_at: at, updated_at: at, deleted_at: None, avatar_url: None, attributes: Default::default() };
+
+    assert_eq!(export_header(TransferFormat::Csv), "id,name,email,created_at,updated_at\r\n");
+    assert_eq!(export_row(TransferFormat::Csv, &user).unwrap(), "7,\"Santoso, Budi\",budi@corp.com,2025-01-01T00:00:00+00:00,2025-01-01T00:00:00+00:00\r\n");
+
+    let line = export_row(TransferFormat::Ndjson, &user).unwrap();
+    assert!(line.ends_with('\n'));
+    let json: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(json["id"], 7);
+    assert!(json.get("password").is_none());
+    assert_eq!(escape_field("plain"), "plain");
+}
+
+/// =======================
+/// Import Tests
+/// =======================
+
+#[tokio::test]
+async fn import_requires_csv_or_ndjson_content_type() {
+    let server = server();
+    let admin = session_cookie(1).await;
+
+    let res = server.post("/users/import").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .content_type("application/json")
+        .text("[]")
+        .await;
+    assert_eq!(res.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn csv_dry_run_reports_each_row_without_creating_users() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let emails = ["import-a@corp.test.com", "import-b@corp.test.com"];
+    delete_emails(&emails).await;
+
+    let csv = "email,name,password\r\nimport-a@corp.test.com,Import A,Rahasia-Panjang-123\r\nimport-b@corp.test.com,B,Rahasia-Panjang-123\r\nimport-a@corp.test.com,Import A Lagi,Rahasia-Panjang-123\r\n";
+    let res = server.post("/users/import").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("dry_run", "true")
+        .content_type("text/csv")
+        .text(csv)
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+
+    let body: Value = res.json();
+    assert_eq!(body["committed"], false);
+    assert_eq!(body["total"], 3);
+    assert_eq!(body["rows"][0]["status"], "valid");
+    assert_eq!(body["rows"][1]["status"], "failed");
+    assert!(body["rows"][1]["errors"][0].as_str().unwrap().starts_with("name:"));
+    assert_eq!(body["rows"][2]["status"], "failed");
+    assert_eq!(count_emails(&emails).await, 0);
+}
+
+#[tokio::test]
+async fn atomic_import_rolls_back_when_any_row_fails() {
+    let server = server();
+    let admin = session_cookie(1).await;
+    let emails = ["import-atomic@corp.test.com"];
+    delete_emails(&emails).await;
+
+    let ndjson = "{\"name\":\"Import Atomic\",\"email\":\"import-atomic@corp.test.com\",\"password\":\"Rahasia-Panjang-123\"}\n{\"name\":\"Rusak\"}\n";
+    let res = server.post("/users/import").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("atomic", "true")
+        .content_type("application/x-ndjson")
+        .text(ndjson)
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+
+    let body: Value = res.json();
+    assert_eq!(body["committed"], false);
+    assert_eq!(body["created"], 0);
+    assert_eq!(body["rows"][0]["status"], "rolled_back");
+    assert_eq!(body["rows"][1]["status"], "failed");
+    assert_eq!(count_emails(&emails).await, 0);
+}
+
+/// =======================
+/// Export Tests
+/// =======================
+
+#[tokio::test]
+async fn export_streams_csv_with_header() {
+    let server = server();
+    let admin = session_cookie(1).await;
+
+    let res = server.get("/users/export").add_header("X-API-KEY", api_key()).add_header("Cookie", &admin)
+        .add_query_param("format", "csv")
+        .await;
+    assert_eq!(res.status_code(), StatusCode::OK);
+    assert!(res.header("content-type").to_str().unwrap().starts_with("text/csv"));
+    assert!(res.text().starts_with("id,name,email,created_at,updated_at\r\n"));
+}
@@ -0,0 +1,103 @@
+use axum::{body::Body, extract::Request, middleware::from_fn, response::Response};
+use chrono::{TimeZone, Utc};
+use http::{HeaderMap, HeaderValue, StatusCode, Uri, header};
+use tower::{Layer, ServiceExt};
+
+use crate::{
+    errors::app_error::AppError,
+    middlewares::version_middleware::{accept_version, negotiate_version, versioned_uri},
+    models::{user_model::User, user_v2_model::UserV2},
+    routes::api_route,
+};
+
+// =======================
+// Helper Functions
+// =======================
+
+fn accept(value: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::ACCEPT, HeaderValue::from_str(value).unwrap());
+    headers
+}
+
+async fn send(path: &str, accept: Option<&str>) -> Response {
+    let app = from_fn(negotiate_version).layer(api_route());
+    let mut req = Request::builder().uri(path);
+    if let Some(accept) = accept {
+        req = req.header(header::ACCEPT, accept);
+    }
+    app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+/// =======================
+/// Accept Negotiation Tests
+/// =======================
+
+#[test]
+fn accept_without_version_is_unversioned() {
+    assert_eq!(accept_version(&HeaderMap::new()).unwrap(), None);
+    assert_eq!(accept_version(&accept("application/json")).unwrap(), None);
+}
+
+#[test]
+fn accept_version_parameter_is_parsed() {
+    assert_eq!(accept_version(&accept("application/json; version=2")).unwrap(), Some(2));
+    assert_eq!(accept_version(&accept("text/html, application/json;q=0.9;version=\"v1\"")).unwrap(), Some(1));
+}
+
+#[test]
+fn unsupported_version_is_not_acceptable() {
+    assert!(matches!(accept_version(&accept("application/json; version=9")), Err(AppError::NotAcceptable)));
+    assert!(matches!(accept_version(&accept("application/json; version=latest")), Err(AppError::NotAcceptable)));
+}
+
+#[test]
+fn versioned_uri_keeps_query() {
+    let uri: Uri = "/users?q=budi".parse().unwrap();
+    assert_eq!(versioned_uri(&uri, 2).unwrap(), "/api/v2/users?q=budi");
+}
+
+/// =======================
+/// V2 Model Tests
+/// =======================
+
+#[test]
+fn v2_user_has_string_id_and_rfc3339_timestamps() {
+    let at = Utc.with_ymd_and_hms(2026, 10, 19, 8, 30, 0).unwrap();
+    let user = User { id: 42, name: "Budi".into(), email: "budi@test.com".into(), password: None, created_at: at, updated_at: at, deleted_at: None, avatar_url: None, attributes: Default::default() };
+    let json = serde_json::to_value(UserV2::from(user)).unwrap();
+
+    assert_eq!(json["id"], "42");
+    assert_eq!(json["created_at"], "2026-10-19T08:30:00Z");
+    assert!(json.get("deleted_at").is_none());
+}
+
+/// =======================
+/// Routing Tests
+/// =======================
+
+#[tokio::test]
+async fn versioned_prefixes_are_mounted() {
+    assert_eq!(send("/api/v1/users", None).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(send("/api/v2/users", None).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(send("/users", None).await.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn legacy_aliases_only_exist_without_prefix() {
+    assert_eq!(send("/user/", None).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(send("/api/v1/user/", None).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn accept_header_selects_version_for_unprefixed_path() {
+    let res = send("/user/", Some("application/json; version=2")).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert!(res.headers().get_all(header::VARY).iter().any(|v| v == "accept"));
+
+    let res = send("/user/", Some("application/json; version=1")).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = send("/users", Some("application/json; version=3")).await;
+    assert_eq!(res.status(), StatusCode::NOT_ACCEPTABLE);
+}
@@ -0,0 +1,246 @@
+use ciborium::Value;
+use p256::ecdsa::{Signature, SigningKey, signature::Signer};
+use rand::rngs::OsRng;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    errors::app_error::AppError,
+    models::config_model::WebauthnConfig,
+    utils::webauthn::{generate_challenge, verify_assertion, verify_registration},
+};
+
+// =======================
+// Software Authenticator
+// =======================
+
+const ORIGIN: &str = "http://localhost:3000";
+const FLAG_UP: u8 = 0x01;
+const FLAG_UV: u8 = 0x04;
+const FLAG_AT: u8 = 0x40;
+
+enum Key {
+    P256(SigningKey),
+    Ed25519(ed25519_dalek::SigningKey),
+}
+
+struct Authenticator {
+    key: Key,
+    credential_id: Vec<u8>,
+    rp_id: String,
+    flags: u8,
+    counter: u32,
+}
+
+fn cbor(value: Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::into_writer(&value, &mut out).unwrap();
+    out
+}
+
+fn int(v: i64) -> Value {
+    Value::Integer(v.into())
+}
+
+impl Authenticator {
+    fn p256() -> Self {
+        Self::with_key(Key::P256(SigningKey::random(&mut OsRng)))
+    }
+
+    fn ed25519() -> Self {
+        Self::with_key(Key::Ed25519(ed25519_dalek::SigningKey::from_bytes(&[7u8; 32])))
+    }
+
+    fn with_key(key: Key) -> Self {
+        Self { key, credential_id: vec![1, 2, 3, 4], rp_id: "localhost".to_string(), flags: FLAG_UP | FLAG_UV, counter: 0 }
+    }
+
+    fn cose_key(&self) -> Vec<u8> {
+        match &self.key {
+            Key::P256(key) => {
+                let point = key.verifying_key().to_encoded_point(false);
+                cbor(Value::Map(vec![
+                    (int(1), int(2)),
+                    (int(3), int(-7)),
+                    (int(-1), int(1)),
+                    (int(-2), Value::Bytes(point.x().unwrap().to_vec())),
+                    (int(-3), Value::Bytes(point.y().unwrap().to_vec())),
+                ]))
+            }
+            Key::Ed25519(key) => cbor(Value::Map(vec![
+                (int(1), int(1)),
+                (int(3), int(-8)),
+                (int(-1), int(6)),
+                (int(-2), Value::Bytes(key.verifying_key().to_bytes().to_vec())),
+            ])),
+        }
+    }
+
+    fn auth_data(&self, attested: bool) -> Vec<u8> {
+        let mut data = Sha256::digest(self.rp_id.as_bytes()).to_vec();
+        data.push(if attested { self.flags | FLAG_AT } else { self.flags });
+        data.extend_from_slice(&self.counter.to_be_bytes());
+        if attested {
+            data.extend_from_slice(&[0u8; 16]);
+            data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+            data.extend_from_slice(&self.credential_id);
+            data.extend_from_slice(&self.cose_key());
+        }
+        data
+    }
+
+    fn sign(&self, auth_data: &[u8], client_data: &[u8]) -> Vec<u8> {
+        let mut message = auth_data.to_vec();
+        message.extend_from_slice(&Sha256::digest(client_data));
+        match &self.key {
+            Key::P256(key) => {
+                let sig: Signature = key.sign(&message);
+                sig.to_der().as_bytes().to_vec()
+            }
+            Key::Ed25519(key) => key.sign(&message).to_bytes().to_vec(),
+        }
+    }
+
+    //navigator.credentials.create: (clientDataJSON, attestationObject)
+    fn create(&self, challenge: &str, fmt: &str) -> (Vec<u8>, Vec<u8>) {
+        let client_data = json!({"type": "webauthn.create", "challenge": challenge, "origin": ORIGIN}).to_string().into_bytes();
+        let auth_data = self.auth_data(true);
+        let att_stmt = match fmt {
+            "packed" => Value::Map(vec![
+                (Value::Text("alg".into()), int(-7)),
+                (Value::Text("sig".into()), Value::Bytes(self.sign(&auth_data, &client_data))),
+            ]),
+            _ => Value::Map(vec![]),
+        };
+        let attestation = cbor(Value::Map(vec![
+            (Value::Text("fmt".into()), Value::Text(fmt.into())),
+            (Value::Text("attStmt".into()), att_stmt),
+            (Value::Text("authData".into()), Value::Bytes(auth_data)),
+        ]));
+        (client_data, attestation)
+    }
+
+    //navigator.credentials.get: (clientDataJSON, authenticatorData, signature)
+    fn get(&self, challenge: &str, origin: &str) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let client_data = json!({"type": "webauthn.get", "challenge": challenge, "origin": origin}).to_string().into_bytes();
+        let auth_data = self.auth_data(false);
+        let signature = self.sign(&auth_data, &client_data);
+        (client_data, auth_data, signature)
+    }
+}
+
+fn register(cfg: &WebauthnConfig, authenticator: &Authenticator) -> Vec<u8> {
+    let challenge = generate_challenge();
+    let (client_data, attestation) = authenticator.create(&challenge, "none");
+    verify_registration(cfg, &challenge, &client_data, &attestation).unwrap().public_key
+}
+
+/// =======================
+/// Registration Tests
+/// =======================
+
+#[test]
+fn registration_with_none_attestation() {
+    let cfg = WebauthnConfig::default();
+    let authenticator = Authenticator::p256();
+    let challenge = generate_challenge();
+    let (client_data, attestation) = authenticator.create(&challenge, "none");
+
+    let credential = verify_registration(&cfg, &challenge, &client_data, &attestation).unwrap();
+    assert_eq!(credential.credential_id, vec![1, 2, 3, 4]);
+    assert_eq!(credential.public_key, authenticator.cose_key());
+}
+
+#[test]
+fn registration_with_packed_self_attestation() {
+    let cfg = WebauthnConfig::default();
+    let challenge = generate_challenge();
+    let (client_data, attestation) = Authenticator::p256().create(&challenge, "packed");
+
+    assert!(verify_registration(&cfg, &challenge, &client_data, &attestation).is_ok());
+}
+
+#[test]
+fn registration_rejects_wrong_challenge_origin_or_rp() {
+    let cfg = WebauthnConfig::default();
+    let challenge = generate_challenge();
+    let (client_data, attestation) = Authenticator::p256().create(&challenge, "none");
+    assert!(matches!(verify_registration(&cfg, &generate_challenge(), &client_data, &attestation), Err(AppError::Unauthorized)));
+
+    let other_origin = WebauthnConfig { origins: vec!["https://evil.test".to_string()], ..WebauthnConfig::default() };
+    assert!(verify_registration(&other_origin, &challenge, &client_data, &attestation).is_err());
+
+    let mut authenticator = Authenticator::p256();
+    authenticator.rp_id = "evil.test".to_string();
+    let (client_data, attestation) = authenticator.create(&challenge, "none");
+    assert!(verify_registration(&cfg, &challenge, &client_data, &attestation).is_err());
+}
+
+#[test]
+fn registration_requires_user_verification_when_configured() {
+    let cfg = WebauthnConfig { require_user_verification: true, ..WebauthnConfig::default() };
+    let mut authenticator = Authenticator::p256();
+    authenticator.flags = FLAG_UP;
+    let challenge = generate_challenge();
+    let (client_data, attestation) = authenticator.create(&challenge, "none");
+
+    assert!(verify_registration(&cfg, &challenge, &client_data, &attestation).is_err());
+}
+
+/// =======================
+/// Assertion Tests
+/// =======================
+
+#[test]
+fn assertion_roundtrip_p256_and_ed25519() {
+    let cfg = WebauthnConfig::default();
+    for mut authenticator in [Authenticator::p256(), Authenticator::ed25519()] {
+        let public_key = register(&cfg, &authenticator);
+        authenticator.counter = 5;
+
+        let challenge = generate_challenge();
+        let (client_data, auth_data, signature) = authenticator.get(&challenge, ORIGIN);
+        let counter = verify_assertion(&cfg, &challenge, &public_key, 0, &client_data, &auth_data, &signature).unwrap();
+        assert_eq!(counter, 5);
+    }
+}
+
+#[test]
+fn assertion_with_tampered_signature_is_rejected() {
+    let cfg = WebauthnConfig::default();
+    let authenticator = Authenticator::p256();
+    let public_key = register(&cfg, &authenticator);
+
+    let challenge = generate_challenge();
+    let (client_data, mut auth_data, signature) = authenticator.get(&challenge, ORIGIN);
+    auth_data[33..37].copy_from_slice(&9u32.to_be_bytes());
+
+    assert!(verify_assertion(&cfg, &challenge, &public_key, 0, &client_data, &auth_data, &signature).is_err());
+}
+
+#[test]
+fn assertion_from_other_origin_is_rejected() {
+    let cfg = WebauthnConfig::default();
+    let authenticator = Authenticator::p256();
+    let public_key = register(&cfg, &authenticator);
+
+    let challenge = generate_challenge();
+    let (client_data, auth_data, signature) = authenticator.get(&challenge, "https://phishing.test");
+    assert!(verify_assertion(&cfg, &challenge, &public_key, 0, &client_data, &auth_data, &signature).is_err());
+}
+
+#[test]
+fn sign_counter_must_increase() {
+    let cfg = WebauthnConfig::default();
+    let mut authenticator = Authenticator::p256();
+    let public_key = register(&cfg, &authenticator);
+
+    let challenge = generate_challenge();
+    let (client_data, auth_data, signature) = authenticator.get(&challenge, ORIGIN);
+    assert!(verify_assertion(&cfg, &challenge, &public_key, 0, &client_data, &auth_data, &signature).is_ok());
+
+    authenticator.counter = 3;
+    let (client_data, auth_data, signature) = authenticator.get(&challenge, ORIGIN);
+    assert!(verify_assertion(&cfg, &challenge, &public_key, 3, &client_data, &auth_data, &signature).is_err());
+    assert_eq!(verify_assertion(&cfg, &challenge, &public_key, 2, &client_data, &auth_data, &signature).unwrap(), 3);
+}
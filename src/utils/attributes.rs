@@ -0,0 +1,236 @@
+use std::{borrow::Cow, collections::BTreeMap};
+use chrono::NaiveDate;
+use regex::{Regex, RegexBuilder};
+use serde_json::{Map, Value};
+use sqlx::MySqlExecutor;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::{
+    errors::app_error::AppError,
+    models::attribute_model::{AttributeDefinition, AttributeDefinitionInput, AttributeKind, AttributeValues},
+};
+
+pub const MAX_NAME_LEN: usize = 64;
+//batas ukuran regex terkompilasi agar pattern dari admin tidak menghabiskan memori
+const PATTERN_SIZE_LIMIT: usize = 64 * 1024;
+
+pub async fn load_definitions<'e>(executor: impl MySqlExecutor<'e>) -> Result<Vec<AttributeDefinition>, AppError> {
+    Ok(sqlx::query_as::<_, AttributeDefinition>("SELECT * FROM attribute_definitions ORDER BY name")
+        .fetch_all(executor)
+        .await?)
+}
+
+//nama atribut menjadi key JSON dan nama field pencarian (attr.<name>), jadi dibatasi huruf kecil, angka dan _
+pub fn is_valid_name(name: &str) -> bool {
+    name.len() <= MAX_NAME_LEN
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+//pattern dicocokkan dengan seluruh nilai, bukan sebagian
+fn compile_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(&format!("^(?:{})$", pattern)).size_limit(PATTERN_SIZE_LIMIT).build()
+}
+
+fn add_error(errors: &mut ValidationErrors, field: String, code: &'static str, message: String) {
+    let entry = errors.errors_mut().entry(Cow::Owned(field)).or_insert_with(|| ValidationErrorsKind::Field(Vec::new()));
+    if let ValidationErrorsKind::Field(list) = entry {
+        list.push(ValidationError::new(code).with_message(Cow::Owned(message)));
+    }
+}
+
+fn result(errors: ValidationErrors) -> Result<(), ValidationErrors> {
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+// =======================
+// Definisi atribut
+// =======================
+
+//aturan harus cocok dengan tipe-nya, misal min_length hanya untuk string
+pub fn build_definition(name: &str, input: AttributeDefinitionInput) -> Result<AttributeDefinition, ValidationErrors> {
+    let mut errors = input.validate().err().unwrap_or_default();
+    if !is_valid_name(name) {
+        add_error(&mut errors, "name".to_string(), "attribute_name", format!("Nama hanya huruf kecil, angka dan _, maksimal {} karakter", MAX_NAME_LEN));
+    }
+
+    let is_text = input.kind == AttributeKind::String;
+    let is_numeric = matches!(input.kind, AttributeKind::Integer | AttributeKind::Number);
+    let misplaced = [
+        ("min_length", input.min_length.is_some() && !is_text),
+        ("max_length", input.max_length.is_some() && !is_text),
+        ("pattern", input.pattern.is_some() && !is_text),
+        ("choices", input.choices.is_some() && !is_text),
+        ("minimum", input.minimum.is_some() && !is_numeric),
+        ("maximum", input.maximum.is_some() && !is_numeric),
+    ];
+    for (field, invalid) in misplaced {
+        if invalid {
+            add_error(&mut errors, field.to_string(), "attribute_rule", format!("{} tidak berlaku untuk tipe {}", field, input.kind.as_str()));
+        }
+    }
+
+    if let (Some(min), Some(max)) = (input.min_length, input.max_length) && min > max {
+        add_error(&mut errors, "min_length".to_string(), "attribute_range", "min_length lebih besar dari max_length".to_string());
+    }
+    if let (Some(min), Some(max)) = (input.minimum, input.maximum) && min > max {
+        add_error(&mut errors, "minimum".to_string(), "attribute_range", "minimum lebih besar dari maximum".to_string());
+    }
+    if [input.minimum, input.maximum].iter().flatten().any(|v| !v.is_finite()) {
+        add_error(&mut errors, "minimum".to_string(), "attribute_range", "minimum dan maximum harus angka biasa".to_string());
+    }
+    if let Some(pattern) = input.pattern.as_deref() && let Err(e) = compile_pattern(pattern) {
+        add_error(&mut errors, "pattern".to_string(), "attribute_pattern", format!("Regex tidak valid: {}", e));
+    }
+    if let Some(choices) = input.choices.as_ref() {
+        let mut sorted: Vec<&String> = choices.iter().collect();
+        sorted.sort();
+        sorted.dedup();
+        if sorted.len() != choices.len() || choices.iter().any(|c| c.trim().is_empty()) {
+            add_error(&mut errors, "choices".to_string(), "attribute_choices", "Choices harus unik dan tidak kosong".to_string());
+        }
+    }
+    result(errors)?;
+
+    Ok(AttributeDefinition {
+        name: name.to_string(),
+        label: input.label.trim().to_string(),
+        kind: input.kind,
+        required: input.required,
+        min_length: input.min_length,
+        max_length: input.max_length,
+        pattern: input.pattern,
+        minimum: input.minimum,
+        maximum: input.maximum,
+        choices: input.choices,
+    })
+}
+
+// =======================
+// Nilai atribut
+// =======================
+
+fn check_value(def: &AttributeDefinition, value: &Value) -> Result<Value, String> {
+    match (def.kind, value) {
+        (AttributeKind::String, Value::String(text)) => {
+            let text = text.trim();
+            let len = text.chars().count() as u32;
+            if let Some(min) = def.min_length && len < min {
+                return Err(format!("{} minimal {} karakter", def.label, min));
+            }
+            if let Some(max) = def.max_length && len > max {
+                return Err(format!("{} maksimal {} karakter", def.label, max));
+            }
+            //pattern sudah dicek saat definisi disimpan, pattern rusak di database dianggap tidak cocok
+            if let Some(pattern) = def.pattern.as_deref() && !compile_pattern(pattern).is_ok_and(|re| re.is_match(text)) {
+                return Err(format!("Format {} tidak valid", def.label));
+            }
+            if let Some(choices) = def.choices.as_ref() && !choices.iter().any(|c| c == text) {
+                return Err(format!("{} harus salah satu dari: {}", def.label, choices.join(", ")));
+            }
+            Ok(Value::String(text.to_string()))
+        }
+        (AttributeKind::Integer, Value::Number(n)) if n.is_i64() || n.is_u64() => check_range(def, n.as_f64().unwrap_or_default(), value),
+        (AttributeKind::Number, Value::Number(n)) => check_range(def, n.as_f64().unwrap_or_default(), value),
+        (AttributeKind::Boolean, Value::Bool(_)) => Ok(value.clone()),
+        (AttributeKind::Date, Value::String(text)) => NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+            .map(|date| Value::String(date.format("%Y-%m-%d").to_string()))
+            .map_err(|_| format!("{} harus tanggal YYYY-MM-DD", def.label)),
+        (kind, _) => Err(format!("{} harus bertipe {}", def.label, kind.as_str())),
+    }
+}
+
+fn check_range(def: &AttributeDefinition, number: f64, value: &Value) -> Result<Value, String> {
+    if let Some(min) = def.minimum && number < min {
+        return Err(format!("{} minimal {}", def.label, min));
+    }
+    if let Some(max) = def.maximum && number > max {
+        return Err(format!("{} maksimal {}", def.label, max));
+    }
+    Ok(value.clone())
+}
+
+//validasi seperti derive validator di UserInsert, error per atribut di field attributes.<name>;
+//mengembalikan nilai yang sudah dinormalisasi (string di-trim, null dibuang)
+pub fn validate_attributes(defs: &[AttributeDefinition], values: &AttributeValues) -> Result<AttributeValues, ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    let mut clean = BTreeMap::new();
+
+    for (name, value) in values {
+        let Some(def) = defs.iter().find(|d| &d.name == name) else {
+            add_error(&mut errors, format!("attributes.{}", name), "attribute_unknown", format!("Atribut '{}' tidak dikenal", name));
+            continue;
+        };
+        if value.is_null() {
+            continue;
+        }
+        match check_value(def, value) {
+            Ok(value) => {
+                clean.insert(name.clone(), value);
+            }
+            Err(message) => add_error(&mut errors, format!("attributes.{}", name), "attribute_value", message),
+        }
+    }
+    for def in defs.iter().filter(|d| d.required) {
+        if !clean.contains_key(&def.name) && !errors.errors().contains_key(format!("attributes.{}", def.name).as_str()) {
+            add_error(&mut errors, format!("attributes.{}", def.name), "required", format!("{} wajib diisi", def.label));
+        }
+    }
+
+    result(errors)?;
+    Ok(clean)
+}
+
+//JSON Merge Patch untuk atribut: null menghapus atribut, key lain menimpa nilai lama
+pub fn merge_attributes(current: &AttributeValues, patch: &Map<String, Value>) -> AttributeValues {
+    let mut merged = current.clone();
+    for (name, value) in patch {
+        if value.is_null() {
+            merged.remove(name);
+        } else {
+            merged.insert(name.clone(), value.clone());
+        }
+    }
+    merged
+}
+
+//nilai dari kolom CSV diubah sesuai tipe, teks yang tidak bisa diubah dibiarkan agar ditolak validasi
+pub fn parse_attribute_text(kind: AttributeKind, text: &str) -> Value {
+    let text = text.trim();
+    if text.is_empty() {
+        return Value::Null;
+    }
+    match kind {
+        AttributeKind::String | AttributeKind::Date => Value::String(text.to_string()),
+        AttributeKind::Integer => text.parse::<i64>().map(Value::from).unwrap_or_else(|_| Value::String(text.to_string())),
+        AttributeKind::Number => text.parse::<f64>().ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .unwrap_or_else(|| Value::String(text.to_string())),
+        AttributeKind::Boolean => match text.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" => Value::Bool(true),
+            "false" | "0" | "no" => Value::Bool(false),
+            _ => Value::String(text.to_string()),
+        },
+    }
+}
+
+//nilai atribut sebagai teks untuk pencarian, sama dengan JSON_UNQUOTE di MySQL
+pub fn attribute_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+//error derive validator digabung dengan error atribut agar client menerima semuanya sekaligus
+pub fn combine_errors(base: Result<(), ValidationErrors>, attributes: Result<AttributeValues, ValidationErrors>) -> Result<AttributeValues, ValidationErrors> {
+    match (base, attributes) {
+        (Ok(()), attributes) => attributes,
+        (Err(errors), Ok(_)) => Err(errors),
+        (Err(mut errors), Err(more)) => {
+            errors.errors_mut().extend(more.errors().clone());
+            Err(errors)
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use axum::extract::FromRequestParts;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use http::request::Parts;
+use serde_json::{Map, Value, json};
+use sqlx::{MySql, MySqlConnection, Pool};
+
+use crate::{
+    errors::app_error::AppError,
+    models::{
+        audit_model::{ActorKind, AuditAction, AuditEntry, AuditVerification},
+        oauth_model::OAuthClaims,
+        user_model::{Claims, User},
+    },
+    utils::{client_info::ClientInfo, s3::sha256_hex},
+};
+
+//field yang selalu berubah atau tidak bermakna untuk diff
+const IGNORED_FIELDS: [&str; 3] = ["id", "created_at", "updated_at"];
+
+//prev_hash entri pertama
+pub fn genesis_hash() -> String {
+    "0".repeat(64)
+}
+
+//siapa dan dari mana sebuah aksi dilakukan
+#[derive(Debug, Clone)]
+pub struct AuditContext {
+    pub actor_type: ActorKind,
+    pub actor_id: Option<String>,
+    pub client: ClientInfo,
+}
+
+impl AuditContext {
+    pub fn user(user_id: u64, client: &ClientInfo) -> Self {
+        Self { actor_type: ActorKind::User, actor_id: Some(user_id.to_string()), client: client.clone() }
+    }
+}
+
+//aktor diambil dari claims yang dipasang middleware: session user lebih dulu, lalu client OAuth2, sisanya X-API-KEY
+impl<S: Send + Sync> FromRequestParts<S> for AuditContext {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let client = ClientInfo::from_request_parts(parts, state).await?;
+        let (actor_type, actor_id) = if let Some(claims) = parts.extensions.get::<Claims>() {
+            (ActorKind::User, Some(claims.sub.to_string()))
+        } else if let Some(claims) = parts.extensions.get::<OAuthClaims>() {
+            (ActorKind::Client, Some(claims.client_id.clone()))
+        } else {
+            (ActorKind::ApiKey, None)
+        };
+        Ok(Self { actor_type, actor_id, client })
+    }
+}
+
+fn snapshot(user: Option<&User>) -> Map<String, Value> {
+    let mut fields = match user.map(serde_json::to_value) {
+        Some(Ok(Value::Object(fields))) => fields,
+        _ => Map::new(),
+    };
+    for field in IGNORED_FIELDS {
+        fields.remove(field);
+    }
+    fields
+}
+
+//field yang berbeda antara sebelum dan sesudah, None jika tidak ada perubahan
+pub fn user_changes(before: Option<&User>, after: Option<&User>) -> Option<Map<String, Value>> {
+    let before = snapshot(before);
+    let after = snapshot(after);
+    let mut changes = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        let old = before.get(key).unwrap_or(&Value::Null);
+        let new = after.get(key).unwrap_or(&Value::Null);
+        if old != new && !changes.contains_key(key) {
+            changes.insert(key.clone(), json!({ "before": old, "after": new }));
+        }
+    }
+    (!changes.is_empty()).then_some(changes)
+}
+
+//hash dihitung dari JSON dengan key terurut sehingga hasilnya sama setelah dibaca ulang dari kolom JSON MySQL
+pub fn entry_hash(entry: &AuditEntry) -> String {
+    let canonical = json!({
+        "created_at": entry.created_at.timestamp_millis(),
+        "action": entry.action.as_str(),
+        "actor_type": entry.actor_type.as_str(),
+        "actor_id": entry.actor_id,
+        "target_id": entry.target_id,
+        "changes": entry.changes,
+        "ip_address": entry.ip_address,
+        "user_agent": entry.user_agent,
+        "prev_hash": entry.prev_hash,
+    });
+    sha256_hex(canonical.to_string().as_bytes())
+}
+
+//harus dijalankan di transaksi yang sama dengan perubahannya, ujung rantai dikunci sampai commit
+pub async fn append(conn: &mut MySqlConnection, ctx: &AuditContext, action: AuditAction, target_id: Option<u64>, changes: Option<Map<String, Value>>) -> Result<(), AppError> {
+    let (prev_hash,): (String,) = sqlx::query_as("SELECT last_hash FROM audit_chain WHERE id = 1 FOR UPDATE")
+        .fetch_one(&mut *conn)
+        .await?;
+
+    //presisi milidetik sama dengan kolom DATETIME(3)
+    let created_at = DateTime::from_timestamp_millis(Utc::now().timestamp_millis()).ok_or(AppError::InternalServerError)?;
+    let mut entry = AuditEntry {
+        id: 0,
+        created_at,
+        action,
+        actor_type: ctx.actor_type,
+        actor_id: ctx.actor_id.clone(),
+        target_id,
+        changes,
+        ip_address: ctx.client.ip_address.clone(),
+        user_agent: ctx.client.user_agent.clone(),
+        prev_hash,
+        hash: String::new(),
+    };
+    entry.hash = entry_hash(&entry);
+
+    sqlx::query(
+        "INSERT INTO audit_log (created_at, action, actor_type, actor_id, target_id, changes, ip_address, user_agent, prev_hash, hash) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+        .bind(entry.created_at)
+        .bind(entry.action.as_str())
+        .bind(entry.actor_type.as_str())
+        .bind(&entry.actor_id)
+        .bind(entry.target_id)
+        .bind(entry.changes.as_ref().map(sqlx::types::Json))
+        .bind(&entry.ip_address)
+        .bind(&entry.user_agent)
+        .bind(&entry.prev_hash)
+        .bind(&entry.hash)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("UPDATE audit_chain SET last_hash = ? WHERE id = 1")
+        .bind(&entry.hash)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+//untuk aksi tanpa transaksi sendiri, misalnya login
+pub async fn record(pool: &Pool<MySql>, ctx: &AuditContext, action: AuditAction, target_id: Option<u64>) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+    append(&mut tx, ctx, action, target_id, None).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+//memeriksa rantai dari entri pertama: tiap hash harus cocok dengan isinya dan menyambung ke entri sebelumnya
+pub struct ChainVerifier {
+    head: String,
+    expected: String,
+    head_seen: bool,
+    checked: u64,
+}
+
+impl ChainVerifier {
+    pub fn new(head: &str) -> Self {
+        let expected = genesis_hash();
+        Self { head_seen: head == expected, head: head.to_string(), expected, checked: 0 }
+    }
+
+    //false jika entri ini memutus rantai, pemeriksaan bisa dihentikan
+    pub fn check(&mut self, entry: &AuditEntry) -> bool {
+        self.checked += 1;
+        if entry.prev_hash != self.expected || entry_hash(entry) != entry.hash {
+            return false;
+        }
+        self.head_seen |= entry.hash == self.head;
+        self.expected = entry.hash.clone();
+        true
+    }
+
+    pub fn broken(&self, entry: &AuditEntry) -> AuditVerification {
+        AuditVerification { valid: false, checked: self.checked, broken_at: Some(entry.id) }
+    }
+
+    //ujung rantai yang tidak ditemukan berarti entri terakhir dihapus
+    pub fn finish(&self) -> AuditVerification {
+        AuditVerification { valid: self.head_seen, checked: self.checked, broken_at: None }
+    }
+}
+
+pub async fn verify_chain(pool: &Pool<MySql>) -> Result<AuditVerification, AppError> {
+    //ujung dibaca lebih dulu, entri yang ditambah selama pemeriksaan hanya memperpanjang rantai
+    let (head,): (String,) = sqlx::query_as("SELECT last_hash FROM audit_chain WHERE id = 1")
+        .fetch_one(pool)
+        .await?;
+
+    let mut verifier = ChainVerifier::new(&head);
+    let mut rows = sqlx::query_as::<_, AuditEntry>("SELECT * FROM audit_log ORDER BY id ASC").fetch(pool);
+    while let Some(entry) = rows.try_next().await? {
+        if !verifier.check(&entry) {
+            return Ok(verifier.broken(&entry));
+        }
+    }
+    Ok(verifier.finish())
+}
@@ -0,0 +1,195 @@
+use std::{io::ErrorKind, path::PathBuf, time::Duration};
+use sqlx::{MySql, Pool};
+
+use crate::{
+    errors::app_error::AppError,
+    models::config_model::{BlobBackendKind, BlobStoreConfig},
+    utils::s3::{S3BlobStore, sha256_hex},
+};
+
+pub struct Blob {
+    pub data: Vec<u8>,
+    pub content_type: String,
+}
+
+//penyimpanan file biner (avatar), backend dipilih lewat config blob_store.backend
+pub trait BlobStore {
+    fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> impl Future<Output = Result<(), AppError>> + Send;
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<Blob>, AppError>> + Send;
+    //key yang tidak ada bukan error
+    fn delete(&self, key: &str) -> impl Future<Output = Result<(), AppError>> + Send;
+    fn url(&self, key: &str) -> String;
+    //URL unduhan sementara, backend tanpa dukungan presign mengembalikan url() biasa
+    fn presigned_url(&self, key: &str, expires: Duration) -> Result<String, AppError>;
+}
+
+//key berupa segmen dipisah "/", hanya huruf, angka, "-", "_" dan "." agar tidak bisa keluar dari root
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && key.split('/').all(|segment| {
+        !segment.is_empty()
+            && segment != "."
+            && segment != ".."
+            && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    })
+}
+
+pub(crate) fn check_key(key: &str) -> Result<(), AppError> {
+    if is_valid_key(key) { Ok(()) } else { Err(AppError::BadRequest) }
+}
+
+pub fn content_type_for(key: &str) -> &'static str {
+    match key.rsplit('.').next() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        Some("gif") => "image/gif",
+        _ => "application/octet-stream",
+    }
+}
+
+//base kosong berarti path relatif yang dilayani server ini
+pub(crate) fn public_url(base: &str, key: &str) -> String {
+    format!("{}/{}", base.trim_end_matches('/'), key)
+}
+
+//key ditentukan oleh isi file, upload yang identik memakai blob yang sama
+pub fn content_key(prefix: &str, data: &[u8], ext: &str) -> String {
+    format!("{}/{}.{}", prefix, sha256_hex(data), ext)
+}
+
+// =======================
+// Local filesystem
+// =======================
+
+pub struct LocalBlobStore {
+    root: PathBuf,
+    public_url: String,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl Into<PathBuf>, public_url: &str) -> Self {
+        Self { root: root.into(), public_url: public_url.to_string() }
+    }
+}
+
+impl BlobStore for LocalBlobStore {
+    //ditulis ke file sementara lalu di-rename agar pembaca tidak pernah melihat file setengah jadi
+    async fn put(&self, key: &str, data: Vec<u8>, _content_type: &str) -> Result<(), AppError> {
+        check_key(key)?;
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4().simple()));
+        tokio::fs::write(&tmp, data).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Blob>, AppError> {
+        check_key(key)?;
+        match tokio::fs::read(self.root.join(key)).await {
+            Ok(data) => Ok(Some(Blob { data, content_type: content_type_for(key).to_string() })),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        check_key(key)?;
+        match tokio::fs::remove_file(self.root.join(key)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        public_url(&self.public_url, key)
+    }
+
+    fn presigned_url(&self, key: &str, _expires: Duration) -> Result<String, AppError> {
+        check_key(key)?;
+        Ok(self.url(key))
+    }
+}
+
+// =======================
+// Pemilihan backend
+// =======================
+
+pub enum AnyBlobStore {
+    Local(LocalBlobStore),
+    S3(S3BlobStore),
+}
+
+pub fn blob_store(cfg: &BlobStoreConfig) -> Result<AnyBlobStore, AppError> {
+    Ok(match cfg.backend {
+        BlobBackendKind::Local => AnyBlobStore::Local(LocalBlobStore::new(&cfg.local_dir, &cfg.public_url)),
+        BlobBackendKind::S3 => AnyBlobStore::S3(S3BlobStore::new(cfg.s3.clone(), &cfg.public_url)?),
+    })
+}
+
+impl BlobStore for AnyBlobStore {
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<(), AppError> {
+        match self {
+            AnyBlobStore::Local(store) => store.put(key, data, content_type).await,
+            AnyBlobStore::S3(store) => store.put(key, data, content_type).await,
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Blob>, AppError> {
+        match self {
+            AnyBlobStore::Local(store) => store.get(key).await,
+            AnyBlobStore::S3(store) => store.get(key).await,
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        match self {
+            AnyBlobStore::Local(store) => store.delete(key).await,
+            AnyBlobStore::S3(store) => store.delete(key).await,
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        match self {
+            AnyBlobStore::Local(store) => store.url(key),
+            AnyBlobStore::S3(store) => store.url(key),
+        }
+    }
+
+    fn presigned_url(&self, key: &str, expires: Duration) -> Result<String, AppError> {
+        match self {
+            AnyBlobStore::Local(store) => store.presigned_url(key, expires),
+            AnyBlobStore::S3(store) => store.presigned_url(key, expires),
+        }
+    }
+}
+
+// =======================
+// Metadata blob
+// =======================
+
+//simpan blob dengan key berbasis hash; jika key sudah tercatat hanya last_used_at yang diperbarui
+//sehingga GC tidak menghapus blob yang baru saja dipakai ulang
+pub async fn save_blob(store: &impl BlobStore, pool: &Pool<MySql>, prefix: &str, ext: &str, content_type: &str, data: Vec<u8>) -> Result<String, AppError> {
+    let key = content_key(prefix, &data, ext);
+    let inserted = sqlx::query(
+        "INSERT INTO blobs (blob_key, sha256, byte_size, content_type) VALUES (?, ?, ?, ?) \
+         ON DUPLICATE KEY UPDATE last_used_at = CURRENT_TIMESTAMP"
+    )
+        .bind(&key)
+        .bind(sha256_hex(&data))
+        .bind(data.len() as u64)
+        .bind(content_type)
+        .execute(pool)
+        .await?
+        .rows_affected() == 1;
+
+    //baris baru: objek belum ada di store, baris dihapus lagi jika upload gagal
+    if inserted && let Err(e) = store.put(&key, data, content_type).await {
+        sqlx::query("DELETE FROM blobs WHERE blob_key = ?").bind(&key).execute(pool).await?;
+        return Err(e);
+    }
+    Ok(key)
+}
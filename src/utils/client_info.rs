@@ -0,0 +1,72 @@
+use std::net::{IpAddr, SocketAddr};
+use axum::extract::{ConnectInfo, FromRequestParts};
+use http::{HeaderMap, request::Parts};
+
+use crate::utils::utils::load_config;
+
+//informasi perangkat/klien dari request, dipakai untuk session dan pencatatan
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+//entri trusted_proxies berupa IP tunggal atau CIDR (10.0.0.0/8, fd00::/8)
+pub fn is_trusted_proxy(ip: IpAddr, trusted: &[String]) -> bool {
+    trusted.iter().any(|entry| {
+        let (network, prefix) = match entry.trim().split_once('/') {
+            Some((network, prefix)) => (network, prefix.parse::<u32>().ok()),
+            None => (entry.trim(), None),
+        };
+        match (network.parse::<IpAddr>(), ip) {
+            (Ok(IpAddr::V4(network)), IpAddr::V4(ip)) => {
+                let bits = prefix.unwrap_or(32).min(32);
+                let mask = u32::MAX.checked_shl(32 - bits).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (Ok(IpAddr::V6(network)), IpAddr::V6(ip)) => {
+                let bits = prefix.unwrap_or(128).min(128);
+                let mask = u128::MAX.checked_shl(128 - bits).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    })
+}
+
+impl ClientInfo {
+    pub fn from_headers(headers: &HeaderMap, peer: Option<SocketAddr>, trusted_proxies: &[String]) -> Self {
+        let header = |name: &str| headers.get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+
+        //header proxy hanya dipercaya jika request datang dari trusted proxy,
+        //X-Forwarded-For dibaca dari kanan melewati proxy tepercaya karena bagian kiri bisa dipalsukan client
+        let peer_ip = peer.map(|addr| addr.ip());
+        let forwarded = peer_ip.filter(|ip| is_trusted_proxy(*ip, trusted_proxies)).and_then(|_| {
+            match header("x-forwarded-for") {
+                Some(chain) => chain.rsplit(',')
+                    .map(|ip| ip.trim().parse::<IpAddr>())
+                    .find(|ip| !matches!(ip, Ok(ip) if is_trusted_proxy(*ip, trusted_proxies)))
+                    .and_then(Result::ok),
+                None => header("x-real-ip").and_then(|ip| ip.parse::<IpAddr>().ok()),
+            }
+        });
+
+        Self {
+            ip_address: forwarded.or(peer_ip).map(|ip| ip.to_string()),
+            user_agent: header("user-agent").map(|ua| ua.chars().take(255).collect()),
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientInfo {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts.extensions.get::<ConnectInfo<SocketAddr>>().map(|c| c.0);
+        let trusted_proxies = load_config().map(|c| c.server.trusted_proxies).unwrap_or_default();
+        Ok(Self::from_headers(&parts.headers, peer, &trusted_proxies))
+    }
+}
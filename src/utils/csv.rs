@@ -0,0 +1,55 @@
+//CSV sederhana (RFC 4180): pemisah koma, field boleh diapit kutip dua, "" di dalam kutip berarti satu kutip
+
+//record yang jumlah kutipnya ganjil belum selesai, baris berikutnya masih bagian dari field yang sama
+pub fn is_incomplete(record: &str) -> bool {
+    record.matches('"').count() % 2 == 1
+}
+
+pub fn parse_record(record: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = record.chars().peekable();
+    let mut quoted = false;
+    let mut was_quoted = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() && !was_quoted => {
+                quoted = true;
+                was_quoted = true;
+            }
+            '"' => return Err("tanda kutip di tengah field".to_string()),
+            ',' if !quoted => {
+                fields.push(std::mem::take(&mut field));
+                was_quoted = false;
+            }
+            _ if was_quoted && !quoted => return Err("karakter setelah kutip penutup".to_string()),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("tanda kutip tidak ditutup".to_string());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+//field yang berisi koma, kutip atau baris baru diapit kutip
+pub fn escape_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn write_record(fields: &[&str]) -> String {
+    let mut line = fields.iter().map(|f| escape_field(f)).collect::<Vec<_>>().join(",");
+    line.push_str("\r\n");
+    line
+}
@@ -0,0 +1,46 @@
+use http::{HeaderMap, header};
+
+use crate::models::user_model::User;
+
+//ETag user diturunkan dari updated_at (presisi milidetik)
+pub fn user_etag(user: &User) -> String {
+    format!("\"{}-{}\"", user.id, user.updated_at.timestamp_millis())
+}
+
+#[derive(Debug, PartialEq)]
+pub enum IfMatch {
+    Any,
+    Tags(Vec<String>),
+}
+
+impl IfMatch {
+    //nilai header If-Match: "*" atau daftar ETag dipisah koma
+    pub fn parse(value: &str) -> Self {
+        let tags: Vec<String> = value.split(',')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect();
+        if tags.iter().any(|t| t == "*") {
+            return IfMatch::Any;
+        }
+        IfMatch::Tags(tags)
+    }
+
+    //If-Match memakai perbandingan strong (RFC 9110 13.1.1), ETag weak tidak pernah cocok
+    pub fn matches(&self, etag: &str) -> bool {
+        match self {
+            IfMatch::Any => true,
+            IfMatch::Tags(tags) => tags.iter().any(|t| !t.starts_with("W/") && t == etag),
+        }
+    }
+}
+
+pub fn if_match(headers: &HeaderMap) -> Option<IfMatch> {
+    let values: Vec<&str> = headers.get_all(header::IF_MATCH).iter()
+        .filter_map(|v| v.to_str().ok())
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    Some(IfMatch::parse(&values.join(",")))
+}
@@ -0,0 +1,228 @@
+use std::{fs, str::FromStr};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+    jwk::{AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm, OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType},
+};
+use rsa::{RsaPublicKey, pkcs8::DecodePublicKey, traits::PublicKeyParts};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{errors::app_error::AppError, models::config_model::{JwtConfig, JwtKeyConfig}};
+
+//panjang SubjectPublicKeyInfo ed25519: 12 byte header + 32 byte public key
+const ED25519_SPKI_LEN: usize = 44;
+
+struct SigningKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: EncodingKey,
+}
+
+struct VerifyingKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: DecodingKey,
+    jwk: Option<Jwk>,
+}
+
+//kumpulan kunci jwt: satu kunci aktif untuk signing, banyak kunci untuk verifikasi (rotasi)
+pub struct JwtKeys {
+    signing: SigningKey,
+    verifying: Vec<VerifyingKey>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    leeway: u64,
+}
+
+impl JwtKeys {
+    pub fn from_config(cfg: &JwtConfig, legacy_secret: &str) -> Result<Self, AppError> {
+        let mut signing = None;
+        let mut verifying = Vec::new();
+
+        for key_cfg in &cfg.keys {
+            let (encoding, verifying_key) = load_key(key_cfg)?;
+            let is_active = match &cfg.active_kid {
+                Some(kid) => kid == &key_cfg.kid,
+                None => signing.is_none(),
+            };
+            if is_active && let Some(key) = encoding {
+                signing = Some(SigningKey {
+                    kid: Some(key_cfg.kid.clone()),
+                    algorithm: verifying_key.algorithm,
+                    key,
+                });
+            }
+            verifying.push(verifying_key);
+        }
+
+        //token lama tanpa kid tetap bisa diverifikasi selama jwt_secret masih diisi
+        if !legacy_secret.is_empty() {
+            verifying.push(VerifyingKey {
+                kid: None,
+                algorithm: Algorithm::HS256,
+                key: DecodingKey::from_secret(legacy_secret.as_bytes()),
+                jwk: None,
+            });
+            if cfg.keys.is_empty() {
+                signing = Some(SigningKey {
+                    kid: None,
+                    algorithm: Algorithm::HS256,
+                    key: EncodingKey::from_secret(legacy_secret.as_bytes()),
+                });
+            }
+        }
+
+        let signing = signing.ok_or_else(|| match &cfg.active_kid {
+            Some(kid) => config_error(format!("jwt key '{}' tidak ada atau tidak punya private key", kid)),
+            None => config_error("tidak ada kunci jwt untuk signing".to_string()),
+        })?;
+
+        Ok(Self {
+            signing,
+            verifying,
+            issuer: cfg.issuer.clone(),
+            audience: cfg.audience.clone(),
+            leeway: cfg.leeway,
+        })
+    }
+
+    pub fn issuer(&self) -> Option<&str> {
+        self.issuer.as_deref()
+    }
+
+    pub fn audience(&self) -> Option<&str> {
+        self.audience.as_deref()
+    }
+
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, AppError> {
+        let mut header = Header::new(self.signing.algorithm);
+        header.kid = self.signing.kid.clone();
+        Ok(encode(&header, claims, &self.signing.key)?)
+    }
+
+    pub fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<T, AppError> {
+        let header = decode_header(token).map_err(|_| AppError::Unauthorized)?;
+
+        //algoritma harus sama dengan kunci yang dipilih agar tidak bisa dipalsukan (alg confusion)
+        let key = self.verifying.iter()
+            .find(|k| k.kid == header.kid && k.algorithm == header.alg)
+            .ok_or(AppError::Unauthorized)?;
+
+        let mut validation = Validation::new(key.algorithm);
+        validation.leeway = self.leeway;
+        validation.validate_nbf = true;
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+            validation.required_spec_claims.insert("iss".to_string());
+        }
+        if let Some(audience) = &self.audience {
+            validation.set_audience(&[audience]);
+            validation.required_spec_claims.insert("aud".to_string());
+        }
+
+        let token_data = decode::<T>(token, &key.key, &validation)
+            .map_err(|_| AppError::Unauthorized)?;
+        Ok(token_data.claims)
+    }
+
+    //hanya kunci publik (asimetris) yang dipublikasikan, secret HMAC tidak pernah keluar
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.verifying.iter().filter_map(|k| k.jwk.clone()).collect(),
+        }
+    }
+}
+
+fn load_key(cfg: &JwtKeyConfig) -> Result<(Option<EncodingKey>, VerifyingKey), AppError> {
+    let algorithm = Algorithm::from_str(&cfg.algorithm)
+        .map_err(|_| config_error(format!("algoritma jwt '{}' tidak dikenal", cfg.algorithm)))?;
+
+    match algorithm {
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+            let secret = cfg.secret.as_deref()
+                .filter(|s| !s.is_empty())
+                .ok_or_else(|| config_error(format!("jwt key '{}' membutuhkan secret", cfg.kid)))?;
+            Ok((
+                Some(EncodingKey::from_secret(secret.as_bytes())),
+                VerifyingKey {
+                    kid: Some(cfg.kid.clone()),
+                    algorithm,
+                    key: DecodingKey::from_secret(secret.as_bytes()),
+                    jwk: None,
+                },
+            ))
+        }
+        Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512
+        | Algorithm::PS256 | Algorithm::PS384 | Algorithm::PS512 => {
+            let public_pem = read_key_file(cfg.public_key_path.as_deref(), &cfg.kid)?;
+            let encoding = match cfg.private_key_path.as_deref() {
+                Some(path) => Some(EncodingKey::from_rsa_pem(&read_key_file(Some(path), &cfg.kid)?)?),
+                None => None,
+            };
+            let public_key = RsaPublicKey::from_public_key_pem(&String::from_utf8_lossy(&public_pem))
+                .map_err(|e| config_error(format!("public key '{}' tidak valid: {}", cfg.kid, e)))?;
+            let params = AlgorithmParameters::RSA(RSAKeyParameters {
+                key_type: RSAKeyType::RSA,
+                n: URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+                e: URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be()),
+            });
+            Ok((
+                encoding,
+                VerifyingKey {
+                    kid: Some(cfg.kid.clone()),
+                    algorithm,
+                    key: DecodingKey::from_rsa_pem(&public_pem)?,
+                    jwk: Some(public_jwk(&cfg.kid, algorithm, params)),
+                },
+            ))
+        }
+        Algorithm::EdDSA => {
+            let public_pem = read_key_file(cfg.public_key_path.as_deref(), &cfg.kid)?;
+            let encoding = match cfg.private_key_path.as_deref() {
+                Some(path) => Some(EncodingKey::from_ed_pem(&read_key_file(Some(path), &cfg.kid)?)?),
+                None => None,
+            };
+            let spki = pem::parse(&public_pem)
+                .map_err(|e| config_error(format!("public key '{}' tidak valid: {}", cfg.kid, e)))?;
+            if spki.contents().len() != ED25519_SPKI_LEN {
+                return Err(config_error(format!("public key '{}' bukan ed25519", cfg.kid)));
+            }
+            let params = AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x: URL_SAFE_NO_PAD.encode(&spki.contents()[ED25519_SPKI_LEN - 32..]),
+            });
+            Ok((
+                encoding,
+                VerifyingKey {
+                    kid: Some(cfg.kid.clone()),
+                    algorithm,
+                    key: DecodingKey::from_ed_pem(&public_pem)?,
+                    jwk: Some(public_jwk(&cfg.kid, algorithm, params)),
+                },
+            ))
+        }
+        _ => Err(config_error(format!("algoritma jwt '{}' belum didukung", cfg.algorithm))),
+    }
+}
+
+fn public_jwk(kid: &str, algorithm: Algorithm, params: AlgorithmParameters) -> Jwk {
+    Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: KeyAlgorithm::from_str(&format!("{:?}", algorithm)).ok(),
+            key_id: Some(kid.to_string()),
+            ..Default::default()
+        },
+        algorithm: params,
+    }
+}
+
+fn read_key_file(path: Option<&str>, kid: &str) -> Result<Vec<u8>, AppError> {
+    let path = path.ok_or_else(|| config_error(format!("jwt key '{}' membutuhkan public_key_path", kid)))?;
+    fs::read(path).map_err(|e| config_error(format!("gagal membaca key {}: {}", path, e)))
+}
+
+fn config_error(message: String) -> AppError {
+    AppError::ConfigError(config::ConfigError::Message(message))
+}
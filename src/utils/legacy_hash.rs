@@ -0,0 +1,100 @@
+use argon2::password_hash::{PasswordHash, PasswordVerifier};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use data_encoding::HEXLOWER_PERMISSIVE;
+use pbkdf2::{Pbkdf2, pbkdf2_hmac};
+use scrypt::Scrypt;
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+use crate::errors::app_error::AppError;
+
+//format hash dari sistem lama, semua di-rehash ke argon2id saat login berhasil
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LegacyFormat {
+    //$2a$ / $2b$ / $2y$ (modular crypt)
+    Bcrypt,
+    //$pbkdf2-sha256$i=...,l=...$salt$hash (PHC)
+    Pbkdf2Phc,
+    //pbkdf2_sha256$iterasi$salt$hash-base64 (Django)
+    Pbkdf2Django,
+    //pbkdf2:sha256:iterasi$salt$hash-hex (Werkzeug)
+    Pbkdf2Werkzeug,
+    //$scrypt$ln=...,r=...,p=...$salt$hash (PHC)
+    Scrypt,
+}
+
+pub fn detect(hash: &str) -> Option<LegacyFormat> {
+    if ["$2a$", "$2b$", "$2y$"].iter().any(|p| hash.starts_with(p)) {
+        Some(LegacyFormat::Bcrypt)
+    } else if hash.starts_with("$pbkdf2-sha256$") {
+        Some(LegacyFormat::Pbkdf2Phc)
+    } else if hash.starts_with("pbkdf2_sha256$") {
+        Some(LegacyFormat::Pbkdf2Django)
+    } else if hash.starts_with("pbkdf2:sha256") {
+        Some(LegacyFormat::Pbkdf2Werkzeug)
+    } else if hash.starts_with("$scrypt$") {
+        Some(LegacyFormat::Scrypt)
+    } else {
+        None
+    }
+}
+
+fn pbkdf2_sha256_matches(password: &str, salt: &[u8], iterations: u32, expected: &[u8]) -> bool {
+    if iterations == 0 || expected.is_empty() {
+        return false;
+    }
+    let mut derived = vec![0u8; expected.len()];
+    pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut derived);
+    derived.ct_eq(expected).into()
+}
+
+fn verify_django(hash: &str, password: &str) -> Option<bool> {
+    let mut parts = hash.splitn(4, '$').skip(1);
+    let iterations = parts.next()?.parse().ok()?;
+    let salt = parts.next()?;
+    let expected = STANDARD.decode(parts.next()?).ok()?;
+    Some(pbkdf2_sha256_matches(password, salt.as_bytes(), iterations, &expected))
+}
+
+fn verify_werkzeug(hash: &str, password: &str) -> Option<bool> {
+    let (method, rest) = hash.split_once('$')?;
+    let (salt, hex) = rest.split_once('$')?;
+    //iterasi boleh tidak ditulis, default lama werkzeug 150000
+    let iterations = match method.strip_prefix("pbkdf2:sha256")? {
+        "" => 150_000,
+        count => count.strip_prefix(':')?.parse().ok()?,
+    };
+    let expected = HEXLOWER_PERMISSIVE.decode(hex.as_bytes()).ok()?;
+    Some(pbkdf2_sha256_matches(password, salt.as_bytes(), iterations, &expected))
+}
+
+fn verify_phc(verifier: &dyn PasswordVerifier, hash: &str, password: &str) -> Option<bool> {
+    let parsed = PasswordHash::new(hash).ok()?;
+    Some(verifier.verify_password(password.as_bytes(), &parsed).is_ok())
+}
+
+//hash yang formatnya rusak diperlakukan sama dengan hash argon2 yang rusak (401)
+pub fn verify_legacy(format: LegacyFormat, hash: &str, password: &str) -> Result<bool, AppError> {
+    let result = match format {
+        LegacyFormat::Bcrypt => bcrypt::verify(password, hash).ok(),
+        LegacyFormat::Pbkdf2Phc => verify_phc(&Pbkdf2, hash, password),
+        LegacyFormat::Pbkdf2Django => verify_django(hash, password),
+        LegacyFormat::Pbkdf2Werkzeug => verify_werkzeug(hash, password),
+        LegacyFormat::Scrypt => verify_phc(&Scrypt, hash, password),
+    };
+    result.ok_or(AppError::Unauthorized)
+}
+
+//dipakai import untuk menolak hash yang tidak akan pernah bisa diverifikasi
+pub fn is_supported_hash(hash: &str) -> bool {
+    if hash.starts_with("$argon2") {
+        return PasswordHash::new(hash).is_ok();
+    }
+    match detect(hash) {
+        Some(LegacyFormat::Bcrypt) => hash.parse::<bcrypt::HashParts>().is_ok(),
+        Some(LegacyFormat::Pbkdf2Phc | LegacyFormat::Scrypt) => PasswordHash::new(hash).is_ok(),
+        Some(LegacyFormat::Pbkdf2Django) => hash.split('$').count() == 4,
+        Some(LegacyFormat::Pbkdf2Werkzeug) => hash.split('$').count() == 3,
+        None => false,
+    }
+}
@@ -1 +1,21 @@
-pub mod utils;
\ No newline at end of file
+#[allow(clippy::module_inception)]
+pub mod utils;
+pub mod jwt_keys;
+pub mod session;
+pub mod client_info;
+pub mod totp;
+pub mod webauthn;
+pub mod oidc;pub mod oauth;
+pub mod password_policy;
+pub mod password_hash;
+pub mod legacy_hash;
+pub mod etag;
+pub mod search_query;
+pub mod search;
+pub mod search_index;
+pub mod csv;
+pub mod blob_store;
+pub mod attributes;
+pub mod s3;
+pub mod audit;
+pub mod outbox;
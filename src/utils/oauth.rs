@@ -0,0 +1,169 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Duration, Utc};
+use data_encoding::HEXLOWER;
+use http::{HeaderMap, header};
+use sha2::{Digest, Sha256};
+use sqlx::{MySql, Pool};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    errors::{app_error::AppError, oauth_error::OAuthError},
+    models::{
+        config_model::{JwtConfig, OAuthConfig},
+        oauth_model::{OAuthClaims, StoredClient, TokenResponse},
+        user_model::TokenType,
+    },
+    utils::{jwt_keys::JwtKeys, utils::{load_config, load_jwt_keys, verify_password}},
+};
+
+//authorization code disimpan sebagai hash, code aslinya hanya ada di redirect
+pub fn hash_code(code: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(code.as_bytes()))
+}
+
+//redirect uri harus absolut dan tanpa fragment (RFC 6749 3.1.2)
+pub fn validate_redirect_uri(uri: &str) -> bool {
+    Url::parse(uri)
+        .map(|url| url.fragment().is_none() && matches!(url.scheme(), "https" | "http"))
+        .unwrap_or(false)
+}
+
+pub fn redirect_with(uri: &str, params: &[(&str, &str)]) -> Result<String, AppError> {
+    let mut url = Url::parse(uri).map_err(|_| AppError::BadRequest)?;
+    url.query_pairs_mut().extend_pairs(params);
+    Ok(url.to_string())
+}
+
+//scope kosong berarti semua scope client, scope yang dihapus dari config tidak lagi diterbitkan
+pub fn grant_scope(requested: Option<&str>, client_scopes: &[String], server_scopes: &[String]) -> Result<String, OAuthError> {
+    let allowed: Vec<&String> = client_scopes.iter().filter(|s| server_scopes.contains(s)).collect();
+    let granted: Vec<&str> = match requested.map(str::trim).filter(|s| !s.is_empty()) {
+        None => allowed.iter().map(|s| s.as_str()).collect(),
+        Some(requested) => {
+            let scopes: Vec<&str> = requested.split_whitespace().collect();
+            if scopes.iter().any(|s| !allowed.iter().any(|a| a == s)) {
+                return Err(OAuthError::InvalidScope);
+            }
+            scopes
+        }
+    };
+
+    if granted.is_empty() {
+        return Err(OAuthError::InvalidScope);
+    }
+    Ok(granted.join(" "))
+}
+
+//tidak ada layar consent, jadi code hanya diberikan ke client first-party dari config
+pub fn is_first_party(cfg: &OAuthConfig, client_id: &str) -> bool {
+    cfg.first_party_clients.iter().any(|id| id == client_id)
+}
+
+//kredensial client dari header Basic atau body form, tidak boleh keduanya (RFC 6749 2.3.1)
+pub fn client_credentials(headers: &HeaderMap, form_id: Option<&str>, form_secret: Option<&str>) -> Result<(String, Option<String>), OAuthError> {
+    let basic = headers.get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Basic "));
+
+    match (basic, form_id) {
+        (Some(_), Some(_)) => Err(OAuthError::InvalidRequest),
+        (Some(encoded), None) => {
+            if form_secret.is_some() {
+                return Err(OAuthError::InvalidRequest);
+            }
+            let decoded = STANDARD.decode(encoded.trim()).map_err(|_| OAuthError::InvalidClient)?;
+            let decoded = String::from_utf8(decoded).map_err(|_| OAuthError::InvalidClient)?;
+            let (id, secret) = decoded.split_once(':').ok_or(OAuthError::InvalidClient)?;
+            Ok((id.to_string(), Some(secret.to_string())))
+        }
+        (None, Some(id)) => Ok((id.to_string(), form_secret.map(str::to_string))),
+        (None, None) => Err(OAuthError::InvalidClient),
+    }
+}
+
+pub async fn find_client(pool: &Pool<MySql>, client_id: &str) -> Result<Option<StoredClient>, AppError> {
+    Ok(sqlx::query_as::<_, StoredClient>("SELECT id, name, secret_hash, redirect_uris, grant_types, scopes, created_at FROM oauth_clients WHERE id = ?")
+        .bind(client_id)
+        .fetch_optional(pool)
+        .await?)
+}
+
+//client confidential wajib menyertakan secret yang benar, client publik tidak boleh mengirim secret
+pub async fn authenticate_client(pool: &Pool<MySql>, headers: &HeaderMap, form_id: Option<&str>, form_secret: Option<&str>) -> Result<StoredClient, OAuthError> {
+    let (client_id, secret) = client_credentials(headers, form_id, form_secret)?;
+    let client = find_client(pool, &client_id).await?.ok_or(OAuthError::InvalidClient)?;
+
+    let valid = match (client.secret_hash.as_deref(), secret.as_deref()) {
+        (Some(hash), Some(secret)) => verify_password(Some(hash), secret).await?,
+        (None, None) => true,
+        _ => false,
+    };
+    if !valid {
+        return Err(OAuthError::InvalidClient);
+    }
+    Ok(client)
+}
+
+pub fn build_oauth_claims(jwt: &JwtConfig, keys: &JwtKeys, client_id: &str, user_id: Option<u64>, scope: &str) -> OAuthClaims {
+    let now = Utc::now();
+    OAuthClaims {
+        sub: user_id.map(|id| id.to_string()).unwrap_or_else(|| client_id.to_string()),
+        client_id: client_id.to_string(),
+        scope: scope.to_string(),
+        exp: (now + Duration::seconds(jwt.lifetimes.for_type(TokenType::OAuth))).timestamp(),
+        iat: now.timestamp(),
+        nbf: now.timestamp(),
+        jti: Uuid::new_v4().to_string(),
+        iss: keys.issuer().map(str::to_string),
+        aud: keys.audience().map(str::to_string),
+        user_id,
+        typ: TokenType::OAuth,
+    }
+}
+
+//access token ditandatangani dengan kunci yang sama dengan create_jwt dan dicatat agar bisa dicabut
+pub async fn issue_access_token(pool: &Pool<MySql>, client_id: &str, user_id: Option<u64>, scope: &str) -> Result<TokenResponse, AppError> {
+    let conf = load_config()?;
+    let keys = load_jwt_keys()?;
+    let claims = build_oauth_claims(&conf.jwt, &keys, client_id, user_id, scope);
+    let token = keys.sign(&claims)?;
+    let expires_at = DateTime::from_timestamp(claims.exp, 0).ok_or(AppError::InternalServerError)?;
+
+    sqlx::query("INSERT INTO oauth_tokens (jti, client_id, user_id, scope, expires_at) VALUES (?, ?, ?, ?, ?)")
+        .bind(&claims.jti)
+        .bind(client_id)
+        .bind(user_id)
+        .bind(scope)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+
+    Ok(TokenResponse {
+        access_token: token,
+        token_type: "Bearer".to_string(),
+        expires_in: claims.exp - claims.iat,
+        scope: scope.to_string(),
+    })
+}
+
+pub fn decode_access_token(keys: &JwtKeys, token: &str) -> Result<OAuthClaims, AppError> {
+    let claims = keys.verify::<OAuthClaims>(token)?;
+    if claims.typ != TokenType::OAuth {
+        return Err(AppError::Unauthorized);
+    }
+    Ok(claims)
+}
+
+//token valid jika tanda tangan benar dan belum dicabut di server
+pub async fn verify_access_token(pool: &Pool<MySql>, keys: &JwtKeys, token: &str) -> Result<OAuthClaims, AppError> {
+    let claims = decode_access_token(keys, token)?;
+    let active: Option<(String,)> = sqlx::query_as("SELECT jti FROM oauth_tokens WHERE jti = ? AND revoked_at IS NULL AND expires_at > NOW()")
+        .bind(&claims.jti)
+        .fetch_optional(pool)
+        .await?;
+    if active.is_none() {
+        return Err(AppError::Unauthorized);
+    }
+    Ok(claims)
+}
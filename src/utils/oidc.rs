@@ -0,0 +1,129 @@
+use std::time::Duration;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header, jwk::JwkSet};
+use rand::{RngCore, rngs::OsRng};
+use reqwest::Client;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::{errors::app_error::AppError, models::config_model::OidcProviderConfig};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TokenResponse {
+    pub id_token: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub name: Option<String>,
+    pub nonce: Option<String>,
+}
+
+pub fn http_client() -> Result<Client, AppError> {
+    Ok(Client::builder().timeout(Duration::from_secs(10)).build()?)
+}
+
+pub fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+//PKCE S256 (RFC 7636): challenge = base64url(sha256(verifier))
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+pub async fn discover(client: &Client, issuer: &str) -> Result<ProviderMetadata, AppError> {
+    let url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
+    let metadata: ProviderMetadata = client.get(url).send().await?.error_for_status()?.json().await?;
+
+    //issuer di discovery document wajib sama persis dengan yang dikonfigurasi
+    if metadata.issuer != issuer {
+        eprintln!("OIDC: issuer discovery {} tidak sama dengan {}", metadata.issuer, issuer);
+        return Err(AppError::Unauthorized);
+    }
+    Ok(metadata)
+}
+
+pub fn authorization_url(metadata: &ProviderMetadata, provider: &OidcProviderConfig, state: &str, nonce: &str, code_verifier: &str) -> Result<String, AppError> {
+    let url = Url::parse_with_params(&metadata.authorization_endpoint, &[
+        ("response_type", "code"),
+        ("client_id", provider.client_id.as_str()),
+        ("redirect_uri", provider.redirect_uri.as_str()),
+        ("scope", provider.scopes.join(" ").as_str()),
+        ("state", state),
+        ("nonce", nonce),
+        ("code_challenge", pkce_challenge(code_verifier).as_str()),
+        ("code_challenge_method", "S256"),
+    ]).map_err(|_| AppError::InternalServerError)?;
+    Ok(url.to_string())
+}
+
+pub async fn exchange_code(client: &Client, metadata: &ProviderMetadata, provider: &OidcProviderConfig, code: &str, code_verifier: &str) -> Result<TokenResponse, AppError> {
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", provider.redirect_uri.as_str()),
+        ("client_id", provider.client_id.as_str()),
+        ("code_verifier", code_verifier),
+    ];
+    if let Some(secret) = provider.client_secret.as_deref() {
+        form.push(("client_secret", secret));
+    }
+
+    let response = client.post(&metadata.token_endpoint).form(&form).send().await?;
+    if !response.status().is_success() {
+        eprintln!("OIDC: token endpoint menolak code ({})", response.status());
+        return Err(AppError::Unauthorized);
+    }
+    Ok(response.json().await?)
+}
+
+pub async fn fetch_jwks(client: &Client, metadata: &ProviderMetadata) -> Result<JwkSet, AppError> {
+    Ok(client.get(&metadata.jwks_uri).send().await?.error_for_status()?.json().await?)
+}
+
+//verifikasi id token: tanda tangan dari JWKS provider, iss, aud = client_id, exp, dan nonce
+pub fn verify_id_token(jwks: &JwkSet, id_token: &str, issuer: &str, client_id: &str, nonce: &str) -> Result<IdTokenClaims, AppError> {
+    let header = decode_header(id_token).map_err(|_| AppError::Unauthorized)?;
+
+    //hanya algoritma asimetris, HS256 dengan client_secret tidak diterima
+    if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+        return Err(AppError::Unauthorized);
+    }
+
+    let jwk = match header.kid.as_deref() {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }.ok_or(AppError::Unauthorized)?;
+    let key = DecodingKey::from_jwk(jwk).map_err(|_| AppError::Unauthorized)?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[issuer]);
+    validation.set_audience(&[client_id]);
+    validation.required_spec_claims.extend(["iss".to_string(), "aud".to_string(), "sub".to_string()]);
+
+    let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+        .map_err(|_| AppError::Unauthorized)?
+        .claims;
+
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(AppError::Unauthorized);
+    }
+    Ok(claims)
+}
@@ -0,0 +1,133 @@
+use std::time::Duration;
+use data_encoding::HEXLOWER;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde_json::Value;
+use sha2::Sha256;
+use sqlx::{MySqlConnection, types::Json as SqlJson};
+
+use crate::{
+    errors::app_error::AppError,
+    models::{
+        config_model::{EventSinkConfig, OutboxConfig},
+        outbox_model::{DomainEventKind, EventEnvelope},
+    },
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+//harus dijalankan di transaksi yang sama dengan perubahan user, event hanya ada jika perubahannya di-commit
+pub async fn enqueue(conn: &mut MySqlConnection, event_type: DomainEventKind, user_id: u64, data: Value) -> Result<(), AppError> {
+    sqlx::query("INSERT INTO outbox_events (event_id, event_type, user_id, payload) VALUES (?, ?, ?, ?)")
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(event_type.as_str())
+        .bind(user_id)
+        .bind(SqlJson(data))
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+//jeda sebelum percobaan berikutnya setelah gagal sebanyak attempts kali
+pub fn retry_delay(cfg: &OutboxConfig, attempts: u32) -> u64 {
+    let factor = 1u64.checked_shl(attempts.saturating_sub(1)).unwrap_or(u64::MAX);
+    cfg.retry_base_secs.saturating_mul(factor).min(cfg.retry_max_secs)
+}
+
+// =======================
+// Sink
+// =======================
+
+//tujuan pengiriman event, dipilih lewat config outbox.sinks
+pub trait EventSink {
+    fn name(&self) -> &str;
+    fn publish(&self, event: &EventEnvelope) -> impl Future<Output = Result<(), AppError>> + Send;
+}
+
+pub struct LogSink;
+
+impl EventSink for LogSink {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    async fn publish(&self, event: &EventEnvelope) -> Result<(), AppError> {
+        println!("EVENT: {}", serde_json::to_string(event).map_err(|_| AppError::InternalServerError)?);
+        Ok(())
+    }
+}
+
+pub struct WebhookSink {
+    url: String,
+    secret: String,
+    client: Client,
+}
+
+//hex HMAC-SHA256 dari body, consumer menghitung ulang dengan secret yang sama
+pub fn webhook_signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC menerima key dengan panjang apa pun");
+    mac.update(body);
+    HEXLOWER.encode(&mac.finalize().into_bytes())
+}
+
+impl WebhookSink {
+    pub fn new(url: &str, secret: &str, timeout: Duration) -> Result<Self, AppError> {
+        Ok(Self { url: url.to_string(), secret: secret.to_string(), client: Client::builder().timeout(timeout).build()? })
+    }
+}
+
+impl EventSink for WebhookSink {
+    fn name(&self) -> &str {
+        &self.url
+    }
+
+    //status selain 2xx dianggap gagal dan dicoba lagi
+    async fn publish(&self, event: &EventEnvelope) -> Result<(), AppError> {
+        let body = serde_json::to_vec(event).map_err(|_| AppError::InternalServerError)?;
+        let mut request = self.client.post(&self.url)
+            .header("content-type", "application/json")
+            .header("x-event-id", &event.id)
+            .header("x-event-type", event.event_type.as_str());
+        if !self.secret.is_empty() {
+            request = request.header("x-signature", format!("sha256={}", webhook_signature(&self.secret, &body)));
+        }
+        request.body(body).send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+// =======================
+// Pemilihan sink
+// =======================
+
+pub enum AnySink {
+    Log(LogSink),
+    Webhook(WebhookSink),
+}
+
+pub fn event_sinks(cfg: &OutboxConfig) -> Result<Vec<AnySink>, AppError> {
+    cfg.sinks.iter()
+        .map(|sink| match sink {
+            EventSinkConfig::Log => Ok(AnySink::Log(LogSink)),
+            EventSinkConfig::Webhook { url, secret, timeout_ms } => {
+                Ok(AnySink::Webhook(WebhookSink::new(url, secret, Duration::from_millis(*timeout_ms))?))
+            }
+        })
+        .collect()
+}
+
+impl EventSink for AnySink {
+    fn name(&self) -> &str {
+        match self {
+            AnySink::Log(sink) => sink.name(),
+            AnySink::Webhook(sink) => sink.name(),
+        }
+    }
+
+    async fn publish(&self, event: &EventEnvelope) -> Result<(), AppError> {
+        match self {
+            AnySink::Log(sink) => sink.publish(event).await,
+            AnySink::Webhook(sink) => sink.publish(event).await,
+        }
+    }
+}
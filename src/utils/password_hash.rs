@@ -0,0 +1,121 @@
+use std::sync::OnceLock;
+use argon2::{Algorithm, Argon2, KeyId, Params, ParamsBuilder, PasswordHash, PasswordHasher, PasswordVerifier, Version, password_hash::{SaltString, rand_core::OsRng}};
+use sqlx::{MySql, Pool};
+use tokio::sync::Semaphore;
+
+use crate::{errors::app_error::AppError, models::config_model::PasswordHashConfig, utils::legacy_hash::{detect, verify_legacy}};
+
+//penanda di string PHC bahwa hash dibuat dengan pepper
+const PEPPER_KEYID: &[u8] = b"pepper";
+
+static LIMITER: OnceLock<Semaphore> = OnceLock::new();
+
+fn hash_error(e: argon2::Error) -> AppError {
+    AppError::HashError(e.into())
+}
+
+fn params(cfg: &PasswordHashConfig) -> Result<Params, AppError> {
+    let mut builder = ParamsBuilder::new();
+    builder.m_cost(cfg.memory_kib).t_cost(cfg.iterations).p_cost(cfg.parallelism);
+    if cfg.pepper.is_some() {
+        builder.keyid(KeyId::new(PEPPER_KEYID).map_err(hash_error)?);
+    }
+    builder.build().map_err(hash_error)
+}
+
+fn argon2(secret: Option<&[u8]>, params: Params) -> Result<Argon2<'_>, AppError> {
+    Ok(match secret {
+        Some(secret) => Argon2::new_with_secret(secret, Algorithm::Argon2id, Version::V0x13, params).map_err(hash_error)?,
+        None => Argon2::new(Algorithm::Argon2id, Version::V0x13, params),
+    })
+}
+
+pub fn hash_blocking(cfg: &PasswordHashConfig, password: &str) -> Result<String, AppError> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hasher = argon2(cfg.pepper.as_deref().map(str::as_bytes), params(cfg)?)?;
+    Ok(hasher.hash_password(password.as_bytes(), &salt)?.to_string())
+}
+
+//parameter verifikasi diambil dari hash itu sendiri, pepper hanya dipakai jika hash ditandai keyid pepper
+pub fn verify_blocking(cfg: &PasswordHashConfig, hash: &str, password: &str) -> Result<bool, AppError> {
+    //hash import dari sistem lama (bcrypt, PBKDF2, scrypt) tidak pernah memakai pepper
+    if let Some(format) = detect(hash) {
+        return verify_legacy(format, hash, password);
+    }
+
+    let parsed = PasswordHash::new(hash).map_err(|_| AppError::Unauthorized)?;
+    let peppered = Params::try_from(&parsed).map(|p| p.keyid() == PEPPER_KEYID).unwrap_or(false);
+
+    let secret = match (peppered, cfg.pepper.as_deref()) {
+        (true, Some(pepper)) => Some(pepper.as_bytes()),
+        (true, None) => {
+            eprintln!("PASSWORD HASH: hash memakai pepper tetapi pepper tidak dikonfigurasi");
+            return Err(AppError::InternalServerError);
+        }
+        (false, _) => None,
+    };
+    let verifier = argon2(secret, Params::default())?;
+    Ok(verifier.verify_password(password.as_bytes(), &parsed).is_ok())
+}
+
+//hash perlu diperbarui jika algoritma, versi, parameter atau status pepper berbeda dari config
+pub fn needs_rehash(cfg: &PasswordHashConfig, hash: &str) -> bool {
+    let Ok(parsed) = PasswordHash::new(hash) else {
+        return true;
+    };
+    let Ok(current) = Params::try_from(&parsed) else {
+        return true;
+    };
+    let Ok(wanted) = params(cfg) else {
+        return false;
+    };
+
+    parsed.algorithm != Algorithm::Argon2id.ident()
+        || parsed.version != Some(Version::V0x13.into())
+        || current.m_cost() != wanted.m_cost()
+        || current.t_cost() != wanted.t_cost()
+        || current.p_cost() != wanted.p_cost()
+        || current.keyid() != wanted.keyid()
+}
+
+//hashing berat dijalankan di thread blocking, jumlahnya dibatasi agar tidak menghabiskan memori
+pub async fn run_limited<T, F>(max_concurrent: usize, job: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+{
+    let limiter = LIMITER.get_or_init(|| Semaphore::new(max_concurrent.max(1)));
+    let _permit = limiter.acquire().await.map_err(|_| AppError::InternalServerError)?;
+    tokio::task::spawn_blocking(job)
+        .await
+        .map_err(|e| {
+            eprintln!("PASSWORD HASH: task gagal: {:?}", e);
+            AppError::InternalServerError
+        })?
+}
+
+//dipanggil setelah login berhasil, gagal rehash tidak menggagalkan login
+pub async fn rehash_if_needed(pool: &Pool<MySql>, cfg: &PasswordHashConfig, user_id: u64, old_hash: &str, password: &str) {
+    if !needs_rehash(cfg, old_hash) {
+        return;
+    }
+
+    let job_cfg = cfg.clone();
+    let job_password = password.to_string();
+    let result = match run_limited(cfg.max_concurrent, move || hash_blocking(&job_cfg, &job_password)).await {
+        //hanya menimpa hash yang sama agar tidak bentrok dengan ganti password bersamaan
+        Ok(new_hash) => sqlx::query("UPDATE users SET password = ? WHERE id = ? AND password = ?")
+            .bind(new_hash)
+            .bind(user_id)
+            .bind(old_hash)
+            .execute(pool)
+            .await
+            .map(|_| ())
+            .map_err(AppError::from),
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        eprintln!("PASSWORD HASH: rehash user {} gagal: {:?}", user_id, e);
+    }
+}
@@ -0,0 +1,105 @@
+use std::{borrow::Cow, io::ErrorKind, path::Path};
+use data_encoding::HEXUPPER;
+use sha1::{Digest, Sha1};
+use validator::{ValidationError, ValidationErrors};
+
+use crate::{errors::app_error::AppError, models::config_model::PasswordPolicyConfig, utils::utils::load_config};
+
+//potongan nama/email yang lebih pendek dari ini tidak dianggap data pribadi
+const MIN_PERSONAL_TOKEN: usize = 3;
+
+fn policy_error(code: &'static str, message: String) -> ValidationError {
+    ValidationError::new(code).with_message(Cow::from(message))
+}
+
+//bagian nama dan email yang tidak boleh muncul di dalam password
+fn personal_tokens(name: &str, email: &str) -> Vec<String> {
+    let local = email.split('@').next().unwrap_or_default();
+    name.split_whitespace()
+        .chain(local.split(['.', '_', '-', '+']))
+        .chain([local])
+        .map(str::to_lowercase)
+        .filter(|t| t.chars().count() >= MIN_PERSONAL_TOKEN)
+        .collect()
+}
+
+//cek aturan lokal (panjang, jenis karakter, data pribadi, skor zxcvbn), semua pelanggaran dikumpulkan
+pub fn check_password(policy: &PasswordPolicyConfig, password: &str, name: &str, email: &str) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let length = password.chars().count();
+
+    if length < policy.min_length {
+        errors.push(policy_error("password_too_short", format!("Password minimal {} karakter", policy.min_length)));
+    }
+    if length > policy.max_length {
+        errors.push(policy_error("password_too_long", format!("Password maksimal {} karakter", policy.max_length)));
+    }
+
+    let classes = [
+        (policy.require_lowercase, password.chars().any(char::is_lowercase), "password_no_lowercase", "huruf kecil"),
+        (policy.require_uppercase, password.chars().any(char::is_uppercase), "password_no_uppercase", "huruf besar"),
+        (policy.require_digit, password.chars().any(|c| c.is_ascii_digit()), "password_no_digit", "angka"),
+        (policy.require_symbol, password.chars().any(|c| !c.is_alphanumeric()), "password_no_symbol", "simbol"),
+    ];
+    for (required, present, code, label) in classes {
+        if required && !present {
+            errors.push(policy_error(code, format!("Password harus mengandung {}", label)));
+        }
+    }
+
+    if policy.disallow_personal_info {
+        let lowered = password.to_lowercase();
+        if personal_tokens(name, email).iter().any(|t| lowered.contains(t.as_str())) {
+            errors.push(policy_error("password_personal_info", "Password tidak boleh memuat nama atau email".to_string()));
+        }
+    }
+
+    //zxcvbn mahal untuk input sangat panjang, dilewati jika panjangnya sudah ditolak
+    if policy.min_score > 0 && length <= policy.max_length {
+        let score = u8::from(zxcvbn::zxcvbn(password, &[name, email]).score());
+        if score < policy.min_score {
+            errors.push(policy_error("password_too_weak", format!("Password terlalu lemah (skor {} dari minimal {})", score, policy.min_score)));
+        }
+    }
+
+    errors
+}
+
+//k-anonymity: hanya file dengan prefix 5 karakter SHA-1 yang dibaca, bukan seluruh daftar
+pub async fn is_breached(dir: &Path, password: &str) -> Result<bool, AppError> {
+    let hash = HEXUPPER.encode(&Sha1::digest(password.as_bytes()));
+    let (prefix, suffix) = hash.split_at(5);
+
+    let content = match tokio::fs::read_to_string(dir.join(format!("{}.txt", prefix))).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(e) => {
+            eprintln!("PASSWORD POLICY: gagal membaca daftar password bocor: {:?}", e);
+            return Err(AppError::InternalServerError);
+        }
+    };
+
+    Ok(content.lines().any(|line| {
+        let (line_suffix, count) = line.trim().split_once(':').unwrap_or((line.trim(), "1"));
+        line_suffix.eq_ignore_ascii_case(suffix) && count.trim() != "0"
+    }))
+}
+
+//dipakai registrasi dan ganti password
+pub async fn enforce_password_policy(password: &str, name: &str, email: &str) -> Result<(), AppError> {
+    let policy = load_config()?.password_policy;
+    let mut errors = check_password(&policy, password, name, email);
+
+    if errors.is_empty() && let Some(dir) = policy.breached_hashes_dir.as_deref() && is_breached(Path::new(dir), password).await? {
+        errors.push(policy_error("password_breached", "Password pernah bocor di kebocoran data, gunakan password lain".to_string()));
+    }
+
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let mut validation = ValidationErrors::new();
+    for error in errors {
+        validation.add("password", error);
+    }
+    Err(AppError::ValidationError(validation))
+}
@@ -0,0 +1,238 @@
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use data_encoding::HEXLOWER;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method, StatusCode};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::{
+    errors::app_error::AppError,
+    models::config_model::S3Config,
+    utils::blob_store::{Blob, BlobStore, check_key, public_url},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+//batas X-Amz-Expires dari S3 (7 hari)
+const MAX_PRESIGN_SECS: u64 = 7 * 24 * 60 * 60;
+
+// =======================
+// AWS Signature Version 4
+// =======================
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    HEXLOWER.encode(&Sha256::digest(data))
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC menerima key dengan panjang apa pun");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+//encode RFC 3986, hanya karakter unreserved yang dibiarkan
+pub fn uri_encode(value: &str, keep_slash: bool) -> String {
+    value.bytes().fold(String::new(), |mut out, b| {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+        out
+    })
+}
+
+pub struct Signer<'a> {
+    pub access_key_id: &'a str,
+    pub secret_access_key: &'a str,
+    pub region: &'a str,
+}
+
+pub struct SignedRequest {
+    pub authorization: String,
+    pub amz_date: String,
+}
+
+impl Signer<'_> {
+    fn scope(&self, now: DateTime<Utc>) -> String {
+        format!("{}/{}/s3/aws4_request", now.format("%Y%m%d"), self.region)
+    }
+
+    fn signature(&self, now: DateTime<Utc>, canonical_request: &str) -> String {
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{}",
+            ALGORITHM,
+            now.format("%Y%m%dT%H%M%SZ"),
+            self.scope(now),
+            sha256_hex(canonical_request.as_bytes())
+        );
+        let date_key = hmac(format!("AWS4{}", self.secret_access_key).as_bytes(), &now.format("%Y%m%d").to_string());
+        let region_key = hmac(&date_key, self.region);
+        let service_key = hmac(&region_key, "s3");
+        let signing_key = hmac(&service_key, "aws4_request");
+        HEXLOWER.encode(&hmac(&signing_key, &string_to_sign))
+    }
+
+    fn canonical_request(method: &str, path: &str, query: &[(String, String)], headers: &[(String, String)], payload_hash: &str) -> String {
+        let mut query: Vec<String> = query.iter().map(|(k, v)| format!("{}={}", uri_encode(k, false), uri_encode(v, false))).collect();
+        query.sort();
+        let canonical_headers: String = headers.iter().map(|(k, v)| format!("{}:{}\n", k, v.trim())).collect();
+        let signed_headers = headers.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>().join(";");
+        format!("{}\n{}\n{}\n{}\n{}\n{}", method, uri_encode(path, true), query.join("&"), canonical_headers, signed_headers, payload_hash)
+    }
+
+    //headers: nama lowercase, harus sudah memuat host, x-amz-content-sha256 dan x-amz-date
+    pub fn sign_headers(&self, method: &str, path: &str, headers: &[(&str, &str)], payload_hash: &str, now: DateTime<Utc>) -> SignedRequest {
+        let mut headers: Vec<(String, String)> = headers.iter().map(|(k, v)| (k.to_ascii_lowercase(), v.to_string())).collect();
+        headers.sort();
+        let canonical = Self::canonical_request(method, path, &[], &headers, payload_hash);
+        let signed_headers = headers.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>().join(";");
+        SignedRequest {
+            authorization: format!(
+                "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+                ALGORITHM, self.access_key_id, self.scope(now), signed_headers, self.signature(now, &canonical)
+            ),
+            amz_date: now.format("%Y%m%dT%H%M%SZ").to_string(),
+        }
+    }
+
+    //query string presigned GET, hanya header host yang ditandatangani
+    pub fn presign_query(&self, method: &str, host: &str, path: &str, expires: Duration, now: DateTime<Utc>) -> String {
+        let mut query = vec![
+            ("X-Amz-Algorithm".to_string(), ALGORITHM.to_string()),
+            ("X-Amz-Credential".to_string(), format!("{}/{}", self.access_key_id, self.scope(now))),
+            ("X-Amz-Date".to_string(), now.format("%Y%m%dT%H%M%SZ").to_string()),
+            ("X-Amz-Expires".to_string(), expires.as_secs().clamp(1, MAX_PRESIGN_SECS).to_string()),
+            ("X-Amz-SignedHeaders".to_string(), "host".to_string()),
+        ];
+        let canonical = Self::canonical_request(method, path, &query, &[("host".to_string(), host.to_string())], UNSIGNED_PAYLOAD);
+        query.push(("X-Amz-Signature".to_string(), self.signature(now, &canonical)));
+
+        let mut encoded: Vec<String> = query.iter().map(|(k, v)| format!("{}={}", uri_encode(k, false), uri_encode(v, false))).collect();
+        encoded.sort();
+        encoded.join("&")
+    }
+}
+
+// =======================
+// S3-compatible backend
+// =======================
+
+//AWS S3, MinIO, R2 dan sejenisnya; path_style untuk server yang tidak mendukung bucket di subdomain
+pub struct S3BlobStore {
+    cfg: S3Config,
+    public_url: String,
+    client: Client,
+}
+
+impl S3BlobStore {
+    pub fn new(cfg: S3Config, public_url: &str) -> Result<Self, AppError> {
+        let client = Client::builder()
+            .connect_timeout(Duration::from_millis(cfg.connect_timeout_ms))
+            .timeout(Duration::from_millis(cfg.timeout_ms))
+            .build()?;
+        Ok(Self { cfg, public_url: public_url.to_string(), client })
+    }
+
+    fn signer(&self) -> Signer<'_> {
+        Signer { access_key_id: &self.cfg.access_key_id, secret_access_key: &self.cfg.secret_access_key, region: &self.cfg.region }
+    }
+
+    //(URL objek, host untuk tanda tangan, path untuk tanda tangan)
+    fn object_url(&self, key: &str) -> Result<(Url, String, String), AppError> {
+        let mut url = Url::parse(&self.cfg.endpoint).map_err(|_| AppError::InternalServerError)?;
+        let endpoint_path = url.path().trim_end_matches('/').to_string();
+        let path = if self.cfg.path_style {
+            format!("{}/{}/{}", endpoint_path, self.cfg.bucket, key)
+        } else {
+            let host = format!("{}.{}", self.cfg.bucket, url.host_str().unwrap_or_default());
+            url.set_host(Some(&host)).map_err(|_| AppError::InternalServerError)?;
+            format!("{}/{}", endpoint_path, key)
+        };
+        url.set_path(&uri_encode(&path, true));
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        Ok((url, host, path))
+    }
+
+    async fn send(&self, method: Method, key: &str, body: Option<(Vec<u8>, &str)>) -> Result<reqwest::Response, AppError> {
+        check_key(key)?;
+        let (url, host, path) = self.object_url(key)?;
+        let payload_hash = sha256_hex(body.as_ref().map(|(data, _)| data.as_slice()).unwrap_or_default());
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let signed = self.signer().sign_headers(
+            method.as_str(),
+            &path,
+            &[("host", &host), ("x-amz-content-sha256", &payload_hash), ("x-amz-date", &amz_date)],
+            &payload_hash,
+            now,
+        );
+
+        let mut request = self.client.request(method, url)
+            .header("x-amz-content-sha256", &payload_hash)
+            .header("x-amz-date", signed.amz_date)
+            .header("authorization", signed.authorization);
+        if let Some((data, content_type)) = body {
+            request = request.header("content-type", content_type).body(data);
+        }
+        Ok(request.send().await?)
+    }
+}
+
+fn s3_error(status: StatusCode, action: &str, key: &str) -> AppError {
+    eprintln!("S3: {} {} gagal dengan status {}", action, key, status);
+    AppError::InternalServerError
+}
+
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<(), AppError> {
+        let res = self.send(Method::PUT, key, Some((data, content_type))).await?;
+        if !res.status().is_success() {
+            return Err(s3_error(res.status(), "PUT", key));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Blob>, AppError> {
+        let res = self.send(Method::GET, key, None).await?;
+        match res.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                let content_type = res.headers().get("content-type")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                Ok(Some(Blob { data: res.bytes().await?.to_vec(), content_type }))
+            }
+            status => Err(s3_error(status, "GET", key)),
+        }
+    }
+
+    //DELETE S3 selalu 204 walaupun objek tidak ada
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        let res = self.send(Method::DELETE, key, None).await?;
+        match res.status() {
+            StatusCode::NOT_FOUND => Ok(()),
+            status if status.is_success() => Ok(()),
+            status => Err(s3_error(status, "DELETE", key)),
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        public_url(&self.public_url, key)
+    }
+
+    fn presigned_url(&self, key: &str, expires: Duration) -> Result<String, AppError> {
+        check_key(key)?;
+        let (mut url, host, path) = self.object_url(key)?;
+        let query = self.signer().presign_query("GET", &host, &path, expires, Utc::now());
+        url.set_query(Some(&query));
+        Ok(url.to_string())
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::BTreeMap;
+use sqlx::{FromRow, MySql, Pool, QueryBuilder};
+
+use crate::{
+    configs::db,
+    errors::app_error::AppError,
+    models::{config_model::{SearchBackendKind, SearchConfig}, user_model::User},
+    utils::{attributes::attribute_text, search_index::embedded_search, search_query::{CmpOp, DateField, Filter, SearchExpr, TextField}},
+};
+
+//kata lebih pendek dari ini tidak masuk index FULLTEXT InnoDB (innodb_ft_min_token_size)
+const MIN_FULLTEXT_LEN: usize = 3;
+
+pub struct SearchHit {
+    pub user: User,
+    pub score: f64,
+}
+
+pub struct SearchResult {
+    pub hits: Vec<SearchHit>,
+    pub total: u64,
+}
+
+//backend pencarian bisa diganti lewat config search.backend
+pub trait SearchBackend {
+    fn search(&self, expr: &SearchExpr, offset: u64, limit: u64) -> impl Future<Output = Result<SearchResult, AppError>> + Send;
+}
+
+pub async fn run_search(cfg: &SearchConfig, expr: &SearchExpr, offset: u64, limit: u64) -> Result<SearchResult, AppError> {
+    match cfg.backend {
+        SearchBackendKind::Mysql => MySqlSearch::new(db::get_pool().await?).search(expr, offset, limit).await,
+        SearchBackendKind::Embedded => embedded_search(expr, offset, limit).await,
+    }
+}
+
+// =======================
+// MySQL (FULLTEXT) backend
+// =======================
+
+pub struct MySqlSearch {
+    pool: Pool<MySql>,
+}
+
+#[derive(FromRow)]
+struct SearchRow {
+    #[sqlx(flatten)]
+    user: User,
+    score: f64,
+}
+
+impl MySqlSearch {
+    pub fn new(pool: Pool<MySql>) -> Self {
+        Self { pool }
+    }
+}
+
+fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+//kata bebas menjadi term boolean mode: kata tunggal dicari sebagai prefix, beberapa kata sebagai frasa.
+//dipecah di setiap karakter non-alfanumerik seperti parser FULLTEXT, budi@corp.com menjadi frasa "budi corp com"
+pub fn fulltext_term(text: &str) -> Option<String> {
+    let words: Vec<&str> = text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    if words.iter().all(|w| w.chars().count() < MIN_FULLTEXT_LEN) {
+        return None;
+    }
+    match words.as_slice() {
+        [word] => Some(format!("{}*", word)),
+        words => Some(format!("\"{}\"", words.join(" "))),
+    }
+}
+
+fn push_filter(builder: &mut QueryBuilder<'_, MySql>, filter: &Filter) {
+    match filter {
+        Filter::Text(text) => match fulltext_term(text) {
+            Some(term) => {
+                builder.push("MATCH(name, email) AGAINST (").push_bind(term).push(" IN BOOLEAN MODE)");
+            }
+            //kata pendek tidak ada di index FULLTEXT, jatuh ke LIKE
+            None => {
+                let pattern = format!("%{}%", escape_like(text));
+                builder.push("(name LIKE ").push_bind(pattern.clone()).push(" OR email LIKE ").push_bind(pattern).push(")");
+            }
+        },
+        Filter::Match { field, pattern } => {
+            let column = match field {
+                TextField::Name => "name",
+                TextField::Email => "email",
+            };
+            if pattern.contains('*') {
+                builder.push(column).push(" LIKE ").push_bind(escape_like(pattern).replace('*', "%"));
+            } else {
+                builder.push(column).push(" = ").push_bind(pattern.clone());
+            }
+        }
+        Filter::Id { op, value } => {
+            let op = match op {
+                CmpOp::Eq => " = ",
+                CmpOp::Gt => " > ",
+                CmpOp::Gte => " >= ",
+                CmpOp::Lt => " < ",
+                CmpOp::Lte => " <= ",
+            };
+            builder.push("id").push(op).push_bind(*value);
+        }
+        Filter::Date { field, from, to } => {
+            let column = match field {
+                DateField::Created => "created_at",
+                DateField::Updated => "updated_at",
+            };
+            builder.push("(TRUE");
+            if let Some(from) = from {
+                builder.push(" AND ").push(column).push(" >= ").push_bind(*from);
+            }
+            if let Some(to) = to {
+                builder.push(" AND ").push(column).push(" < ").push_bind(*to);
+            }
+            builder.push(")");
+        }
+        Filter::Attribute { name, pattern } => {
+            //user tanpa atribut ini bernilai FALSE (bukan NULL) agar NOT tetap mengikutkannya
+            let pattern = pattern.to_lowercase();
+            builder.push("COALESCE(LOWER(JSON_UNQUOTE(JSON_EXTRACT(attributes, ").push_bind(format!("$.{}", name)).push(")))");
+            if pattern.contains('*') {
+                builder.push(" LIKE ").push_bind(escape_like(&pattern).replace('*', "%"));
+            } else {
+                builder.push(" = ").push_bind(pattern);
+            }
+            builder.push(", FALSE)");
+        }
+    }
+}
+
+pub fn push_expr(builder: &mut QueryBuilder<'_, MySql>, expr: &SearchExpr) {
+    match expr {
+        SearchExpr::And(items) | SearchExpr::Or(items) if items.is_empty() => {
+            builder.push("TRUE");
+        }
+        SearchExpr::And(items) | SearchExpr::Or(items) => {
+            let joiner = if matches!(expr, SearchExpr::And(_)) { " AND " } else { " OR " };
+            builder.push("(");
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    builder.push(joiner);
+                }
+                push_expr(builder, item);
+            }
+            builder.push(")");
+        }
+        SearchExpr::Not(inner) => {
+            builder.push("NOT (");
+            push_expr(builder, inner);
+            builder.push(")");
+        }
+        SearchExpr::Filter(filter) => push_filter(builder, filter),
+    }
+}
+
+//semua kata bebas yang tidak dinegasikan dipakai untuk skor relevansi
+fn ranking_text(expr: &SearchExpr) -> Option<String> {
+    let words: Vec<&str> = expr.positive_filters().into_iter()
+        .filter_map(|f| match f {
+            Filter::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+pub fn select_query<'a>(expr: &SearchExpr, offset: u64, limit: u64) -> QueryBuilder<'a, MySql> {
+    let mut builder = QueryBuilder::new("SELECT users.*, ");
+    match ranking_text(expr) {
+        Some(text) => {
+            builder.push("CAST(MATCH(name, email) AGAINST (").push_bind(text).push(" IN NATURAL LANGUAGE MODE) AS DOUBLE)");
+        }
+        None => {
+            builder.push("CAST(0 AS DOUBLE)");
+        }
+    }
+    builder.push(" AS score FROM users WHERE deleted_at IS NULL AND ");
+    push_expr(&mut builder, expr);
+    builder.push(" ORDER BY score DESC, id ASC LIMIT ").push_bind(limit).push(" OFFSET ").push_bind(offset);
+    builder
+}
+
+pub fn count_query<'a>(expr: &SearchExpr) -> QueryBuilder<'a, MySql> {
+    let mut builder = QueryBuilder::new("SELECT COUNT(*) FROM users WHERE deleted_at IS NULL AND ");
+    push_expr(&mut builder, expr);
+    builder
+}
+
+impl SearchBackend for MySqlSearch {
+    async fn search(&self, expr: &SearchExpr, offset: u64, limit: u64) -> Result<SearchResult, AppError> {
+        let (total,): (i64,) = count_query(expr).build_query_as().fetch_one(&self.pool).await?;
+        let rows: Vec<SearchRow> = select_query(expr, offset, limit).build_query_as().fetch_all(&self.pool).await?;
+
+        Ok(SearchResult {
+            hits: rows.into_iter().map(|r| SearchHit { user: r.user, score: r.score }).collect(),
+            total: total as u64,
+        })
+    }
+}
+
+// =======================
+// Highlight
+// =======================
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+//bagian teks yang cocok dibungkus <mark>, teks lain di-escape agar aman ditampilkan sebagai HTML
+pub fn highlight(text: &str, terms: &[&str]) -> Option<String> {
+    //lowercase ASCII menjaga posisi byte tetap sama dengan teks asli
+    let lowered = text.to_ascii_lowercase();
+    let mut ranges: Vec<(usize, usize)> = terms.iter()
+        .map(|t| t.to_ascii_lowercase())
+        .filter(|t| !t.is_empty())
+        .flat_map(|t| lowered.match_indices(t.as_str()).map(|(i, m)| (i, i + m.len())).collect::<Vec<_>>())
+        .collect();
+    if ranges.is_empty() {
+        return None;
+    }
+
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    let mut out = String::new();
+    let mut pos = 0;
+    for (start, end) in merged {
+        out.push_str(&escape_html(&text[pos..start]));
+        out.push_str("<mark>");
+        out.push_str(&escape_html(&text[start..end]));
+        out.push_str("</mark>");
+        pos = end;
+    }
+    out.push_str(&escape_html(&text[pos..]));
+    Some(out)
+}
+
+pub fn highlights(expr: &SearchExpr, user: &User) -> BTreeMap<String, String> {
+    let mut name_terms = Vec::new();
+    let mut email_terms = Vec::new();
+    for filter in expr.positive_filters() {
+        match filter {
+            Filter::Text(text) => {
+                name_terms.extend(text.split_whitespace());
+                email_terms.extend(text.split_whitespace());
+            }
+            Filter::Match { field: TextField::Name, pattern } => name_terms.extend(pattern.split('*')),
+            Filter::Match { field: TextField::Email, pattern } => email_terms.extend(pattern.split('*')),
+            _ => {}
+        }
+    }
+
+    let mut result = BTreeMap::new();
+    if let Some(name) = highlight(&user.name, &name_terms) {
+        result.insert("name".to_string(), name);
+    }
+    if let Some(email) = highlight(&user.email, &email_terms) {
+        result.insert("email".to_string(), email);
+    }
+    for filter in expr.positive_filters() {
+        if let Filter::Attribute { name, pattern } = filter
+            && let Some(value) = user.attributes.get(name)
+            && let Some(marked) = highlight(&attribute_text(value), &pattern.split('*').collect::<Vec<_>>())
+        {
+            result.insert(format!("attr.{}", name), marked);
+        }
+    }
+    result
+}
@@ -0,0 +1,435 @@
+use std::{collections::HashMap, ops::Bound, path::Path, sync::{Mutex, OnceLock}};
+use sqlx::{MySql, Pool};
+use tantivy::{
+    Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, TantivyError, Term,
+    collector::{Count, TopDocs},
+    directory::MmapDirectory,
+    doc,
+    query::{AllQuery, BooleanQuery, FuzzyTermQuery, Occur, Query, RangeQuery, RegexQuery, TermQuery},
+    schema::{FAST, Field, INDEXED, IndexRecordOption, STORED, STRING, Schema, TEXT, Value},
+};
+
+use crate::{
+    configs::db,
+    errors::app_error::AppError,
+    models::{config_model::SearchBackendKind, user_model::User},
+    utils::{
+        attributes::attribute_text,
+        search::{SearchBackend, SearchHit, SearchResult},
+        search_query::{CmpOp, DateField, Filter, SearchExpr, TextField},
+        utils::load_config,
+    },
+};
+
+//memori writer tantivy, minimal 15MB per thread
+const WRITER_MEMORY: usize = 20_000_000;
+const REBUILD_BATCH: i64 = 1000;
+
+static INDEX: OnceLock<UserIndex> = OnceLock::new();
+
+#[derive(Clone, Copy)]
+struct Fields {
+    id: Field,
+    //ditokenisasi, untuk kata bebas dan fuzzy
+    name: Field,
+    email: Field,
+    //utuh dan lowercase, untuk name:/email: dengan wildcard
+    name_raw: Field,
+    email_raw: Field,
+    created_at: Field,
+    updated_at: Field,
+    //satu nilai per atribut custom: "<nama>=<nilai lowercase>"
+    attributes: Field,
+}
+
+fn schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let fields = Fields {
+        id: builder.add_u64_field("id", INDEXED | STORED | FAST),
+        name: builder.add_text_field("name", TEXT),
+        email: builder.add_text_field("email", TEXT),
+        name_raw: builder.add_text_field("name_raw", STRING),
+        email_raw: builder.add_text_field("email_raw", STRING),
+        created_at: builder.add_i64_field("created_at", INDEXED | FAST),
+        updated_at: builder.add_i64_field("updated_at", INDEXED | FAST),
+        attributes: builder.add_text_field("attributes", STRING),
+    };
+    (builder.build(), fields)
+}
+
+//typo yang ditoleransi bertambah dengan panjang kata
+fn fuzzy_distance(word: &str) -> u8 {
+    match word.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+fn wildcard_regex(pattern: &str) -> String {
+    pattern.to_lowercase().split('*').map(regex_escape).collect::<Vec<_>>().join(".*")
+}
+
+fn regex_escape(text: &str) -> String {
+    text.chars().fold(String::new(), |mut out, c| {
+        if "\\.+*?()|[]{}^$#&-~\"@<>".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+        out
+    })
+}
+
+//index user di disk, turunan dari tabel users dan bisa dibangun ulang kapan saja
+pub struct UserIndex {
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    fields: Fields,
+}
+
+impl UserIndex {
+    pub fn open(dir: &Path) -> Result<Self, AppError> {
+        std::fs::create_dir_all(dir).map_err(TantivyError::from)?;
+        let directory = MmapDirectory::open(dir).map_err(TantivyError::from)?;
+        let (schema, _) = schema();
+        match Index::open_or_create(directory, schema.clone()) {
+            //schema berubah (misal field baru), index lama dibuang dan harus diisi ulang
+            Err(TantivyError::SchemaError(_)) => {
+                eprintln!("SEARCH INDEX: schema berubah, index dibuat ulang; jalankan rebuild-search-index");
+                std::fs::remove_dir_all(dir).map_err(TantivyError::from)?;
+                std::fs::create_dir_all(dir).map_err(TantivyError::from)?;
+                Self::from_index(Index::create_in_dir(dir, schema)?)
+            }
+            index => Self::from_index(index?),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn in_memory() -> Result<Self, AppError> {
+        let (schema, _) = schema();
+        Self::from_index(Index::create_in_ram(schema))
+    }
+
+    fn from_index(index: Index) -> Result<Self, AppError> {
+        let (_, fields) = schema();
+        let reader = index.reader_builder().reload_policy(ReloadPolicy::Manual).try_into()?;
+        let writer = index.writer_with_num_threads(1, WRITER_MEMORY)?;
+        Ok(Self { reader, writer: Mutex::new(writer), fields })
+    }
+
+    fn document(&self, user: &User) -> TantivyDocument {
+        let f = self.fields;
+        let mut document = doc!(
+            f.id => user.id,
+            f.name => user.name.as_str(),
+            f.email => user.email.as_str(),
+            f.name_raw => user.name.to_lowercase(),
+            f.email_raw => user.email.to_lowercase(),
+            f.created_at => user.created_at.timestamp_millis(),
+            f.updated_at => user.updated_at.timestamp_millis(),
+        );
+        for (name, value) in &user.attributes {
+            document.add_text(f.attributes, format!("{}={}", name, attribute_text(value).to_lowercase()));
+        }
+        document
+    }
+
+    //commit lalu reload agar perubahan langsung terlihat di pencarian berikutnya
+    fn commit(&self, writer: &mut IndexWriter) -> Result<(), AppError> {
+        writer.commit()?;
+        Ok(self.reader.reload()?)
+    }
+
+    pub fn upsert(&self, user: &User) -> Result<(), AppError> {
+        let mut writer = self.writer.lock().map_err(|_| AppError::InternalServerError)?;
+        writer.delete_term(Term::from_field_u64(self.fields.id, user.id));
+        writer.add_document(self.document(user))?;
+        self.commit(&mut writer)
+    }
+
+    pub fn remove(&self, id: u64) -> Result<(), AppError> {
+        let mut writer = self.writer.lock().map_err(|_| AppError::InternalServerError)?;
+        writer.delete_term(Term::from_field_u64(self.fields.id, id));
+        self.commit(&mut writer)
+    }
+
+    //hapus semua dokumen lalu isi ulang per batch, satu commit di akhir agar pencarian tidak pernah melihat index kosong.
+    //batch yang gagal dibaca membatalkan semua perubahan sehingga index lama tetap utuh
+    pub fn replace_all(&self, batches: impl IntoIterator<Item = Result<Vec<User>, AppError>>) -> Result<usize, AppError> {
+        let mut writer = self.writer.lock().map_err(|_| AppError::InternalServerError)?;
+        let result = (|| {
+            writer.delete_all_documents()?;
+            let mut count = 0;
+            for batch in batches {
+                for user in &batch? {
+                    writer.add_document(self.document(user))?;
+                    count += 1;
+                }
+            }
+            Ok(count)
+        })();
+        match result {
+            Ok(count) => self.commit(&mut writer).map(|_| count),
+            Err(e) => {
+                writer.rollback()?;
+                Err(e)
+            }
+        }
+    }
+
+    fn text_query(&self, text: &str) -> Box<dyn Query> {
+        let f = self.fields;
+        let mut words = Vec::new();
+        for word in text.split_whitespace().map(str::to_lowercase) {
+            let word: String = word.chars().filter(|c| c.is_alphanumeric()).collect();
+            if word.is_empty() {
+                continue;
+            }
+            //nama toleran typo dan prefix, email cukup prefix
+            let clauses: Vec<(Occur, Box<dyn Query>)> = vec![
+                (Occur::Should, Box::new(FuzzyTermQuery::new_prefix(Term::from_field_text(f.name, &word), fuzzy_distance(&word), true))),
+                (Occur::Should, Box::new(FuzzyTermQuery::new_prefix(Term::from_field_text(f.email, &word), 0, true))),
+            ];
+            words.push((Occur::Must, Box::new(BooleanQuery::new(clauses)) as Box<dyn Query>));
+        }
+        if words.is_empty() {
+            return Box::new(AllQuery);
+        }
+        Box::new(BooleanQuery::new(words))
+    }
+
+    fn filter_query(&self, filter: &Filter) -> Result<Box<dyn Query>, AppError> {
+        let f = self.fields;
+        Ok(match filter {
+            Filter::Text(text) => self.text_query(text),
+            Filter::Match { field, pattern } => {
+                let raw = match field {
+                    TextField::Name => f.name_raw,
+                    TextField::Email => f.email_raw,
+                };
+                if pattern.contains('*') {
+                    Box::new(RegexQuery::from_pattern(&wildcard_regex(pattern), raw)?)
+                } else {
+                    Box::new(TermQuery::new(Term::from_field_text(raw, &pattern.to_lowercase()), IndexRecordOption::Basic))
+                }
+            }
+            Filter::Id { op, value } => {
+                let term = |v| Term::from_field_u64(f.id, v);
+                let (lower, upper) = match op {
+                    CmpOp::Eq => (Bound::Included(term(*value)), Bound::Included(term(*value))),
+                    CmpOp::Gt => (Bound::Excluded(term(*value)), Bound::Unbounded),
+                    CmpOp::Gte => (Bound::Included(term(*value)), Bound::Unbounded),
+                    CmpOp::Lt => (Bound::Unbounded, Bound::Excluded(term(*value))),
+                    CmpOp::Lte => (Bound::Unbounded, Bound::Included(term(*value))),
+                };
+                Box::new(RangeQuery::new(lower, upper))
+            }
+            Filter::Attribute { name, pattern } => {
+                if pattern.contains('*') {
+                    let regex = format!("{}={}", regex_escape(name), wildcard_regex(pattern));
+                    Box::new(RegexQuery::from_pattern(&regex, f.attributes)?)
+                } else {
+                    let term = format!("{}={}", name, pattern.to_lowercase());
+                    Box::new(TermQuery::new(Term::from_field_text(f.attributes, &term), IndexRecordOption::Basic))
+                }
+            }
+            Filter::Date { field, from, to } => {
+                let field = match field {
+                    DateField::Created => f.created_at,
+                    DateField::Updated => f.updated_at,
+                };
+                let bound = |at: Option<chrono::DateTime<chrono::Utc>>, upper: bool| match at {
+                    Some(at) if upper => Bound::Excluded(Term::from_field_i64(field, at.timestamp_millis())),
+                    Some(at) => Bound::Included(Term::from_field_i64(field, at.timestamp_millis())),
+                    None => Bound::Unbounded,
+                };
+                match (from, to) {
+                    (None, None) => Box::new(AllQuery),
+                    _ => Box::new(RangeQuery::new(bound(*from, false), bound(*to, true))),
+                }
+            }
+        })
+    }
+
+    pub fn query(&self, expr: &SearchExpr) -> Result<Box<dyn Query>, AppError> {
+        Ok(match expr {
+            SearchExpr::And(items) | SearchExpr::Or(items) if items.is_empty() => Box::new(AllQuery),
+            SearchExpr::And(items) => {
+                let clauses = items.iter().map(|e| Ok((Occur::Must, self.query(e)?))).collect::<Result<Vec<_>, AppError>>()?;
+                Box::new(BooleanQuery::new(clauses))
+            }
+            SearchExpr::Or(items) => {
+                let clauses = items.iter().map(|e| Ok((Occur::Should, self.query(e)?))).collect::<Result<Vec<_>, AppError>>()?;
+                Box::new(BooleanQuery::new(clauses))
+            }
+            //tantivy butuh klausa positif, NOT x = semua dokumen kecuali x
+            SearchExpr::Not(inner) => Box::new(BooleanQuery::new(vec![
+                (Occur::Must, Box::new(AllQuery) as Box<dyn Query>),
+                (Occur::MustNot, self.query(inner)?),
+            ])),
+            SearchExpr::Filter(filter) => self.filter_query(filter)?,
+        })
+    }
+
+    //id user berurutan menurut skor, beserta jumlah total yang cocok
+    pub fn search_ids(&self, expr: &SearchExpr, offset: u64, limit: u64) -> Result<(Vec<(u64, f64)>, u64), AppError> {
+        let query = self.query(expr)?;
+        let searcher = self.reader.searcher();
+        let top = TopDocs::with_limit(limit.max(1) as usize).and_offset(offset as usize).order_by_score();
+        let (docs, total) = searcher.search(&query, &(top, Count))?;
+
+        let mut ids = Vec::with_capacity(docs.len());
+        for (score, address) in docs {
+            let doc: TantivyDocument = searcher.doc(address)?;
+            if let Some(id) = doc.get_first(self.fields.id).and_then(|v| v.as_u64()) {
+                ids.push((id, score as f64));
+            }
+        }
+        Ok((ids, total as u64))
+    }
+}
+
+//index global dibuka sekali di direktori search.index_dir
+pub fn user_index() -> Result<&'static UserIndex, AppError> {
+    if let Some(index) = INDEX.get() {
+        return Ok(index);
+    }
+    let index = UserIndex::open(Path::new(&load_config()?.search.index_dir))?;
+    Ok(INDEX.get_or_init(|| index))
+}
+
+// =======================
+// Sinkronisasi dari user_controller
+// =======================
+
+fn embedded_enabled() -> bool {
+    load_config().map(|c| c.search.backend == SearchBackendKind::Embedded).unwrap_or(false)
+}
+
+async fn apply(id: u64, user: Option<User>) -> Result<(), AppError> {
+    let index = user_index()?;
+    tokio::task::spawn_blocking(move || match user {
+        Some(user) => index.upsert(&user),
+        None => index.remove(id),
+    })
+    .await
+    .map_err(|_| AppError::InternalServerError)?
+}
+
+//baca ulang user dari database lalu perbarui index, user yang sudah dihapus dikeluarkan dari index
+//index hanya turunan data, gagal sinkron cukup dicatat dan diperbaiki dengan rebuild-search-index
+pub async fn index_user(id: u64) {
+    if !embedded_enabled() {
+        return;
+    }
+    let result = async {
+        let pool = db::get_pool().await?;
+        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ? AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&pool)
+            .await?;
+        apply(id, user).await
+    }.await;
+    if let Err(e) = result {
+        eprintln!("SEARCH INDEX: gagal sinkron user {}: {:?}", id, e);
+    }
+}
+
+pub async fn unindex_user(id: u64) {
+    if !embedded_enabled() {
+        return;
+    }
+    if let Err(e) = apply(id, None).await {
+        eprintln!("SEARCH INDEX: gagal menghapus user {} dari index: {:?}", id, e);
+    }
+}
+
+//dipakai setelah import massal, satu rebuild lebih murah daripada commit per user
+pub async fn reindex_all() {
+    if !embedded_enabled() {
+        return;
+    }
+    let result = async { rebuild_index(&db::get_pool().await?, user_index()?).await }.await;
+    if let Err(e) = result {
+        eprintln!("SEARCH INDEX: gagal rebuild index: {:?}", e);
+    }
+}
+
+//batch langsung diteruskan ke writer sehingga hanya satu batch yang ada di memori
+pub async fn rebuild_index(pool: &Pool<MySql>, index: &'static UserIndex) -> Result<usize, AppError> {
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<Result<Vec<User>, AppError>>(1);
+    let writer = tokio::task::spawn_blocking(move || index.replace_all(std::iter::from_fn(|| rx.blocking_recv())));
+
+    let mut last_id = 0u64;
+    loop {
+        let batch = sqlx::query_as::<_, User>("SELECT * FROM users WHERE deleted_at IS NULL AND id > ? ORDER BY id LIMIT ?")
+            .bind(last_id)
+            .bind(REBUILD_BATCH)
+            .fetch_all(pool)
+            .await
+            .map_err(AppError::from);
+        let last = match &batch {
+            Ok(users) => users.last().map(|u| u.id),
+            Err(_) => None,
+        };
+        //writer berhenti lebih awal jika gagal menulis, errornya diambil dari hasil task
+        if tx.send(batch).await.is_err() {
+            break;
+        }
+        match last {
+            Some(id) => last_id = id,
+            None => break,
+        }
+    }
+    drop(tx);
+
+    writer.await.map_err(|_| AppError::InternalServerError)?
+}
+
+// =======================
+// Embedded backend
+// =======================
+
+pub struct EmbeddedSearch {
+    pool: Pool<MySql>,
+    index: &'static UserIndex,
+}
+
+impl EmbeddedSearch {
+    pub fn new(pool: Pool<MySql>, index: &'static UserIndex) -> Self {
+        Self { pool, index }
+    }
+}
+
+impl SearchBackend for EmbeddedSearch {
+    //index hanya menentukan id dan urutan, data user tetap diambil dari MySQL
+    async fn search(&self, expr: &SearchExpr, offset: u64, limit: u64) -> Result<SearchResult, AppError> {
+        let (ids, total) = self.index.search_ids(expr, offset, limit)?;
+        if ids.is_empty() {
+            return Ok(SearchResult { hits: Vec::new(), total });
+        }
+
+        let mut builder = sqlx::QueryBuilder::<MySql>::new("SELECT * FROM users WHERE deleted_at IS NULL AND id IN (");
+        let mut separated = builder.separated(", ");
+        for (id, _) in &ids {
+            separated.push_bind(*id);
+        }
+        builder.push(")");
+        let mut users: HashMap<u64, User> = builder.build_query_as::<User>()
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|u| (u.id, u))
+            .collect();
+
+        let hits = ids.into_iter()
+            .filter_map(|(id, score)| users.remove(&id).map(|user| SearchHit { user, score }))
+            .collect();
+        Ok(SearchResult { hits, total })
+    }
+}
+
+pub async fn embedded_search(expr: &SearchExpr, offset: u64, limit: u64) -> Result<SearchResult, AppError> {
+    let index = user_index()?;
+    EmbeddedSearch::new(db::get_pool().await?, index).search(expr, offset, limit).await
+}
@@ -0,0 +1,301 @@
+use std::borrow::Cow;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use validator::{ValidationError, ValidationErrors};
+
+use crate::errors::app_error::AppError;
+
+//batas agar query tidak menghasilkan SQL yang terlalu besar
+pub const MAX_QUERY_LEN: usize = 500;
+pub const MAX_TERMS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextField {
+    Name,
+    Email,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateField {
+    Created,
+    Updated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CmpOp {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    //kata bebas, dicari lewat full-text di name dan email
+    Text(String),
+    //name:budi atau email:*@corp.com, tanda * adalah wildcard
+    Match { field: TextField, pattern: String },
+    Id { op: CmpOp, value: u64 },
+    //rentang [from, to), salah satu boleh kosong
+    Date { field: DateField, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>> },
+    //attr.department:eng* mencocokkan nilai atribut custom sebagai teks, tanpa membedakan huruf besar
+    Attribute { name: String, pattern: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchExpr {
+    And(Vec<SearchExpr>),
+    Or(Vec<SearchExpr>),
+    Not(Box<SearchExpr>),
+    Filter(Filter),
+}
+
+impl SearchExpr {
+    //semua filter yang tidak dinegasikan, dipakai untuk ranking dan highlight
+    pub fn positive_filters(&self) -> Vec<&Filter> {
+        match self {
+            SearchExpr::And(items) | SearchExpr::Or(items) => items.iter().flat_map(SearchExpr::positive_filters).collect(),
+            SearchExpr::Not(_) => Vec::new(),
+            SearchExpr::Filter(filter) => vec![filter],
+        }
+    }
+
+    //nama atribut yang dipakai, termasuk di bawah NOT
+    pub fn attribute_names(&self) -> Vec<&str> {
+        match self {
+            SearchExpr::And(items) | SearchExpr::Or(items) => items.iter().flat_map(SearchExpr::attribute_names).collect(),
+            SearchExpr::Not(inner) => inner.attribute_names(),
+            SearchExpr::Filter(Filter::Attribute { name, .. }) => vec![name],
+            SearchExpr::Filter(_) => Vec::new(),
+        }
+    }
+}
+
+fn syntax_error(message: String) -> AppError {
+    let mut errors = ValidationErrors::new();
+    errors.add("q", ValidationError::new("search_syntax").with_message(Cow::from(message)));
+    AppError::ValidationError(errors)
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    Or,
+    And,
+    Not,
+    Word(String),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, AppError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            //"-" di awal kata atau kurung berarti NOT
+            '-' => {
+                chars.next();
+                tokens.push(Token::Not);
+            }
+            _ => {
+                let mut word = String::new();
+                let mut quoted = false;
+                while let Some(&c) = chars.peek() {
+                    if !quoted && (c.is_whitespace() || c == '(' || c == ')') {
+                        break;
+                    }
+                    if c == '"' {
+                        quoted = !quoted;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                if quoted {
+                    return Err(syntax_error("Tanda kutip tidak ditutup".to_string()));
+                }
+                tokens.push(match word.as_str() {
+                    "OR" => Token::Or,
+                    "AND" => Token::And,
+                    "NOT" => Token::Not,
+                    _ => Token::Word(word),
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn unquote(value: &str) -> String {
+    value.replace('"', "")
+}
+
+fn parse_date(value: &str, op: CmpOp, field: DateField) -> Result<Filter, AppError> {
+    //tanggal saja berarti satu hari penuh (UTC), datetime RFC 3339 dipakai apa adanya
+    let (start, end) = if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let start = day.and_hms_opt(0, 0, 0).map(|d| d.and_utc());
+        let end = day.checked_add_days(Days::new(1)).and_then(|d| d.and_hms_opt(0, 0, 0)).map(|d| d.and_utc());
+        (start, end)
+    } else if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        let at = at.with_timezone(&Utc);
+        (Some(at), at.checked_add_signed(chrono::Duration::milliseconds(1)))
+    } else {
+        return Err(syntax_error(format!("Tanggal '{}' tidak valid, gunakan YYYY-MM-DD", value)));
+    };
+
+    let (from, to) = match op {
+        CmpOp::Eq => (start, end),
+        CmpOp::Gt => (end, None),
+        CmpOp::Gte => (start, None),
+        CmpOp::Lt => (None, start),
+        CmpOp::Lte => (None, end),
+    };
+    Ok(Filter::Date { field, from, to })
+}
+
+fn parse_attribute(word: &str) -> Result<Filter, AppError> {
+    let (name, value) = word.split_once(':')
+        .ok_or_else(|| syntax_error(format!("Atribut '{}' hanya mendukung ':'", word)))?;
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        return Err(syntax_error(format!("Nama atribut '{}' tidak valid", name)));
+    }
+    let pattern = unquote(value);
+    if pattern.is_empty() {
+        return Err(syntax_error(format!("Nilai untuk 'attr.{}' kosong", name)));
+    }
+    Ok(Filter::Attribute { name: name.to_string(), pattern })
+}
+
+fn parse_filter(word: &str) -> Result<Filter, AppError> {
+    if let Some(rest) = word.strip_prefix("attr.") {
+        return parse_attribute(rest);
+    }
+    let field_len = word.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(word.len());
+    let (field, rest) = word.split_at(field_len);
+    let op = [(">=", CmpOp::Gte), ("<=", CmpOp::Lte), (":", CmpOp::Eq), (">", CmpOp::Gt), ("<", CmpOp::Lt)]
+        .into_iter()
+        .find(|(symbol, _)| rest.starts_with(symbol));
+
+    let Some((symbol, op)) = op.filter(|_| !field.is_empty()) else {
+        return Ok(Filter::Text(unquote(word)));
+    };
+    let value = unquote(&rest[symbol.len()..]);
+    if value.is_empty() {
+        return Err(syntax_error(format!("Nilai untuk '{}' kosong", field)));
+    }
+
+    match (field, op) {
+        ("name", CmpOp::Eq) => Ok(Filter::Match { field: TextField::Name, pattern: value }),
+        ("email", CmpOp::Eq) => Ok(Filter::Match { field: TextField::Email, pattern: value }),
+        ("id", op) => value.parse()
+            .map(|value| Filter::Id { op, value })
+            .map_err(|_| syntax_error(format!("Id '{}' bukan angka", value))),
+        ("created", op) => parse_date(&value, op, DateField::Created),
+        ("updated", op) => parse_date(&value, op, DateField::Updated),
+        ("name" | "email", _) => Err(syntax_error(format!("Field '{}' hanya mendukung ':'", field))),
+        _ => Err(syntax_error(format!("Field '{}' tidak dikenal", field))),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    terms: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        self.pos += 1;
+        self.tokens.get(self.pos - 1)
+    }
+
+    //or := and ("OR" and)*
+    fn parse_or(&mut self) -> Result<SearchExpr, AppError> {
+        let mut items = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            items.push(self.parse_and()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { SearchExpr::Or(items) })
+    }
+
+    //and := unary (("AND")? unary)*, kata yang berdampingan otomatis AND
+    fn parse_and(&mut self) -> Result<SearchExpr, AppError> {
+        let mut items = vec![self.parse_unary()?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.next();
+                }
+                Some(Token::LParen | Token::Not | Token::Word(_)) => {}
+                _ => break,
+            }
+            items.push(self.parse_unary()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { SearchExpr::And(items) })
+    }
+
+    fn parse_unary(&mut self) -> Result<SearchExpr, AppError> {
+        match self.next() {
+            Some(Token::Not) => Ok(SearchExpr::Not(Box::new(self.parse_unary()?))),
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(syntax_error("Kurung tidak ditutup".to_string())),
+                }
+            }
+            Some(Token::Word(word)) => {
+                let word = word.clone();
+                self.terms += 1;
+                if self.terms > MAX_TERMS {
+                    return Err(syntax_error(format!("Maksimal {} kata pencarian", MAX_TERMS)));
+                }
+                Ok(SearchExpr::Filter(parse_filter(&word)?))
+            }
+            Some(token) => Err(syntax_error(format!("Token {:?} tidak diharapkan", token))),
+            None => Err(syntax_error("Query berakhir terlalu cepat".to_string())),
+        }
+    }
+}
+
+//atribut yang tidak terdaftar di attribute_definitions ditolak seperti field yang tidak dikenal
+pub fn check_attributes(expr: &SearchExpr, known: &[&str]) -> Result<(), AppError> {
+    match expr.attribute_names().into_iter().find(|name| !known.contains(name)) {
+        Some(name) => Err(syntax_error(format!("Atribut '{}' tidak dikenal", name))),
+        None => Ok(()),
+    }
+}
+
+//contoh: name:budi email:*@corp.com created>2025-01-01 (budi OR santi) -email:*@test.com attr.department:eng
+pub fn parse_search(input: &str) -> Result<SearchExpr, AppError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(SearchExpr::And(Vec::new()));
+    }
+    if input.chars().count() > MAX_QUERY_LEN {
+        return Err(syntax_error(format!("Query maksimal {} karakter", MAX_QUERY_LEN)));
+    }
+
+    let mut parser = Parser { tokens: tokenize(input)?, pos: 0, terms: 0 };
+    let expr = parser.parse_or()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(syntax_error("Kurung tutup tanpa pasangan".to_string()));
+    }
+    Ok(expr)
+}
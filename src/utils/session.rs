@@ -0,0 +1,91 @@
+use axum_extra::extract::{CookieJar, cookie::{Cookie, SameSite}};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{MySql, MySqlExecutor, Pool};
+use uuid::Uuid;
+
+use crate::{errors::app_error::AppError, models::{audit_model::AuditAction, outbox_model::DomainEventKind, totp_model::TwoFactorChallenge, user_model::{Claims, TokenType}}, utils::{audit::{AuditContext, append}, client_info::ClientInfo, outbox::enqueue, utils::{create_jwt, create_token, load_config}}};
+
+pub fn new_session_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+//cookie yang hanya dibaca server: tidak terlihat JavaScript dan tidak ikut request lintas situs selain navigasi
+pub fn server_cookie(name: &'static str, value: String) -> Result<Cookie<'static>, AppError> {
+    let secure = load_config()?.server.secure_cookies;
+    Ok(Cookie::build((name, value)).http_only(true).secure(secure).same_site(SameSite::Lax).path("/").build())
+}
+
+//path harus sama dengan cookie dari start_session agar browser benar-benar menghapusnya
+pub fn remove_session_cookie(jar: CookieJar) -> CookieJar {
+    jar.remove(Cookie::build("jwt").path("/"))
+}
+
+//user dengan 2FA aktif mendapat challenge token, ditukar dengan session lewat POST /login/totp
+pub fn two_factor_challenge(user_id: u64) -> Result<TwoFactorChallenge, AppError> {
+    let (challenge_token, claims) = create_token(user_id, TokenType::Challenge, None)?;
+    Ok(TwoFactorChallenge { challenge_token, expires_in: claims.exp - claims.iat })
+}
+
+//langkah terakhir semua metode login: buat session dan cookie jwt
+pub async fn start_session(pool: &Pool<MySql>, user_id: u64, client: &ClientInfo) -> Result<CookieJar, AppError> {
+    //user yang sudah dihapus tidak boleh mendapat session dari metode login mana pun
+    let active: Option<(u64,)> = sqlx::query_as("SELECT id FROM users WHERE id = ? AND deleted_at IS NULL")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+    if active.is_none() {
+        return Err(AppError::Unauthorized);
+    }
+
+    let sid = new_session_id();
+    let (token, claims) = create_jwt(user_id, &sid)?;
+    let expires_at = DateTime::from_timestamp(claims.exp, 0).ok_or(AppError::InternalServerError)?;
+    //session, audit dan event login ditulis bersama
+    let mut tx = pool.begin().await?;
+    create_session(&mut *tx, user_id, &sid, &claims.jti, expires_at, client).await?;
+    append(&mut tx, &AuditContext::user(user_id, client), AuditAction::Login, Some(user_id), None).await?;
+    enqueue(&mut tx, DomainEventKind::UserLoggedIn, user_id, json!({ "ip_address": client.ip_address, "user_agent": client.user_agent })).await?;
+    tx.commit().await?;
+
+    Ok(CookieJar::new().add(server_cookie("jwt", token)?))
+}
+
+//mencatat session baru di server, id session dipakai sebagai claim sid di jwt
+pub async fn create_session<'e>(executor: impl MySqlExecutor<'e>, user_id: u64, sid: &str, jti: &str, expires_at: DateTime<Utc>, client: &ClientInfo) -> Result<(), AppError> {
+    sqlx::query("INSERT INTO sessions (id, user_id, jti, expires_at, user_agent, ip_address, last_seen_at) VALUES (?, ?, ?, ?, ?, ?, NOW())")
+        .bind(sid)
+        .bind(user_id)
+        .bind(jti)
+        .bind(expires_at)
+        .bind(&client.user_agent)
+        .bind(&client.ip_address)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+//token hanya berlaku jika session-nya masih ada, belum dicabut dan belum kedaluwarsa,
+//sekaligus memperbarui last_seen_at (rows_affected memakai found rows)
+//session user yang di-soft delete sudah dicabut bersamaan dengan delete
+pub async fn touch_session(pool: &Pool<MySql>, claims: &Claims) -> Result<bool, AppError> {
+    let Some(sid) = claims.sid.as_deref() else {
+        return Ok(false);
+    };
+    let result = sqlx::query("UPDATE sessions SET last_seen_at = NOW() WHERE id = ? AND user_id = ? AND revoked_at IS NULL AND expires_at > NOW()")
+        .bind(sid)
+        .bind(claims.sub)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+//session tidak dihapus agar riwayat session tetap ada, false jika bukan milik user atau sudah dicabut
+pub async fn revoke_session(pool: &Pool<MySql>, user_id: u64, sid: &str) -> Result<bool, AppError> {
+    let result = sqlx::query("UPDATE sessions SET revoked_at = NOW() WHERE id = ? AND user_id = ? AND revoked_at IS NULL")
+        .bind(sid)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,77 @@
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::{Rng, RngCore, rngs::OsRng};
+use sha1::Sha1;
+
+use crate::models::config_model::TotpConfig;
+
+type HmacSha1 = Hmac<Sha1>;
+
+//karakter kode pemulihan, tanpa karakter yang mirip (0/o, 1/l/i)
+const RECOVERY_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+//secret 160 bit sesuai rekomendasi RFC 4226, dalam base32 untuk aplikasi authenticator
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    OsRng.fill_bytes(&mut bytes);
+    BASE32_NOPAD.encode(&bytes)
+}
+
+//HOTP (RFC 4226) dengan dynamic truncation
+pub fn hotp(key: &[u8], counter: u64, digits: u32) -> String {
+    let mut mac = HmacSha1::new_from_slice(key).expect("HMAC menerima key dengan panjang berapa pun");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+    let code = binary % 10u32.pow(digits);
+    format!("{:0width$}", code, width = digits as usize)
+}
+
+//mengembalikan time step yang cocok, dipakai untuk mencegah kode yang sama dipakai ulang
+pub fn verify_code(cfg: &TotpConfig, secret: &str, code: &str, unix_time: u64) -> Option<u64> {
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let code = code.trim();
+    if code.len() != cfg.digits as usize {
+        return None;
+    }
+
+    let current = unix_time / cfg.period;
+    (current.saturating_sub(cfg.skew)..=current + cfg.skew)
+        .find(|step| hotp(&key, *step, cfg.digits) == code)
+}
+
+//uri otpauth:// untuk QR code di aplikasi authenticator
+pub fn provisioning_uri(cfg: &TotpConfig, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(&cfg.issuer),
+        percent_encode(account),
+        secret,
+        percent_encode(&cfg.issuer),
+        cfg.digits,
+        cfg.period,
+    )
+}
+
+pub fn generate_recovery_code() -> String {
+    let mut rng = OsRng;
+    let mut code: String = (0..10)
+        .map(|_| RECOVERY_ALPHABET[rng.gen_range(0..RECOVERY_ALPHABET.len())] as char)
+        .collect();
+    code.insert(5, '-');
+    code
+}
+
+//kode pemulihan dibandingkan tanpa tanda hubung dan huruf besar
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase()
+}
+
+fn percent_encode(value: &str) -> String {
+    value.bytes().map(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => (b as char).to_string(),
+        _ => format!("%{:02X}", b),
+    }).collect()
+}
@@ -1,23 +1,24 @@
-use std::borrow::Cow;
-use argon2::{PasswordHash, PasswordVerifier};
-use argon2::password_hash::Error as PasswordHashError;
-use argon2::{Argon2, password_hash::{SaltString, rand_core::OsRng, PasswordHasher}};
+use std::{borrow::Cow, sync::{Arc, PoisonError, RwLock}};
 use chrono::{Duration, Utc};
-use config::{Config, File, FileFormat};
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, decode, encode};
-use validator::ValidationError;
+use config::{Config, ConfigError, File, FileFormat};
+use serde::{Deserialize, Deserializer};
+use sqlx::{MySql, Pool};
+use uuid::Uuid;
+use validator::{ValidationError, ValidationErrors};
 
 
 use crate::errors::app_error::AppError;
-use crate::models::config_model::AppConfig;
-use crate::models::user_model::{Claims};
-
-//untuk hashing password menggunakan argon2
-pub async fn hashing_password(password:&str)->Result<String,PasswordHashError>{
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    let password_hash = argon2.hash_password(password.as_bytes(), &salt)?.to_string();
-    Ok(password_hash)
+use crate::middlewares::cors_middleware::check_cors_config;
+use crate::models::config_model::{AppConfig, JwtConfig, TotpConfig};
+use crate::models::user_model::{Claims, TokenType};
+use crate::utils::jwt_keys::JwtKeys;
+use crate::utils::password_hash::{hash_blocking, run_limited, verify_blocking};
+
+//untuk hashing password menggunakan argon2id sesuai config, dijalankan di luar executor async
+pub async fn hashing_password(password:&str)->Result<String,AppError>{
+    let cfg = load_config()?.password_hash;
+    let password = password.to_string();
+    run_limited(cfg.max_concurrent, move || hash_blocking(&cfg, &password)).await
 }
 
 //untuk mengecek apakah email sudah terdaftar
@@ -30,6 +31,14 @@ pub async fn check_email(email:&str)->Result<bool, AppError>{
 }
 
 
+//untuk mengecek apakah user sudah mengaktifkan 2FA
+pub async fn is_totp_enabled(pool: &Pool<MySql>, user_id: u64) -> Result<bool, AppError> {
+    let result: (bool,) = sqlx::query_as("SELECT totp_enabled_at IS NOT NULL FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_one(pool).await?;
+    Ok(result.0)
+}
+
 //untuk validasi tld email
 pub fn validate_email_tld(email:&str)->Result<(), ValidationError>{
     //simple check format email
@@ -52,50 +61,117 @@ pub fn validate_email_tld(email:&str)->Result<(), ValidationError>{
     Ok(())
 }
 
+//pesan validasi per field, dipakai laporan per baris/per operasi
+pub fn validation_messages(errors: &ValidationErrors) -> Vec<String> {
+    errors.field_errors().into_iter()
+        .flat_map(|(field, errors)| errors.iter().map(move |e| match &e.message {
+            Some(message) => format!("{}: {}", field, message),
+            None => format!("{}: {}", field, e.code),
+        }))
+        .collect()
+}
+
+//untuk field opsional yang boleh tidak dikirim tetapi tidak boleh null
+pub fn non_null<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
 pub fn load_config() -> Result<AppConfig, AppError> {
-    let config = Config::builder()
+    let config: AppConfig = Config::builder()
         .add_source(File::new("config.yaml", FileFormat::Yaml))
         .build()?
         .try_deserialize()?;
+    check_totp_config(&config.totp)?;
+    check_cors_config(&config.cors).map_err(ConfigError::Message)?;
     Ok(config)
 }
 
-pub fn create_jwt(user_id: u64) -> Result<String, AppError> {
+//digits di luar 6..=8 membuat 10^digits overflow di hotp, period 0 membuat pembagian dengan nol
+pub fn check_totp_config(cfg: &TotpConfig) -> Result<(), ConfigError> {
+    if !(6..=8).contains(&cfg.digits) {
+        return Err(ConfigError::Message(format!("totp.digits harus 6 sampai 8, bukan {}", cfg.digits)));
+    }
+    if cfg.period == 0 {
+        return Err(ConfigError::Message("totp.period harus lebih dari 0".to_string()));
+    }
+    Ok(())
+}
+
+//kunci jwt dibangun sekali dan dipakai ulang setiap request, diganti lewat reload_jwt_keys
+static JWT_KEYS: RwLock<Option<Arc<JwtKeys>>> = RwLock::new(None);
+
+pub fn load_jwt_keys() -> Result<Arc<JwtKeys>, AppError> {
+    if let Some(keys) = JWT_KEYS.read().unwrap_or_else(PoisonError::into_inner).as_ref() {
+        return Ok(keys.clone());
+    }
+    reload_jwt_keys()
+}
+
+//membaca ulang config dan file PEM (SIGHUP saat rotasi kunci), jika gagal kunci lama tetap dipakai
+pub fn reload_jwt_keys() -> Result<Arc<JwtKeys>, AppError> {
     let conf = load_config()?;
-    let expiration = Utc::now()
-        .checked_add_signed(Duration::hours(24))
+    let keys = Arc::new(JwtKeys::from_config(&conf.jwt, &conf.jwt_secret)?);
+    *JWT_KEYS.write().unwrap_or_else(PoisonError::into_inner) = Some(keys.clone());
+    Ok(keys)
+}
+
+//membuat claims lengkap (iat, nbf, jti, iss, aud) dengan masa berlaku sesuai jenis token
+pub fn build_claims(jwt: &JwtConfig, keys: &JwtKeys, user_id: u64, typ: TokenType, sid: Option<&str>) -> Claims {
+    let now = Utc::now();
+    let expiration = now
+        .checked_add_signed(Duration::seconds(jwt.lifetimes.for_type(typ)))
         .expect("Valid TimeStamp")
         .timestamp();
 
-    let claims = Claims{
+    Claims{
         sub: user_id.to_owned(),
         exp: expiration,
-    };
-
-    let encoded = encode(&Header::default(), &claims, &EncodingKey::from_secret(conf.jwt_secret.as_ref()))?;
-    Ok(encoded)
+        iat: now.timestamp(),
+        nbf: now.timestamp(),
+        jti: Uuid::new_v4().to_string(),
+        iss: keys.issuer().map(str::to_string),
+        aud: keys.audience().map(str::to_string),
+        sid: sid.map(str::to_string),
+        typ,
+    }
 }
 
-pub async fn verify_password(hash: &str, password: &str) -> Result<bool, AppError> {
-    let parsed_hash = PasswordHash::new(hash)
-        .map_err(|_|AppError::Unauthorized)?;
+pub fn create_token(user_id: u64, typ: TokenType, sid: Option<&str>) -> Result<(String, Claims), AppError> {
+    let conf = load_config()?;
+    let keys = load_jwt_keys()?;
+    let claims = build_claims(&conf.jwt, &keys, user_id, typ, sid);
+    Ok((keys.sign(&claims)?, claims))
+}
 
-    let is_valid = Argon2::default()
-        .verify_password(password.as_bytes(), &parsed_hash)
-        .is_ok();
+//access token untuk cookie jwt, selalu terikat ke session di server
+pub fn create_jwt(user_id: u64, sid: &str) -> Result<(String, Claims), AppError> {
+    create_token(user_id, TokenType::Access, Some(sid))
+}
 
-    Ok(is_valid)
+//user tanpa password (login lewat OIDC) selalu gagal verifikasi password
+pub async fn verify_password(hash: Option<&str>, password: &str) -> Result<bool, AppError> {
+    let Some(hash) = hash.filter(|h| !h.is_empty()) else {
+        return Ok(false);
+    };
+    let cfg = load_config()?.password_hash;
+    let (hash, password) = (hash.to_string(), password.to_string());
+    run_limited(cfg.max_concurrent, move || verify_blocking(&cfg, &hash, &password)).await
 }
 
 pub async fn jwt_verify(token: &str) -> Result<Claims, AppError> {
-    let conf = load_config()?;
-    let decoding_key = DecodingKey::from_secret(conf.jwt_secret.as_ref());
+    jwt_verify_type(token, TokenType::Access).await
+}
 
-    let token_data = decode::<Claims>(
-        token,
-        &decoding_key,
-        &jsonwebtoken::Validation::default()
-    ).map_err(|_| AppError::Unauthorized)?; // error kalau token invalid
+pub async fn jwt_verify_type(token: &str, typ: TokenType) -> Result<Claims, AppError> {
+    let keys = load_jwt_keys()?;
+    let claims = keys.verify::<Claims>(token)?; // error kalau token invalid
 
-    Ok(token_data.claims)
+    if claims.typ != typ {
+        return Err(AppError::Unauthorized);
+    }
+    Ok(claims)
 }
\ No newline at end of file
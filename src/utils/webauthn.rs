@@ -0,0 +1,248 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ciborium::Value;
+use p256::ecdsa::{Signature as P256Signature, VerifyingKey as P256VerifyingKey, signature::Verifier};
+use rand::{RngCore, rngs::OsRng};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::{errors::app_error::AppError, models::config_model::WebauthnConfig};
+
+//flag di authenticator data (WebAuthn level 2, 6.1)
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_DATA: u8 = 0x40;
+
+//algoritma COSE yang didukung
+pub const COSE_ES256: i64 = -7;
+pub const COSE_EDDSA: i64 = -8;
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    typ: String,
+    challenge: String,
+    origin: String,
+}
+
+struct AuthData {
+    rp_id_hash: Vec<u8>,
+    flags: u8,
+    sign_count: u32,
+    credential: Option<AttestedCredential>,
+}
+
+struct AttestedCredential {
+    credential_id: Vec<u8>,
+    public_key: Vec<u8>,
+}
+
+pub enum CosePublicKey {
+    Es256(P256VerifyingKey),
+    EdDsa(ed25519_dalek::VerifyingKey),
+}
+
+pub struct RegisteredCredential {
+    pub credential_id: Vec<u8>,
+    pub public_key: Vec<u8>,
+    pub sign_count: u32,
+}
+
+pub fn generate_challenge() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+pub fn decode_b64(value: &str) -> Result<Vec<u8>, AppError> {
+    URL_SAFE_NO_PAD.decode(value.trim_end_matches('='))
+        .map_err(|_| AppError::BadRequest)
+}
+
+//alasan penolakan dicatat di log, klien hanya menerima 401
+fn reject(reason: &str) -> AppError {
+    eprintln!("WEBAUTHN: {}", reason);
+    AppError::Unauthorized
+}
+
+fn verify_client_data(cfg: &WebauthnConfig, raw: &[u8], typ: &str, challenge: &str) -> Result<(), AppError> {
+    let client_data: ClientData = serde_json::from_slice(raw).map_err(|_| AppError::BadRequest)?;
+    if client_data.typ != typ {
+        return Err(reject("tipe client data salah"));
+    }
+    if client_data.challenge.trim_end_matches('=') != challenge {
+        return Err(reject("challenge tidak cocok"));
+    }
+    if !cfg.origins.iter().any(|o| o == &client_data.origin) {
+        return Err(reject("origin tidak diizinkan"));
+    }
+    Ok(())
+}
+
+fn parse_auth_data(bytes: &[u8]) -> Result<AuthData, AppError> {
+    if bytes.len() < 37 {
+        return Err(AppError::BadRequest);
+    }
+    let flags = bytes[32];
+    let sign_count = u32::from_be_bytes([bytes[33], bytes[34], bytes[35], bytes[36]]);
+
+    let credential = if flags & FLAG_ATTESTED_DATA != 0 {
+        //aaguid (16) + panjang credential id (2) + credential id + COSE public key
+        let rest = bytes.get(37..).ok_or(AppError::BadRequest)?;
+        if rest.len() < 18 {
+            return Err(AppError::BadRequest);
+        }
+        let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+        let credential_id = rest.get(18..18 + id_len).ok_or(AppError::BadRequest)?.to_vec();
+
+        let mut key_reader = rest.get(18 + id_len..).ok_or(AppError::BadRequest)?;
+        let before = key_reader.len();
+        let _: Value = ciborium::from_reader(&mut key_reader).map_err(|_| AppError::BadRequest)?;
+        let key_start = 18 + id_len;
+        let public_key = rest[key_start..key_start + (before - key_reader.len())].to_vec();
+
+        Some(AttestedCredential { credential_id, public_key })
+    } else {
+        None
+    };
+
+    Ok(AuthData {
+        rp_id_hash: bytes[..32].to_vec(),
+        flags,
+        sign_count,
+        credential,
+    })
+}
+
+fn verify_auth_data(cfg: &WebauthnConfig, auth_data: &AuthData) -> Result<(), AppError> {
+    if auth_data.rp_id_hash != Sha256::digest(cfg.rp_id.as_bytes()).as_slice() {
+        return Err(reject("rp id hash tidak cocok"));
+    }
+    if auth_data.flags & FLAG_USER_PRESENT == 0 {
+        return Err(reject("user tidak hadir (UP)"));
+    }
+    if cfg.require_user_verification && auth_data.flags & FLAG_USER_VERIFIED == 0 {
+        return Err(reject("user tidak terverifikasi (UV)"));
+    }
+    Ok(())
+}
+
+fn map_get(map: &[(Value, Value)], key: i64) -> Option<&Value> {
+    map.iter()
+        .find(|(k, _)| k.as_integer().map(i128::from) == Some(key as i128))
+        .map(|(_, v)| v)
+}
+
+fn map_get_text<'a>(map: &'a [(Value, Value)], key: &str) -> Option<&'a Value> {
+    map.iter().find(|(k, _)| k.as_text() == Some(key)).map(|(_, v)| v)
+}
+
+//COSE_Key (RFC 9053): kty 2/crv 1 = P-256, kty 1/crv 6 = Ed25519
+pub fn parse_cose_key(bytes: &[u8]) -> Result<(i64, CosePublicKey), AppError> {
+    let value: Value = ciborium::from_reader(bytes).map_err(|_| AppError::BadRequest)?;
+    let map = value.as_map().ok_or(AppError::BadRequest)?;
+    let int = |key| map_get(map, key).and_then(|v| v.as_integer()).map(i128::from);
+    let bytes_at = |key| map_get(map, key).and_then(|v| v.as_bytes());
+
+    match (int(1), int(3), int(-1)) {
+        (Some(2), Some(alg), Some(1)) if alg == COSE_ES256 as i128 => {
+            let x = bytes_at(-2).ok_or(AppError::BadRequest)?;
+            let y = bytes_at(-3).ok_or(AppError::BadRequest)?;
+            let mut sec1 = vec![0x04];
+            sec1.extend_from_slice(x);
+            sec1.extend_from_slice(y);
+            let key = P256VerifyingKey::from_sec1_bytes(&sec1).map_err(|_| AppError::BadRequest)?;
+            Ok((COSE_ES256, CosePublicKey::Es256(key)))
+        }
+        (Some(1), Some(alg), Some(6)) if alg == COSE_EDDSA as i128 => {
+            let x: [u8; 32] = bytes_at(-2).ok_or(AppError::BadRequest)?
+                .as_slice().try_into().map_err(|_| AppError::BadRequest)?;
+            let key = ed25519_dalek::VerifyingKey::from_bytes(&x).map_err(|_| AppError::BadRequest)?;
+            Ok((COSE_EDDSA, CosePublicKey::EdDsa(key)))
+        }
+        _ => Err(reject("algoritma public key tidak didukung")),
+    }
+}
+
+fn verify_signature(key: &CosePublicKey, message: &[u8], signature: &[u8]) -> bool {
+    match key {
+        //signature ES256 WebAuthn berformat DER
+        CosePublicKey::Es256(key) => P256Signature::from_der(signature)
+            .map(|sig| key.verify(message, &sig).is_ok())
+            .unwrap_or(false),
+        CosePublicKey::EdDsa(key) => ed25519_dalek::Signature::from_slice(signature)
+            .map(|sig| key.verify_strict(message, &sig).is_ok())
+            .unwrap_or(false),
+    }
+}
+
+fn signed_message(auth_data: &[u8], client_data_json: &[u8]) -> Vec<u8> {
+    let mut message = auth_data.to_vec();
+    message.extend_from_slice(&Sha256::digest(client_data_json));
+    message
+}
+
+//verifikasi ceremony registrasi, format attestation yang diterima: none dan packed self-attestation
+pub fn verify_registration(cfg: &WebauthnConfig, challenge: &str, client_data_json: &[u8], attestation_object: &[u8]) -> Result<RegisteredCredential, AppError> {
+    verify_client_data(cfg, client_data_json, "webauthn.create", challenge)?;
+
+    let attestation: Value = ciborium::from_reader(attestation_object).map_err(|_| AppError::BadRequest)?;
+    let attestation = attestation.as_map().ok_or(AppError::BadRequest)?;
+    let fmt = map_get_text(attestation, "fmt").and_then(|v| v.as_text()).ok_or(AppError::BadRequest)?;
+    let att_stmt = map_get_text(attestation, "attStmt").and_then(|v| v.as_map()).ok_or(AppError::BadRequest)?;
+    let raw_auth_data = map_get_text(attestation, "authData").and_then(|v| v.as_bytes()).ok_or(AppError::BadRequest)?;
+
+    let auth_data = parse_auth_data(raw_auth_data)?;
+    verify_auth_data(cfg, &auth_data)?;
+    let credential = auth_data.credential.ok_or_else(|| reject("authenticator data tanpa credential"))?;
+    let (alg, public_key) = parse_cose_key(&credential.public_key)?;
+
+    match fmt {
+        "none" if att_stmt.is_empty() => (),
+        "packed" if map_get_text(att_stmt, "x5c").is_none() => {
+            let stmt_alg = map_get_text(att_stmt, "alg").and_then(|v| v.as_integer()).map(i128::from);
+            let sig = map_get_text(att_stmt, "sig").and_then(|v| v.as_bytes()).ok_or(AppError::BadRequest)?;
+            if stmt_alg != Some(alg as i128) {
+                return Err(reject("algoritma attestation tidak cocok"));
+            }
+            if !verify_signature(&public_key, &signed_message(raw_auth_data, client_data_json), sig) {
+                return Err(reject("signature attestation tidak valid"));
+            }
+        }
+        _ => return Err(reject("format attestation tidak didukung")),
+    }
+
+    Ok(RegisteredCredential {
+        credential_id: credential.credential_id,
+        public_key: credential.public_key,
+        sign_count: auth_data.sign_count,
+    })
+}
+
+//verifikasi ceremony login, mengembalikan sign counter baru
+pub fn verify_assertion(
+    cfg: &WebauthnConfig,
+    challenge: &str,
+    public_key: &[u8],
+    stored_sign_count: u32,
+    client_data_json: &[u8],
+    authenticator_data: &[u8],
+    signature: &[u8],
+) -> Result<u32, AppError> {
+    verify_client_data(cfg, client_data_json, "webauthn.get", challenge)?;
+
+    let auth_data = parse_auth_data(authenticator_data)?;
+    verify_auth_data(cfg, &auth_data)?;
+
+    let (_, key) = parse_cose_key(public_key)?;
+    if !verify_signature(&key, &signed_message(authenticator_data, client_data_json), signature) {
+        return Err(reject("signature assertion tidak valid"));
+    }
+
+    //counter 0 berarti authenticator tidak memakai counter (umum pada passkey yang disinkronkan)
+    let counter_used = auth_data.sign_count != 0 || stored_sign_count != 0;
+    if counter_used && auth_data.sign_count <= stored_sign_count {
+        return Err(reject("sign counter mundur, kemungkinan authenticator diklon"));
+    }
+
+    Ok(auth_data.sign_count)
+}